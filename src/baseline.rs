@@ -0,0 +1,415 @@
+//! Baseline estimation for sloped or curved backgrounds
+
+use std::collections::VecDeque;
+
+use crate::data::map_in_x_order;
+use crate::fit;
+use crate::smooth::moving_average;
+
+/// Default, smallest and largest degree of the polynomial baseline
+pub const DEFAULT_DEGREE: usize = 3;
+pub const MIN_DEGREE: usize = 1;
+pub const MAX_DEGREE: usize = 8;
+
+/// Default, smallest and largest feature scale of the ALS and rolling-ball
+/// baselines, as a fraction of the points
+pub const DEFAULT_WINDOW: f64 = 0.05;
+pub const MIN_WINDOW: f64 = 0.005;
+pub const MAX_WINDOW: f64 = 0.5;
+
+/// Weight given to points above the ALS baseline
+const ALS_ASYMMETRY: f64 = 0.01;
+
+/// Largest ALS smoothness; beyond it the banded Cholesky pivots lose all
+/// precision on long data sets
+const ALS_MAX_LAMBDA: f64 = 1e9;
+
+/// Algorithm used to estimate the baseline
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BaselineMethod {
+    /// Polynomial refitted to the points below it until it stops moving
+    Polynomial,
+    /// Asymmetric least squares (Eilers & Boelens)
+    Als,
+    /// Minimum/maximum filter opening, smoothed with a moving average
+    RollingBall,
+}
+
+impl BaselineMethod {
+    /// Cycle off → polynomial → ALS → rolling ball → off
+    pub fn cycle(method: Option<BaselineMethod>) -> Option<BaselineMethod> {
+        match method {
+            None => Some(BaselineMethod::Polynomial),
+            Some(BaselineMethod::Polynomial) => Some(BaselineMethod::Als),
+            Some(BaselineMethod::Als) => Some(BaselineMethod::RollingBall),
+            Some(BaselineMethod::RollingBall) => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BaselineMethod::Polynomial => "polynomial",
+            BaselineMethod::Als => "ALS",
+            BaselineMethod::RollingBall => "rolling ball",
+        }
+    }
+
+    /// Whether the polynomial degree, rather than the window, tunes it
+    pub fn uses_degree(self) -> bool {
+        self == BaselineMethod::Polynomial
+    }
+
+    /// Settings used for `points` data points, for display
+    pub fn describe(self, points: usize, settings: BaselineSettings) -> String {
+        match self {
+            BaselineMethod::Polynomial => format!("polynomial, degree {}", settings.degree),
+            BaselineMethod::Als => format!(
+                "ALS, λ = {:.1e}, p = {}",
+                als_lambda(settings.radius(points)),
+                ALS_ASYMMETRY
+            ),
+            BaselineMethod::RollingBall => {
+                format!("rolling ball, radius {} pts", settings.radius(points))
+            }
+        }
+    }
+}
+
+/// User-adjustable baseline settings
+#[derive(Clone, Copy)]
+pub struct BaselineSettings {
+    /// Degree of the polynomial baseline
+    pub degree: usize,
+    /// Feature scale of the ALS and rolling-ball baselines, as a fraction of the points
+    pub window: f64,
+}
+
+impl Default for BaselineSettings {
+    fn default() -> Self {
+        BaselineSettings {
+            degree: DEFAULT_DEGREE,
+            window: DEFAULT_WINDOW,
+        }
+    }
+}
+
+impl BaselineSettings {
+    /// Half-width in points of the ALS and rolling-ball feature scale
+    pub fn radius(self, points: usize) -> usize {
+        ((points as f64 * self.window) as usize).max(2)
+    }
+}
+
+/// Baseline value under every point of `data`, in the same order
+///
+/// Returns `None` when the ALS system is too ill-conditioned to solve.
+pub fn estimate(
+    data: &[(f64, f64)],
+    method: BaselineMethod,
+    settings: BaselineSettings,
+) -> Option<Vec<f64>> {
+    let radius = settings.radius(data.len());
+    let mut solved = true;
+    let baseline = map_in_x_order(data, |x, y| match method {
+        BaselineMethod::Polynomial => polynomial(x, y, settings.degree),
+        BaselineMethod::Als => asymmetric_least_squares(y, als_lambda(radius), ALS_ASYMMETRY)
+            .unwrap_or_else(|| {
+                solved = false;
+                Vec::new()
+            }),
+        BaselineMethod::RollingBall => rolling_ball(y, radius),
+    });
+    solved.then_some(baseline)
+}
+
+/// ALS smoothness; the second-difference penalty bends over about λ^¼ points,
+/// capped so long data sets stay solvable
+fn als_lambda(radius: usize) -> f64 {
+    (2.0 * radius as f64).powi(4).min(ALS_MAX_LAMBDA)
+}
+
+/// Iterative polynomial fit that clips the data to the fit after every pass
+fn polynomial(x: &[f64], y: &[f64], degree: usize) -> Vec<f64> {
+    let (Some(&x0), Some(&x1)) = (x.first(), x.last()) else {
+        return Vec::new();
+    };
+    // Map X onto [-1, 1] to keep the normal equations well conditioned
+    let span = if x1 > x0 { x1 - x0 } else { 1.0 };
+    let t: Vec<f64> = x.iter().map(|&v| 2.0 * (v - x0) / span - 1.0).collect();
+    let y_range = y.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        - y.iter().copied().fold(f64::INFINITY, f64::min);
+
+    let mut target = y.to_vec();
+    let mut fitted = target.clone();
+    for _ in 0..100 {
+        let Some(coefficients) = polyfit(&t, &target, degree.min(t.len().saturating_sub(1))) else {
+            break;
+        };
+        fitted = t
+            .iter()
+            .map(|&v| coefficients.iter().rev().fold(0.0, |acc, c| acc * v + c))
+            .collect();
+
+        let mut clipped = 0.0;
+        for (value, &fit) in target.iter_mut().zip(&fitted) {
+            if *value > fit {
+                clipped += *value - fit;
+                *value = fit;
+            }
+        }
+        if clipped <= 1e-6 * y_range * t.len() as f64 {
+            break;
+        }
+    }
+    fitted
+}
+
+/// Least-squares polynomial coefficients, constant term first
+fn polyfit(t: &[f64], y: &[f64], degree: usize) -> Option<Vec<f64>> {
+    let m = degree + 1;
+    let mut normal = vec![vec![0.0; m]; m];
+    let mut rhs = vec![0.0; m];
+    for (&v, &target) in t.iter().zip(y) {
+        let powers: Vec<f64> = (0..m).map(|i| v.powi(i as i32)).collect();
+        for i in 0..m {
+            rhs[i] += powers[i] * target;
+            for j in 0..m {
+                normal[i][j] += powers[i] * powers[j];
+            }
+        }
+    }
+    fit::solve(normal, rhs)
+}
+
+/// Asymmetric least squares: a smooth curve that points above it barely pull on
+fn asymmetric_least_squares(y: &[f64], lambda: f64, p: f64) -> Option<Vec<f64>> {
+    let n = y.len();
+    if n < 3 {
+        return Some(y.to_vec());
+    }
+
+    // Bands of λDᵀD for the second-difference matrix D
+    let mut diagonal = vec![0.0; n];
+    let mut first = vec![0.0; n - 1];
+    let mut second = vec![0.0; n - 2];
+    for k in 0..n - 2 {
+        let c = [1.0, -2.0, 1.0];
+        for a in 0..3 {
+            diagonal[k + a] += lambda * c[a] * c[a];
+        }
+        first[k] += lambda * c[0] * c[1];
+        first[k + 1] += lambda * c[1] * c[2];
+        second[k] += lambda * c[0] * c[2];
+    }
+
+    let mut weights = vec![1.0; n];
+    let mut z = y.to_vec();
+    for _ in 0..10 {
+        let weighted_diagonal: Vec<f64> =
+            diagonal.iter().zip(&weights).map(|(d, w)| d + w).collect();
+        let rhs: Vec<f64> = y.iter().zip(&weights).map(|(v, w)| v * w).collect();
+        z = solve_pentadiagonal(&weighted_diagonal, &first, &second, &rhs)?;
+        let next: Vec<f64> = y
+            .iter()
+            .zip(&z)
+            .map(|(v, b)| if v > b { p } else { 1.0 - p })
+            .collect();
+        if next == weights {
+            break;
+        }
+        weights = next;
+    }
+    Some(z)
+}
+
+/// Solve a symmetric positive definite pentadiagonal system by banded Cholesky
+///
+/// Returns `None` when a pivot is not positive, as rounding makes it for a
+/// nearly singular system.
+fn solve_pentadiagonal(
+    diagonal: &[f64],
+    first: &[f64],
+    second: &[f64],
+    rhs: &[f64],
+) -> Option<Vec<f64>> {
+    let n = diagonal.len();
+    // Row i of L holds L[i][i-2], L[i][i-1], L[i][i]
+    let mut l = vec![[0.0f64; 3]; n];
+    for i in 0..n {
+        let a2 = if i >= 2 { second[i - 2] } else { 0.0 };
+        let a1 = if i >= 1 { first[i - 1] } else { 0.0 };
+        if i >= 2 {
+            l[i][0] = a2 / l[i - 2][2];
+        }
+        if i >= 1 {
+            l[i][1] = (a1 - l[i][0] * l[i - 1][1]) / l[i - 1][2];
+        }
+        let d = diagonal[i] - l[i][0] * l[i][0] - l[i][1] * l[i][1];
+        if d.is_nan() || d <= 0.0 {
+            return None;
+        }
+        l[i][2] = d.sqrt();
+    }
+
+    // L w = rhs, then Lᵀ z = w
+    let mut w = vec![0.0; n];
+    for i in 0..n {
+        let mut sum = rhs[i];
+        if i >= 1 {
+            sum -= l[i][1] * w[i - 1];
+        }
+        if i >= 2 {
+            sum -= l[i][0] * w[i - 2];
+        }
+        w[i] = sum / l[i][2];
+    }
+    let mut z = vec![0.0; n];
+    for i in (0..n).rev() {
+        let mut sum = w[i];
+        if i + 1 < n {
+            sum -= l[i + 1][1] * z[i + 1];
+        }
+        if i + 2 < n {
+            sum -= l[i + 2][0] * z[i + 2];
+        }
+        z[i] = sum / l[i][2];
+    }
+    Some(z)
+}
+
+/// Morphological opening (minimum then maximum filter) smoothed by a moving average
+fn rolling_ball(y: &[f64], radius: usize) -> Vec<f64> {
+    let eroded = sliding_extreme(y, radius, |a, b| a <= b);
+    let opened = sliding_extreme(&eroded, radius, |a, b| a >= b);
+    moving_average(&opened, radius)
+}
+
+/// Sliding-window minimum or maximum, where `keep(a, b)` prefers `a` over a later `b`
+fn sliding_extreme(y: &[f64], radius: usize, keep: impl Fn(f64, f64) -> bool) -> Vec<f64> {
+    let n = y.len();
+    let mut window: VecDeque<usize> = VecDeque::new();
+    let mut out = Vec::with_capacity(n);
+    let mut next = 0;
+    for i in 0..n {
+        // Admit points up to i + radius, dropping those that can no longer win
+        while next < n && next <= i + radius {
+            while window.back().is_some_and(|&j| !keep(y[j], y[next])) {
+                window.pop_back();
+            }
+            window.push_back(next);
+            next += 1;
+        }
+        while window.front().is_some_and(|&j| j + radius < i) {
+            window.pop_front();
+        }
+        out.push(window.front().map_or(y[i], |&j| y[j]));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line from 1 to 3 with a Gaussian peak of height 5 and σ of 10 points
+    /// in the middle
+    fn sloped_peak(n: usize) -> (Vec<(f64, f64)>, Vec<f64>) {
+        let line: Vec<f64> = (0..n)
+            .map(|i| 1.0 + 2.0 * i as f64 / (n - 1) as f64)
+            .collect();
+        let data = line
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                let t = (i as f64 - n as f64 / 2.0) / 10.0;
+                (i as f64, b + 5.0 * (-0.5 * t * t).exp())
+            })
+            .collect();
+        (data, line)
+    }
+
+    fn max_error(estimate: &[f64], truth: &[f64]) -> f64 {
+        estimate
+            .iter()
+            .zip(truth)
+            .map(|(e, t)| (e - t).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn als_follows_slope_under_peak_for_long_data() {
+        for n in [1_000, 50_000, 100_000] {
+            let (data, line) = sloped_peak(n);
+            let baseline = estimate(&data, BaselineMethod::Als, BaselineSettings::default())
+                .expect("ALS solves");
+            assert!(baseline.iter().all(|b| b.is_finite()), "n = {}", n);
+            assert!(
+                (baseline[0] - 1.0).abs() < 0.05,
+                "n = {}: b[0] = {}",
+                n,
+                baseline[0]
+            );
+            let e = max_error(&baseline, &line);
+            assert!(e < 0.1, "n = {}: error {}", n, e);
+        }
+    }
+
+    #[test]
+    fn polynomial_recovers_line_under_peak() {
+        let (data, line) = sloped_peak(2_000);
+        let baseline = estimate(
+            &data,
+            BaselineMethod::Polynomial,
+            BaselineSettings::default(),
+        )
+        .unwrap();
+        let e = max_error(&baseline, &line);
+        assert!(e < 0.1, "error {}", e);
+    }
+
+    #[test]
+    fn settings_set_degree_and_radius() {
+        // A straight line under the peak needs only degree 1
+        let (data, line) = sloped_peak(2_000);
+        let linear = BaselineSettings {
+            degree: 1,
+            ..BaselineSettings::default()
+        };
+        let baseline = estimate(&data, BaselineMethod::Polynomial, linear).unwrap();
+        assert!(max_error(&baseline, &line) < 0.05);
+        assert_eq!(
+            BaselineMethod::Polynomial.describe(2_000, linear),
+            "polynomial, degree 1"
+        );
+
+        let wide = BaselineSettings {
+            window: 0.1,
+            ..BaselineSettings::default()
+        };
+        assert_eq!(wide.radius(2_000), 200);
+        assert_eq!(
+            BaselineMethod::RollingBall.describe(2_000, wide),
+            "rolling ball, radius 200 pts"
+        );
+    }
+
+    #[test]
+    fn pentadiagonal_solve_matches_known_solution() {
+        // Tridiagonal 2, -1 system with zero outer band; solution is all ones
+        let n = 6;
+        let diagonal = vec![2.0; n];
+        let first = vec![-1.0; n - 1];
+        let second = vec![0.0; n - 2];
+        let mut rhs = vec![0.0; n];
+        rhs[0] = 1.0;
+        rhs[n - 1] = 1.0;
+        let z = solve_pentadiagonal(&diagonal, &first, &second, &rhs).unwrap();
+        assert!(z.iter().all(|v| (v - 1.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn pentadiagonal_solve_rejects_non_positive_pivot() {
+        let z = solve_pentadiagonal(&[1.0, 1.0, 1.0], &[2.0, 0.0], &[0.0], &[1.0; 3]);
+        assert!(z.is_none());
+    }
+}
@@ -0,0 +1,357 @@
+//! Column table parsing for delimited numeric text files
+
+use std::ops::Range;
+
+/// Numeric column table parsed from a text file
+#[derive(Default)]
+pub struct DataTable {
+    /// Column-major values; short rows are padded with NaN
+    pub columns: Vec<Vec<f64>>,
+    /// Column names from the header row, or `Col N` when there is none
+    pub names: Vec<String>,
+    /// Whether the names came from a header row
+    pub named: bool,
+    /// Zero-based source line index of every row
+    pub source_lines: Vec<usize>,
+    /// Per column, fields that failed to parse or were missing from short rows
+    pub unparsed: Vec<usize>,
+    /// Runs of rows separated by blank or comment lines; one block covering
+    /// every row when there are no separators
+    pub blocks: Vec<DataBlock>,
+}
+
+/// Consecutive rows of a multi-block file, such as one scan
+pub struct DataBlock {
+    pub rows: Range<usize>,
+    /// Comment, or text after a blank line, just before the block
+    pub title: Option<String>,
+}
+
+impl DataTable {
+    /// Parse every numeric row of `content` into columns
+    pub fn parse(content: &str) -> DataTable {
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        let mut source_lines = Vec::new();
+        let mut unparsed: Vec<usize> = Vec::new();
+        // Text and comment lines seen before the first data row
+        let mut headers: Vec<&str> = Vec::new();
+        // First row and title of each block, and whether a separator was seen
+        let mut block_starts: Vec<(usize, Option<String>)> = Vec::new();
+        let mut separated = false;
+        let mut block_title: Option<String> = None;
+
+        for (line_idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                separated = true;
+                continue;
+            }
+
+            // Comments contribute a header, or the title of the next block
+            if let Some(comment) = line.strip_prefix('#').or_else(|| line.strip_prefix(';')) {
+                if rows.is_empty() {
+                    headers.push(comment);
+                } else {
+                    separated = true;
+                    block_title = Some(comment.trim().to_string()).filter(|t| !t.is_empty());
+                }
+                continue;
+            }
+
+            let parsed: Vec<Option<f64>> = split_fields(line).map(|s| s.parse().ok()).collect();
+            let values: Vec<f64> = parsed.iter().map(|v| v.unwrap_or(f64::NAN)).collect();
+
+            // A data row starts with a number and holds at least two of them
+            let numeric = values.iter().filter(|v| !v.is_nan()).count();
+            if values.len() >= 2 && !values[0].is_nan() && numeric >= 2 {
+                if unparsed.len() < parsed.len() {
+                    unparsed.resize(parsed.len(), 0);
+                }
+                for (count, value) in unparsed.iter_mut().zip(&parsed) {
+                    if value.is_none() {
+                        *count += 1;
+                    }
+                }
+                if rows.is_empty() || separated {
+                    block_starts.push((rows.len(), block_title.take()));
+                    separated = false;
+                }
+                rows.push(values);
+                source_lines.push(line_idx);
+            } else if rows.is_empty() {
+                headers.push(line);
+            } else if separated {
+                block_title = Some(line.to_string());
+            } else {
+                // A malformed row inside a block loses every field it has
+                if unparsed.len() < parsed.len() {
+                    unparsed.resize(parsed.len(), 0);
+                }
+                for count in unparsed.iter_mut().take(parsed.len()) {
+                    *count += 1;
+                }
+            }
+        }
+
+        let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut columns = vec![Vec::with_capacity(rows.len()); column_count];
+        for row in &rows {
+            for (col, column) in columns.iter_mut().enumerate() {
+                column.push(row.get(col).copied().unwrap_or(f64::NAN));
+            }
+            for count in unparsed.iter_mut().skip(row.len()) {
+                *count += 1;
+            }
+        }
+
+        // Use the last header line that names every column
+        let header = headers
+            .iter()
+            .rposition(|line| split_header(line).len() == column_count);
+        let (names, named) = match header {
+            Some(i) => (split_header(headers[i]), true),
+            None => (
+                (1..=column_count).map(|i| format!("Col {}", i)).collect(),
+                false,
+            ),
+        };
+
+        // A line after the header titles the first block
+        if let Some((_, title)) = block_starts.first_mut()
+            && header.is_none_or(|i| i + 1 < headers.len())
+        {
+            *title = headers.last().map(|line| line.trim().to_string());
+        }
+
+        let block_ends = block_starts
+            .iter()
+            .skip(1)
+            .map(|&(start, _)| start)
+            .chain([rows.len()]);
+        let blocks = block_starts
+            .iter()
+            .zip(block_ends)
+            .map(|((start, title), end)| DataBlock {
+                rows: *start..end,
+                title: title.clone(),
+            })
+            .collect();
+
+        DataTable {
+            columns,
+            names,
+            named,
+            blocks,
+            source_lines,
+            unparsed,
+        }
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn column_name(&self, col: usize) -> &str {
+        self.names.get(col).map(String::as_str).unwrap_or("?")
+    }
+
+    /// All rows, or the rows of block `block` of a multi-block file
+    pub fn block_rows(&self, block: Option<usize>) -> Range<usize> {
+        block
+            .and_then(|b| self.blocks.get(b))
+            .map_or(0..self.source_lines.len(), |b| b.rows.clone())
+    }
+
+    /// Pair two columns over `rows` into points, dropping rows where either
+    /// value is not finite
+    ///
+    /// Returns the points and the source line index of each one.
+    pub fn xy_points(
+        &self,
+        x_col: usize,
+        y_col: usize,
+        rows: Range<usize>,
+    ) -> (Vec<(f64, f64)>, Vec<usize>) {
+        let (Some(xs), Some(ys)) = (self.columns.get(x_col), self.columns.get(y_col)) else {
+            return (Vec::new(), Vec::new());
+        };
+        xs[rows.clone()]
+            .iter()
+            .zip(&ys[rows.clone()])
+            .zip(&self.source_lines[rows])
+            .filter(|((x, y), _)| x.is_finite() && y.is_finite())
+            .map(|((x, y), line)| ((*x, *y), *line))
+            .unzip()
+    }
+
+    /// Values of `col` on the rows `xy_points(x_col, y_col, rows)` keeps, NaN where missing
+    pub fn values_at_points(
+        &self,
+        x_col: usize,
+        y_col: usize,
+        col: usize,
+        rows: Range<usize>,
+    ) -> Vec<f64> {
+        let (Some(xs), Some(ys)) = (self.columns.get(x_col), self.columns.get(y_col)) else {
+            return Vec::new();
+        };
+        let values = self.columns.get(col);
+        xs.iter()
+            .zip(ys)
+            .enumerate()
+            .skip(rows.start)
+            .take(rows.len())
+            .filter(|(_, (x, y))| x.is_finite() && y.is_finite())
+            .map(|(row, _)| values.and_then(|v| v.get(row)).copied().unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    /// First column past the default X and Y whose header names an uncertainty,
+    /// such as `sigma`, `err` or `esd`
+    pub fn error_column(&self) -> Option<usize> {
+        const MARKERS: [&str; 6] = ["err", "sigma", "σ", "esd", "unc", "stdev"];
+        const NAMES: [&str; 3] = ["dy", "sd", "std"];
+        (2..self.column_count()).find(|&col| {
+            let name = self.names[col].to_lowercase();
+            MARKERS.iter().any(|m| name.contains(m)) || NAMES.contains(&name.as_str())
+        })
+    }
+}
+
+/// Apply `f` to the X and Y values of `data` sorted by X, returning its
+/// per-point output in the original order
+pub fn map_in_x_order(data: &[(f64, f64)], f: impl FnOnce(&[f64], &[f64]) -> Vec<f64>) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    order.sort_by(|&a, &b| data[a].0.total_cmp(&data[b].0));
+    let x: Vec<f64> = order.iter().map(|&i| data[i].0).collect();
+    let y: Vec<f64> = order.iter().map(|&i| data[i].1).collect();
+
+    let mut out = vec![0.0; data.len()];
+    for (&i, value) in order.iter().zip(f(&x, &y)) {
+        out[i] = value;
+    }
+    out
+}
+
+/// Step between evenly spaced sorted values, or `None` when any step differs
+/// from the median one by more than 1%, which leaves room for text rounding
+pub fn uniform_step(sorted: &[f64]) -> Option<f64> {
+    let mut steps: Vec<f64> = sorted.windows(2).map(|w| w[1] - w[0]).collect();
+    if steps.is_empty() {
+        return None;
+    }
+    steps.sort_by(f64::total_cmp);
+    let median = steps[steps.len() / 2];
+    let tolerance = median.abs() * 1e-2;
+    (median > 0.0 && steps.iter().all(|s| (s - median).abs() <= tolerance)).then_some(median)
+}
+
+/// Split a line by whitespace, comma, or tab
+pub fn split_fields(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+}
+
+/// Split a header line into column names
+///
+/// Comma- and tab-separated headers keep spaces inside names (`two theta, intensity`),
+/// anything else is split on whitespace.
+fn split_header(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let fields: Vec<&str> = if line.contains(',') {
+        line.split(',').collect()
+    } else if line.contains('\t') {
+        line.split('\t').collect()
+    } else {
+        line.split_whitespace().collect()
+    };
+    fields
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_and_comment_lines_start_blocks() {
+        let table = DataTable::parse(
+            "# two_theta, intensity\n\
+             1 10\n2 20\n\n\
+             Scan 2\n1 11\n2 21\n\
+             # scan 3\n1 12\n2 22\n",
+        );
+        assert_eq!(table.names, ["two_theta", "intensity"]);
+        let blocks: Vec<(Range<usize>, Option<&str>)> = table
+            .blocks
+            .iter()
+            .map(|b| (b.rows.clone(), b.title.as_deref()))
+            .collect();
+        assert_eq!(
+            blocks,
+            [(0..2, None), (2..4, Some("Scan 2")), (4..6, Some("scan 3"))]
+        );
+        assert_eq!(table.source_lines, [1, 2, 5, 6, 8, 9]);
+        assert_eq!(table.block_rows(Some(1)), 2..4);
+        assert_eq!(table.block_rows(None), 0..6);
+    }
+
+    #[test]
+    fn a_malformed_row_counts_as_unparsed_without_splitting() {
+        let table = DataTable::parse("x y z\n1 2 3\n2 oops\n3 4\n4 5 n/a\n");
+        assert_eq!(table.blocks.len(), 1);
+        assert_eq!(table.blocks[0].rows, 0..3);
+        // `2 oops` loses both its fields, `3 4` is short and `n/a` fails
+        assert_eq!(table.unparsed, [1, 1, 2]);
+        assert!(table.columns[2][1].is_nan());
+        assert_eq!(table.columns[2][0], 3.0);
+    }
+
+    #[test]
+    fn headers_keep_spaces_inside_delimited_names() {
+        assert_eq!(
+            split_header("two theta, intensity"),
+            ["two theta", "intensity"]
+        );
+        assert_eq!(split_header("a b\tc d"), ["a b", "c d"]);
+        assert_eq!(split_header("  x   y  "), ["x", "y"]);
+
+        // Only a line naming every column is used
+        let table = DataTable::parse("# run 4\nx y\n1 2\n2 3\n");
+        assert!(table.named);
+        assert_eq!(table.names, ["x", "y"]);
+        let unnamed = DataTable::parse("# run number 4\n1 2\n2 3\n");
+        assert!(!unnamed.named);
+        assert_eq!(unnamed.names, ["Col 1", "Col 2"]);
+        assert_eq!(unnamed.blocks[0].title.as_deref(), Some("run number 4"));
+    }
+
+    #[test]
+    fn values_follow_the_points_kept() {
+        let table = DataTable::parse("1 2 0.1\n2 nan 0.2\n3 4 0.3\n4 5\n");
+        let (points, lines) = table.xy_points(0, 1, 0..4);
+        assert_eq!(points, [(1.0, 2.0), (3.0, 4.0), (4.0, 5.0)]);
+        assert_eq!(lines, [0, 2, 3]);
+        let errors = table.values_at_points(0, 1, 2, 0..4);
+        assert_eq!(errors[..2], [0.1, 0.3]);
+        assert!(errors[2].is_nan());
+        assert_eq!(table.values_at_points(0, 1, 2, 1..3), [0.3]);
+    }
+
+    #[test]
+    fn error_columns_are_found_by_name() {
+        let error_column =
+            |header: &str| DataTable::parse(&format!("{}\n1 2 3 4\n", header)).error_column();
+        assert_eq!(error_column("x y sigma z"), Some(2));
+        assert_eq!(error_column("x y z dY"), Some(3));
+        assert_eq!(error_column("x y z esd_y"), Some(3));
+        // X and Y themselves are never errors, nor are names that merely contain `sd`
+        assert_eq!(error_column("err y z w"), None);
+        assert_eq!(error_column("x y used w"), None);
+        assert_eq!(error_column("1 2 3 4"), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Processed data export as CSV, TSV or JSON
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// File format of a data export, chosen by extension
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl DataFormat {
+    pub fn from_path(path: &Path) -> Option<DataFormat> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(DataFormat::Csv),
+            "tsv" | "txt" => Some(DataFormat::Tsv),
+            "json" => Some(DataFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Named columns of processed values, with notes on where they came from
+pub struct DataExport {
+    /// (label, text) lines such as ("Source", path), in order
+    pub provenance: Vec<(&'static str, String)>,
+    pub columns: Vec<String>,
+    /// One value per column in each row
+    pub rows: Vec<Vec<f64>>,
+}
+
+impl DataExport {
+    /// Text of the export; delimited formats carry the provenance as `#` lines
+    fn render(&self, format: DataFormat) -> String {
+        match format {
+            DataFormat::Csv => self.delimited(',', csv_field),
+            DataFormat::Tsv => self.delimited('\t', |name| name.replace('\t', " ")),
+            DataFormat::Json => self.json(),
+        }
+    }
+
+    pub fn write(&self, path: &Path, format: DataFormat) -> io::Result<()> {
+        fs::write(path, self.render(format))
+    }
+
+    fn delimited(&self, separator: char, field: impl Fn(&str) -> String) -> String {
+        let mut out = String::new();
+        for (label, text) in &self.provenance {
+            let _ = writeln!(out, "# {}: {}", label, text);
+        }
+        let header: Vec<String> = self.columns.iter().map(|name| field(name)).collect();
+        let _ = writeln!(out, "{}", header.join(&separator.to_string()));
+        for row in &self.rows {
+            let values: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+            let _ = writeln!(out, "{}", values.join(&separator.to_string()));
+        }
+        out
+    }
+
+    /// Object with the provenance under lowercased labels, then `columns` and `data`
+    fn json(&self) -> String {
+        let mut out = String::from("{\n");
+        for (label, text) in &self.provenance {
+            let key = label.to_lowercase().replace(' ', "_");
+            let _ = writeln!(out, "  {}: {},", json_string(&key), json_string(text));
+        }
+        let names: Vec<String> = self.columns.iter().map(|name| json_string(name)).collect();
+        let _ = writeln!(out, "  \"columns\": [{}],", names.join(", "));
+        out.push_str("  \"data\": [");
+        for (i, row) in self.rows.iter().enumerate() {
+            let values: Vec<String> = row.iter().map(|&v| json_number(v)).collect();
+            let separator = if i == 0 { "\n" } else { ",\n" };
+            let _ = write!(out, "{}    [{}]", separator, values.join(", "));
+        }
+        out.push_str(if self.rows.is_empty() {
+            "]\n}\n"
+        } else {
+            "\n  ]\n}\n"
+        });
+        out
+    }
+}
+
+/// Quote a CSV header field when it holds a comma, quote or line break
+pub fn csv_field(name: &str) -> String {
+    if name.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    } else {
+        name.to_string()
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// JSON has no NaN or infinity, so those become null
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export() -> DataExport {
+        DataExport {
+            provenance: vec![
+                ("Source", "run 1.dat".to_string()),
+                ("X range", "1 to 2".to_string()),
+            ],
+            columns: vec![
+                "x, nm".to_string(),
+                "say \"hi\"".to_string(),
+                "a\tb".to_string(),
+            ],
+            rows: vec![vec![1.0, 0.5, f64::NAN], vec![2.0, -3.0, 4.0]],
+        }
+    }
+
+    #[test]
+    fn csv_quotes_awkward_names_under_the_provenance() {
+        assert_eq!(
+            export().render(DataFormat::Csv),
+            "# Source: run 1.dat\n\
+             # X range: 1 to 2\n\
+             \"x, nm\",\"say \"\"hi\"\"\",a\tb\n\
+             1,0.5,NaN\n\
+             2,-3,4\n"
+        );
+    }
+
+    #[test]
+    fn tsv_replaces_tabs_in_names() {
+        let text = export().render(DataFormat::Tsv);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[2], "x, nm\tsay \"hi\"\ta b");
+        assert_eq!(lines[3], "1\t0.5\tNaN");
+    }
+
+    #[test]
+    fn json_escapes_strings_and_nulls_missing_values() {
+        assert_eq!(
+            export().render(DataFormat::Json),
+            "{\n  \"source\": \"run 1.dat\",\n  \"x_range\": \"1 to 2\",\n  \
+             \"columns\": [\"x, nm\", \"say \\\"hi\\\"\", \"a\\tb\"],\n  \
+             \"data\": [\n    [1, 0.5, null],\n    [2, -3, 4]\n  ]\n}\n"
+        );
+        assert_eq!(json_string("back\\slash\u{1}"), "\"back\\\\slash\\u0001\"");
+    }
+
+    #[test]
+    fn format_follows_the_extension() {
+        assert!(DataFormat::from_path(Path::new("a.CSV")) == Some(DataFormat::Csv));
+        assert!(DataFormat::from_path(Path::new("a.txt")) == Some(DataFormat::Tsv));
+        assert!(DataFormat::from_path(Path::new("a.json")) == Some(DataFormat::Json));
+        assert!(DataFormat::from_path(Path::new("a.dat")).is_none());
+    }
+}
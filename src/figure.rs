@@ -0,0 +1,552 @@
+//! Chart figures: what the chart draws, exportable as SVG or PNG
+
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use ratatui::style::Color;
+use ratatui::symbols::Marker;
+use ratatui::widgets::GraphType;
+use unicode_width::UnicodeWidthChar;
+
+use crate::font;
+
+/// Size of an exported figure in SVG units; PNGs are rendered at `PNG_SCALE`×
+pub const WIDTH: f64 = 960.0;
+const HEIGHT: f64 = 600.0;
+const PNG_SCALE: f64 = 2.0;
+
+/// Advance of one character of 12-unit monospace text
+const CHAR_WIDTH: f64 = 7.2;
+
+const BACKGROUND: (u8, u8, u8) = (40, 44, 52);
+const AXIS: (u8, u8, u8) = (171, 178, 191);
+const GRID: (u8, u8, u8) = (62, 68, 81);
+
+/// One dataset of the chart, with ratatui's marker and graph type
+pub struct Layer {
+    /// `None` keeps it out of the legend
+    pub name: Option<String>,
+    pub points: Vec<(f64, f64)>,
+    pub marker: Marker,
+    pub graph_type: GraphType,
+    pub color: Color,
+}
+
+/// A chart in plot-space coordinates with its titles and axis labels
+pub struct Figure {
+    pub title: String,
+    pub x_bounds: [f64; 2],
+    pub y_bounds: [f64; 2],
+    /// Tick labels spread evenly along each axis, as ratatui places them
+    pub x_labels: Vec<String>,
+    pub y_labels: Vec<String>,
+    pub x_title: String,
+    pub y_title: String,
+    pub layers: Vec<Layer>,
+}
+
+#[derive(Clone, Copy)]
+enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// Drawing operations in SVG units, shared by the SVG and PNG writers
+trait Painter {
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: (u8, u8, u8));
+    fn polyline(&mut self, points: &[(f64, f64)], width: f64, color: (u8, u8, u8), clip: bool);
+    fn dot(&mut self, x: f64, y: f64, radius: f64, color: (u8, u8, u8));
+    /// Text with its baseline at `y`, or running upwards from `x` when `vertical`
+    fn text(&mut self, x: f64, y: f64, text: &str, anchor: Anchor, vertical: bool);
+}
+
+/// Plot rectangle as (left, top, right, bottom)
+type Plot = (f64, f64, f64, f64);
+
+/// RGB triple of a chart colour
+pub fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::White => (255, 255, 255),
+        _ => AXIS,
+    }
+}
+
+/// Characters that take up space, leaving out combining marks
+fn char_count(text: &str) -> usize {
+    text.chars().filter(|c| c.width() != Some(0)).count()
+}
+
+/// Plot rectangle, leaving room on the left for the widest Y label
+fn plot_rect(figure: &Figure) -> Plot {
+    let label_chars = figure
+        .y_labels
+        .iter()
+        .map(|l| char_count(l))
+        .max()
+        .unwrap_or(0);
+    (
+        36.0 + label_chars as f64 * CHAR_WIDTH,
+        40.0,
+        WIDTH - 24.0,
+        HEIGHT - 54.0,
+    )
+}
+
+/// Lay out and draw the whole figure
+fn paint(figure: &Figure, painter: &mut impl Painter) {
+    let plot = plot_rect(figure);
+    let (left, top, right, bottom) = plot;
+    painter.rect(0.0, 0.0, WIDTH, HEIGHT, BACKGROUND);
+    painter.text(
+        WIDTH / 2.0,
+        24.0,
+        figure.title.trim(),
+        Anchor::Middle,
+        false,
+    );
+
+    // Grid lines and tick labels at evenly spaced positions
+    let spread = |count: usize, i: usize| {
+        if count > 1 {
+            i as f64 / (count - 1) as f64
+        } else {
+            0.0
+        }
+    };
+    // Blank labels only space out the labelled decades of a log axis
+    for (i, label) in figure.x_labels.iter().enumerate() {
+        if label.is_empty() {
+            continue;
+        }
+        let x = left + (right - left) * spread(figure.x_labels.len(), i);
+        painter.polyline(&[(x, top), (x, bottom)], 1.0, GRID, false);
+        painter.text(x, bottom + 18.0, label, Anchor::Middle, false);
+    }
+    for (i, label) in figure.y_labels.iter().enumerate() {
+        if label.is_empty() {
+            continue;
+        }
+        let y = bottom - (bottom - top) * spread(figure.y_labels.len(), i);
+        painter.polyline(&[(left, y), (right, y)], 1.0, GRID, false);
+        painter.text(left - 6.0, y + 4.0, label, Anchor::End, false);
+    }
+    painter.text(
+        (left + right) / 2.0,
+        HEIGHT - 12.0,
+        &figure.x_title,
+        Anchor::Middle,
+        false,
+    );
+    painter.text(
+        16.0,
+        (top + bottom) / 2.0,
+        &figure.y_title,
+        Anchor::Middle,
+        true,
+    );
+
+    // Layers in order, so later ones draw on top as in the terminal
+    let [x0, x1] = figure.x_bounds;
+    let [y0, y1] = figure.y_bounds;
+    let to_svg = |&(x, y): &(f64, f64)| {
+        (
+            left + (x - x0) / (x1 - x0) * (right - left),
+            bottom - (y - y0) / (y1 - y0) * (bottom - top),
+        )
+    };
+    for layer in &figure.layers {
+        let points: Vec<(f64, f64)> = layer.points.iter().map(to_svg).collect();
+        let color = rgb(layer.color);
+        match layer.graph_type {
+            GraphType::Line => {
+                // Half blocks only draw the shaded error band, one column per bar
+                let width = if layer.marker == Marker::HalfBlock {
+                    1.0
+                } else {
+                    1.5
+                };
+                painter.polyline(&points, width, color, true);
+            }
+            _ => {
+                let radius = if layer.marker == Marker::Block {
+                    4.0
+                } else {
+                    2.0
+                };
+                let inside =
+                    |&&(x, y): &&(f64, f64)| x >= left && x <= right && y >= top && y <= bottom;
+                for &(x, y) in points.iter().filter(inside) {
+                    painter.dot(x, y, radius, color);
+                }
+            }
+        }
+    }
+
+    // Axis lines over the data edges
+    painter.polyline(
+        &[(left, top), (left, bottom), (right, bottom)],
+        1.0,
+        AXIS,
+        false,
+    );
+    paint_legend(figure, plot, painter);
+}
+
+/// Named layers with a colour swatch each, in the top right corner
+fn paint_legend(figure: &Figure, plot: Plot, painter: &mut impl Painter) {
+    let entries: Vec<(&str, (u8, u8, u8))> = figure
+        .layers
+        .iter()
+        .filter_map(|layer| Some((layer.name.as_deref()?, rgb(layer.color))))
+        .collect();
+    if entries.is_empty() {
+        return;
+    }
+    let chars = entries
+        .iter()
+        .map(|(name, _)| char_count(name))
+        .max()
+        .unwrap_or(0);
+    let width = chars as f64 * CHAR_WIDTH + 40.0;
+    let height = entries.len() as f64 * 16.0 + 8.0;
+    let (x, y) = (plot.2 - width - 8.0, plot.1 + 8.0);
+    painter.rect(x, y, width, height, GRID);
+    painter.rect(x + 1.0, y + 1.0, width - 2.0, height - 2.0, BACKGROUND);
+    for (i, (name, color)) in entries.into_iter().enumerate() {
+        let line_y = y + 12.0 + i as f64 * 16.0;
+        painter.polyline(&[(x + 8.0, line_y), (x + 24.0, line_y)], 2.0, color, false);
+        painter.text(x + 30.0, line_y + 4.0, name, Anchor::Start, false);
+    }
+}
+
+/// Write the figure as an SVG document
+pub fn write_svg(figure: &Figure, path: &Path) -> io::Result<()> {
+    fs::write(path, svg_document(figure))
+}
+
+fn svg_document(figure: &Figure) -> String {
+    let mut svg = Svg {
+        body: String::new(),
+    };
+    paint(figure, &mut svg);
+    let (left, top, right, bottom) = plot_rect(figure);
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-size=\"12\">\n\
+         <defs><clipPath id=\"plot\"><rect x=\"{left:.1}\" y=\"{top}\" \
+         width=\"{pw:.1}\" height=\"{ph}\"/></clipPath></defs>\n{body}</svg>\n",
+        w = WIDTH,
+        h = HEIGHT,
+        pw = right - left,
+        ph = bottom - top,
+        body = svg.body
+    )
+}
+
+struct Svg {
+    body: String,
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Painter for Svg {
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: (u8, u8, u8)) {
+        let _ = writeln!(
+            self.body,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+            x,
+            y,
+            width,
+            height,
+            hex(color)
+        );
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], width: f64, color: (u8, u8, u8), clip: bool) {
+        let coords: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+            .collect();
+        let _ = writeln!(
+            self.body,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+             stroke-linejoin=\"round\"{}/>",
+            coords.join(" "),
+            hex(color),
+            width,
+            if clip {
+                " clip-path=\"url(#plot)\""
+            } else {
+                ""
+            }
+        );
+    }
+
+    fn dot(&mut self, x: f64, y: f64, radius: f64, color: (u8, u8, u8)) {
+        let _ = writeln!(
+            self.body,
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" fill=\"{}\"/>",
+            x,
+            y,
+            radius,
+            hex(color)
+        );
+    }
+
+    fn text(&mut self, x: f64, y: f64, text: &str, anchor: Anchor, vertical: bool) {
+        let anchor = match anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        let rotate = if vertical {
+            format!(" transform=\"rotate(-90 {:.1} {:.1})\"", x, y)
+        } else {
+            String::new()
+        };
+        let _ = writeln!(
+            self.body,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" fill=\"{}\"{}>{}</text>",
+            x,
+            y,
+            anchor,
+            hex(AXIS),
+            rotate,
+            escape(text)
+        );
+    }
+}
+
+/// Write the figure as a PNG image
+pub fn write_png(figure: &Figure, path: &Path) -> io::Result<()> {
+    encode_png(figure, BufWriter::new(File::create(path)?))
+}
+
+fn encode_png(figure: &Figure, out: impl Write) -> io::Result<()> {
+    let width = (WIDTH * PNG_SCALE) as usize;
+    let height = (HEIGHT * PNG_SCALE) as usize;
+    let mut raster = Raster {
+        width,
+        height,
+        pixels: vec![0; width * height * 3],
+        plot: plot_rect(figure),
+        clip: false,
+    };
+    paint(figure, &mut raster);
+
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(&raster.pixels)
+        .map_err(io::Error::other)
+}
+
+/// RGB pixels at `PNG_SCALE` pixels per SVG unit
+struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    plot: Plot,
+    /// Keep drawing inside the plot rectangle
+    clip: bool,
+}
+
+impl Raster {
+    fn set(&mut self, x: i64, y: i64, (r, g, b): (u8, u8, u8)) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        if self.clip {
+            let (left, top, right, bottom) = self.plot;
+            let (ux, uy) = (x as f64 / PNG_SCALE, y as f64 / PNG_SCALE);
+            if ux < left || ux > right || uy < top || uy > bottom {
+                return;
+            }
+        }
+        let i = (y as usize * self.width + x as usize) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+    }
+
+    /// Filled disk of `radius` pixels around a pixel position
+    fn disk(&mut self, cx: f64, cy: f64, radius: f64, color: (u8, u8, u8)) {
+        let r = radius.ceil() as i64;
+        let (px, py) = (cx.round() as i64, cy.round() as i64);
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let (fx, fy) = ((px + dx) as f64 - cx, (py + dy) as f64 - cy);
+                if fx * fx + fy * fy <= radius * radius {
+                    self.set(px + dx, py + dy, color);
+                }
+            }
+        }
+    }
+}
+
+impl Painter for Raster {
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: (u8, u8, u8)) {
+        let x0 = (x * PNG_SCALE).round() as i64;
+        let y0 = (y * PNG_SCALE).round() as i64;
+        let x1 = ((x + width) * PNG_SCALE).round() as i64;
+        let y1 = ((y + height) * PNG_SCALE).round() as i64;
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], width: f64, color: (u8, u8, u8), clip: bool) {
+        self.clip = clip;
+        let radius = width * PNG_SCALE / 2.0;
+        for pair in points.windows(2) {
+            let (ax, ay) = (pair[0].0 * PNG_SCALE, pair[0].1 * PNG_SCALE);
+            let (bx, by) = (pair[1].0 * PNG_SCALE, pair[1].1 * PNG_SCALE);
+            if !(ax.is_finite() && ay.is_finite() && bx.is_finite() && by.is_finite()) {
+                continue;
+            }
+            // Segments far outside the image are clipped away entirely
+            let limit = (self.width + self.height) as f64 * 4.0;
+            if ax.abs().max(ay.abs()).max(bx.abs()).max(by.abs()) > limit {
+                continue;
+            }
+            let steps = ((bx - ax).abs().max((by - ay).abs()) * 2.0).ceil().max(1.0) as usize;
+            for step in 0..=steps {
+                let t = step as f64 / steps as f64;
+                self.disk(ax + (bx - ax) * t, ay + (by - ay) * t, radius, color);
+            }
+        }
+        self.clip = false;
+    }
+
+    fn dot(&mut self, x: f64, y: f64, radius: f64, color: (u8, u8, u8)) {
+        self.disk(x * PNG_SCALE, y * PNG_SCALE, radius * PNG_SCALE, color);
+    }
+
+    fn text(&mut self, x: f64, y: f64, text: &str, anchor: Anchor, vertical: bool) {
+        // Glyph pixels are PNG_SCALE px square, 6 of them per character
+        let pixel = PNG_SCALE as i64;
+        let advance = 6 * pixel;
+        let chars: Vec<char> = text.chars().filter(|c| c.width() != Some(0)).collect();
+        let length = chars.len() as i64 * advance - pixel;
+        let offset = match anchor {
+            Anchor::Start => 0,
+            Anchor::Middle => length / 2,
+            Anchor::End => length,
+        };
+        let (x, y) = (
+            (x * PNG_SCALE).round() as i64,
+            (y * PNG_SCALE).round() as i64,
+        );
+        for (i, &c) in chars.iter().enumerate() {
+            let columns = font::glyph(c);
+            for (col, bits) in columns.iter().enumerate() {
+                for row in 0..7 {
+                    if bits & (1 << row) == 0 {
+                        continue;
+                    }
+                    // Along the text and up from the baseline
+                    let along = i as i64 * advance + col as i64 * pixel - offset;
+                    let up = (7 - row) * pixel;
+                    let (px, py) = if vertical {
+                        (x - up, y - along)
+                    } else {
+                        (x + along, y - up)
+                    };
+                    for dy in 0..pixel {
+                        for dx in 0..pixel {
+                            self.set(px + dx, py + dy, AXIS);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn figure() -> Figure {
+        Figure {
+            title: " a < b … ".to_string(),
+            x_bounds: [0.0, 10.0],
+            y_bounds: [0.0, 1.0],
+            x_labels: vec!["0".to_string(), String::new(), "10".to_string()],
+            y_labels: vec!["0".to_string(), "1".to_string()],
+            x_title: "2θ (°)".to_string(),
+            y_title: "I".to_string(),
+            layers: vec![
+                Layer {
+                    name: Some("line".to_string()),
+                    points: vec![(0.0, 0.0), (5.0, 1.0), (10.0, 0.5)],
+                    marker: Marker::Braille,
+                    graph_type: GraphType::Line,
+                    color: Color::Rgb(255, 0, 0),
+                },
+                Layer {
+                    name: None,
+                    // The last point lies outside the plot and is left out
+                    points: vec![(2.0, 0.2), (4.0, 0.4), (20.0, 0.5)],
+                    marker: Marker::Dot,
+                    graph_type: GraphType::Scatter,
+                    color: Color::Rgb(0, 0, 255),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn svg_holds_every_element() {
+        let svg = svg_document(&figure());
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"960\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("<clipPath id=\"plot\">"));
+        assert!(svg.contains(">a &lt; b …</text>"));
+        assert!(svg.contains(">2θ (°)</text>"));
+        assert!(svg.contains("transform=\"rotate(-90"));
+        // Three grid lines (the blank X label has none), the data and the axes
+        assert_eq!(svg.matches("stroke=\"#3e4451\"").count(), 4);
+        assert_eq!(svg.matches("stroke=\"#ff0000\"").count(), 2);
+        assert_eq!(svg.matches("clip-path=\"url(#plot)\"").count(), 1);
+        assert_eq!(svg.matches("fill=\"#0000ff\"").count(), 2);
+        // Only the named layer has a legend entry
+        assert_eq!(svg.matches(">line</text>").count(), 1);
+    }
+
+    #[test]
+    fn png_decodes_at_twice_the_size() {
+        let mut bytes = Vec::new();
+        encode_png(&figure(), &mut bytes).unwrap();
+        let decoder = png::Decoder::new(io::Cursor::new(bytes));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (1920, 1200));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+
+        let pixel = |x: usize, y: usize| {
+            let i = (y * 1920 + x) * 3;
+            (pixels[i], pixels[i + 1], pixels[i + 2])
+        };
+        assert_eq!(pixel(0, 0), BACKGROUND);
+        let colored = |color| (0..1920 * 1200).any(|i| pixel(i % 1920, i / 1920) == color);
+        assert!(colored((255, 0, 0)));
+        assert!(colored((0, 0, 255)));
+        assert!(colored(AXIS));
+    }
+}
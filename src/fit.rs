@@ -0,0 +1,722 @@
+//! Least-squares peak profile fitting (Levenberg-Marquardt)
+
+use std::f64::consts::{LN_2, PI};
+
+/// Peak shape used for fitting
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Profile {
+    #[default]
+    Gaussian,
+    Lorentzian,
+    PseudoVoigt,
+}
+
+impl Profile {
+    pub fn next(self) -> Profile {
+        match self {
+            Profile::Gaussian => Profile::Lorentzian,
+            Profile::Lorentzian => Profile::PseudoVoigt,
+            Profile::PseudoVoigt => Profile::Gaussian,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Gaussian => "Gaussian",
+            Profile::Lorentzian => "Lorentzian",
+            Profile::PseudoVoigt => "pseudo-Voigt",
+        }
+    }
+
+    /// Parameters per peak: height, centre, FWHM and (pseudo-Voigt only) eta
+    pub fn param_count(self) -> usize {
+        match self {
+            Profile::PseudoVoigt => 4,
+            _ => 3,
+        }
+    }
+}
+
+/// A fitted value with its standard uncertainty
+#[derive(Clone, Copy)]
+pub struct Estimate {
+    pub value: f64,
+    pub error: f64,
+}
+
+/// Fitted parameters of one peak
+pub struct PeakFit {
+    pub height: Estimate,
+    pub center: Estimate,
+    pub fwhm: Estimate,
+    /// Lorentzian fraction (pseudo-Voigt only)
+    pub eta: Option<Estimate>,
+    pub area: Estimate,
+}
+
+/// Result of fitting one or more peaks on a linear background
+pub struct FitResult {
+    pub profile: Profile,
+    pub x_range: [f64; 2],
+    pub peaks: Vec<PeakFit>,
+    pub background: [Estimate; 2], // offset, slope
+    pub r_squared: f64,
+    pub reduced_chi2: f64,
+    pub points: usize,
+    /// Residuals weighted by 1/σ² from the Y errors
+    pub weighted: bool,
+    params: Vec<f64>,
+}
+
+impl FitResult {
+    /// Evaluate the fitted model at `x`
+    pub fn evaluate(&self, x: f64) -> f64 {
+        model(self.profile, &self.params, x)
+    }
+
+    /// Model sampled at `samples` evenly spaced X values across the fit range
+    pub fn curve(&self, samples: usize) -> Vec<(f64, f64)> {
+        let [x0, x1] = self.x_range;
+        (0..samples)
+            .map(|i| {
+                let x = x0 + (x1 - x0) * i as f64 / (samples - 1).max(1) as f64;
+                (x, self.evaluate(x))
+            })
+            .collect()
+    }
+
+    /// Plain text report of the fitted parameters
+    pub fn report(&self, source: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# Peak fit of {}\n", source));
+        out.push_str(&format!("# Profile: {}\n", self.profile.name()));
+        out.push_str(&format!(
+            "# X range: {} to {} ({} points)\n",
+            self.x_range[0], self.x_range[1], self.points
+        ));
+        out.push_str(&format!(
+            "# R^2: {:.6}  reduced chi^2: {:.6e}\n",
+            self.r_squared, self.reduced_chi2
+        ));
+        if self.weighted {
+            out.push_str("# Weighted by 1/sigma^2; uncertainties from the Y errors\n");
+        }
+        out.push_str(&format!(
+            "# Background: offset {} +/- {}, slope {} +/- {}\n",
+            self.background[0].value,
+            self.background[0].error,
+            self.background[1].value,
+            self.background[1].error
+        ));
+        out.push_str("peak\tcenter\tcenter_err\theight\theight_err\tfwhm\tfwhm_err\tarea\tarea_err\teta\teta_err\n");
+        for (i, peak) in self.peaks.iter().enumerate() {
+            let (eta, eta_err) = peak
+                .eta
+                .map(|e| (e.value.to_string(), e.error.to_string()))
+                .unwrap_or_else(|| ("-".to_string(), "-".to_string()));
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                i + 1,
+                peak.center.value,
+                peak.center.error,
+                peak.height.value,
+                peak.height.error,
+                peak.fwhm.value,
+                peak.fwhm.error,
+                peak.area.value,
+                peak.area.error,
+                eta,
+                eta_err
+            ));
+        }
+        out
+    }
+}
+
+/// Fit `profile` peaks starting from `(center, height, fwhm)` guesses to the
+/// points of `data` inside `x_range`
+///
+/// With `errors` (the Y standard error of each point of `data`) residuals are
+/// weighted by 1/σ², points without a positive error are left out, and the
+/// parameter uncertainties follow from the errors alone.
+pub fn fit_peaks(
+    data: &[(f64, f64)],
+    errors: Option<&[f64]>,
+    x_range: [f64; 2],
+    guesses: &[(f64, f64, f64)],
+    profile: Profile,
+) -> Option<FitResult> {
+    let (points, weights): (Vec<(f64, f64)>, Vec<f64>) = data
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.0 >= x_range[0] && p.0 <= x_range[1])
+        .filter_map(|(i, &p)| match errors {
+            Some(errors) => {
+                let sigma = errors.get(i).copied().unwrap_or(f64::NAN);
+                (sigma.is_finite() && sigma > 0.0).then(|| (p, 1.0 / (sigma * sigma)))
+            }
+            None => Some((p, 1.0)),
+        })
+        .unzip();
+    let weighted = errors.is_some();
+
+    let per_peak = profile.param_count();
+    let param_count = 2 + per_peak * guesses.len();
+    if guesses.is_empty() || points.len() <= param_count {
+        return None;
+    }
+
+    let x_span = x_range[1] - x_range[0];
+    let y_min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+
+    // Fit against X centred on the range and scaled to ±1, so the fit
+    // behaves the same whatever the units of X
+    let x_mid = (x_range[0] + x_range[1]) / 2.0;
+    let x_scale = if x_span > 0.0 { x_span / 2.0 } else { 1.0 };
+    let scaled: Vec<(f64, f64)> = points
+        .iter()
+        .map(|&(x, y)| ((x - x_mid) / x_scale, y))
+        .collect();
+
+    let mut params = vec![y_min, 0.0];
+    for &(center, height, fwhm) in guesses {
+        params.extend([
+            height - y_min,
+            (center - x_mid) / x_scale,
+            fwhm.max(x_span * 1e-3) / x_scale,
+        ]);
+        if profile == Profile::PseudoVoigt {
+            params.push(0.5);
+        }
+    }
+
+    let params = levenberg_marquardt(&scaled, &weights, profile, params)?;
+    let chi2 = chi_squared(&scaled, &weights, profile, &params);
+    let dof = (points.len() - param_count) as f64;
+    let reduced_chi2 = chi2 / dof;
+
+    // Covariance from the Jacobian at the solution; unweighted fits estimate
+    // the point scatter from the residuals
+    let jacobian = jacobian(&scaled, &weights, profile, &params);
+    let (jtj, _) = normal_equations(&jacobian, &vec![0.0; points.len()]);
+    let covariance = invert(jtj)?;
+    let (params, covariance) = unscale_x(profile, &params, &covariance, x_mid, x_scale);
+    let scale = if weighted { 1.0 } else { reduced_chi2 };
+    let errors: Vec<f64> = (0..param_count)
+        .map(|i| (covariance[i][i] * scale).max(0.0).sqrt())
+        .collect();
+    let estimate = |i: usize| Estimate {
+        value: params[i],
+        error: errors[i],
+    };
+
+    let weight_sum: f64 = weights.iter().sum();
+    let y_mean = points
+        .iter()
+        .zip(&weights)
+        .map(|(p, w)| w * p.1)
+        .sum::<f64>()
+        / weight_sum;
+    let total: f64 = points
+        .iter()
+        .zip(&weights)
+        .map(|(p, w)| w * (p.1 - y_mean).powi(2))
+        .sum();
+    let r_squared = if total > 0.0 { 1.0 - chi2 / total } else { 1.0 };
+
+    let peaks = (0..guesses.len())
+        .map(|k| {
+            let base = 2 + k * per_peak;
+            let height = estimate(base);
+            let fwhm = estimate(base + 2);
+            let eta = (profile == Profile::PseudoVoigt).then(|| estimate(base + 3));
+            // The pseudo-Voigt factor is linear in η
+            let d_factor = area_factor(profile, 1.0) - area_factor(profile, 0.0);
+            let area_factor = area_factor(profile, eta.map_or(0.0, |e| e.value));
+            let area_value = height.value * fwhm.value * area_factor;
+            // Height and width are strongly anti-correlated, so the area error
+            // follows the full covariance: gᵀ C g with g = ∂area/∂(h, w, η)
+            let mut gradient = vec![
+                (base, fwhm.value * area_factor),
+                (base + 2, height.value * area_factor),
+            ];
+            if eta.is_some() {
+                gradient.push((base + 3, height.value * fwhm.value * d_factor));
+            }
+            let variance: f64 = gradient
+                .iter()
+                .flat_map(|&(i, gi)| gradient.iter().map(move |&(j, gj)| (i, j, gi * gj)))
+                .map(|(i, j, g)| g * covariance[i][j])
+                .sum();
+            PeakFit {
+                height,
+                center: estimate(base + 1),
+                fwhm,
+                eta,
+                area: Estimate {
+                    value: area_value,
+                    error: (variance * scale).max(0.0).sqrt(),
+                },
+            }
+        })
+        .collect();
+
+    Some(FitResult {
+        profile,
+        x_range,
+        peaks,
+        background: [estimate(0), estimate(1)],
+        r_squared,
+        reduced_chi2,
+        points: points.len(),
+        weighted,
+        params,
+    })
+}
+
+/// Area of a unit-height, unit-FWHM peak
+fn area_factor(profile: Profile, eta: f64) -> f64 {
+    let gaussian = (PI / (4.0 * LN_2)).sqrt();
+    let lorentzian = PI / 2.0;
+    match profile {
+        Profile::Gaussian => gaussian,
+        Profile::Lorentzian => lorentzian,
+        Profile::PseudoVoigt => eta * lorentzian + (1.0 - eta) * gaussian,
+    }
+}
+
+/// Parameters and covariance of a fit against `(x - x_mid) / x_scale`,
+/// carried back to X. The map is linear, `p = T p' + c`, so the covariance
+/// becomes `T C Tᵀ`.
+fn unscale_x(
+    profile: Profile,
+    params: &[f64],
+    covariance: &[Vec<f64>],
+    x_mid: f64,
+    x_scale: f64,
+) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = params.len();
+    let mut transform = vec![vec![0.0; n]; n];
+    let mut offset = vec![0.0; n];
+    // a' + b'·(x - x_mid)/x_scale = (a' - b'·x_mid/x_scale) + (b'/x_scale)·x
+    transform[0][0] = 1.0;
+    transform[0][1] = -x_mid / x_scale;
+    transform[1][1] = 1.0 / x_scale;
+    for base in (2..n).step_by(profile.param_count()) {
+        transform[base][base] = 1.0;
+        transform[base + 1][base + 1] = x_scale;
+        offset[base + 1] = x_mid;
+        transform[base + 2][base + 2] = x_scale;
+        if profile == Profile::PseudoVoigt {
+            transform[base + 3][base + 3] = 1.0;
+        }
+    }
+
+    let unscaled = (0..n)
+        .map(|i| offset[i] + (0..n).map(|j| transform[i][j] * params[j]).sum::<f64>())
+        .collect();
+    let product: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| (0..n).map(|k| transform[i][k] * covariance[k][j]).sum())
+                .collect()
+        })
+        .collect();
+    let covariance = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| (0..n).map(|k| product[i][k] * transform[j][k]).sum())
+                .collect()
+        })
+        .collect();
+    (unscaled, covariance)
+}
+
+/// Linear background plus the sum of all peaks
+fn model(profile: Profile, params: &[f64], x: f64) -> f64 {
+    let mut y = params[0] + params[1] * x;
+    for peak in params[2..].chunks_exact(profile.param_count()) {
+        let (height, center, fwhm) = (peak[0], peak[1], peak[2]);
+        let u = (x - center) / fwhm;
+        let gaussian = (-4.0 * LN_2 * u * u).exp();
+        let lorentzian = 1.0 / (1.0 + 4.0 * u * u);
+        y += height
+            * match profile {
+                Profile::Gaussian => gaussian,
+                Profile::Lorentzian => lorentzian,
+                Profile::PseudoVoigt => peak[3] * lorentzian + (1.0 - peak[3]) * gaussian,
+            };
+    }
+    y
+}
+
+fn chi_squared(points: &[(f64, f64)], weights: &[f64], profile: Profile, params: &[f64]) -> f64 {
+    points
+        .iter()
+        .zip(weights)
+        .map(|(&(x, y), w)| w * (y - model(profile, params, x)).powi(2))
+        .sum()
+}
+
+/// Keep widths positive and the pseudo-Voigt mixing inside [0, 1]
+fn constrain(profile: Profile, params: &mut [f64]) {
+    for peak in params[2..].chunks_exact_mut(profile.param_count()) {
+        peak[2] = peak[2].abs().max(f64::MIN_POSITIVE);
+        if profile == Profile::PseudoVoigt {
+            peak[3] = peak[3].clamp(0.0, 1.0);
+        }
+    }
+}
+
+/// Analytic Jacobian of the model, one row per point scaled by √weight
+fn jacobian(
+    points: &[(f64, f64)],
+    weights: &[f64],
+    profile: Profile,
+    params: &[f64],
+) -> Vec<Vec<f64>> {
+    points
+        .iter()
+        .zip(weights)
+        .map(|(&(x, _), w)| {
+            let mut row = vec![1.0, x];
+            for peak in params[2..].chunks_exact(profile.param_count()) {
+                let (height, center, fwhm) = (peak[0], peak[1], peak[2]);
+                let u = (x - center) / fwhm;
+                let gaussian = (-4.0 * LN_2 * u * u).exp();
+                let lorentzian = 1.0 / (1.0 + 4.0 * u * u);
+                let d_gaussian = -8.0 * LN_2 * u * gaussian;
+                let d_lorentzian = -8.0 * u * lorentzian * lorentzian;
+                let (shape, d_shape) = match profile {
+                    Profile::Gaussian => (gaussian, d_gaussian),
+                    Profile::Lorentzian => (lorentzian, d_lorentzian),
+                    Profile::PseudoVoigt => (
+                        peak[3] * lorentzian + (1.0 - peak[3]) * gaussian,
+                        peak[3] * d_lorentzian + (1.0 - peak[3]) * d_gaussian,
+                    ),
+                };
+                row.push(shape);
+                row.push(-height * d_shape / fwhm);
+                row.push(-height * d_shape * u / fwhm);
+                if profile == Profile::PseudoVoigt {
+                    row.push(height * (lorentzian - gaussian));
+                }
+            }
+            let scale = w.sqrt();
+            row.iter_mut().for_each(|v| *v *= scale);
+            row
+        })
+        .collect()
+}
+
+/// JᵀJ and Jᵀr
+fn normal_equations(jacobian: &[Vec<f64>], residuals: &[f64]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let m = jacobian.first().map_or(0, Vec::len);
+    let mut jtj = vec![vec![0.0; m]; m];
+    let mut jtr = vec![0.0; m];
+    for (row, r) in jacobian.iter().zip(residuals) {
+        for i in 0..m {
+            jtr[i] += row[i] * r;
+            for j in 0..=i {
+                jtj[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    // Fill the upper triangle from the lower one
+    let lower = jtj.clone();
+    for (j, row) in jtj.iter_mut().enumerate() {
+        for (i, value) in row.iter_mut().enumerate().skip(j + 1) {
+            *value = lower[i][j];
+        }
+    }
+    (jtj, jtr)
+}
+
+fn levenberg_marquardt(
+    points: &[(f64, f64)],
+    weights: &[f64],
+    profile: Profile,
+    mut params: Vec<f64>,
+) -> Option<Vec<f64>> {
+    let mut lambda = 1e-3;
+    let mut chi2 = chi_squared(points, weights, profile, &params);
+
+    for _ in 0..200 {
+        let jacobian = jacobian(points, weights, profile, &params);
+        let residuals: Vec<f64> = points
+            .iter()
+            .zip(weights)
+            .map(|(&(x, y), w)| w.sqrt() * (y - model(profile, &params, x)))
+            .collect();
+        let (jtj, jtr) = normal_equations(&jacobian, &residuals);
+
+        // Retry with heavier damping until the step improves the fit
+        loop {
+            let mut damped = jtj.clone();
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += lambda * jtj[i][i].max(1e-12);
+            }
+            let Some(step) = solve_normal(damped, jtr.clone()) else {
+                lambda *= 10.0;
+                if lambda > 1e12 {
+                    return Some(params);
+                }
+                continue;
+            };
+            let mut candidate: Vec<f64> = params.iter().zip(&step).map(|(p, s)| p + s).collect();
+            constrain(profile, &mut candidate);
+            let candidate_chi2 = chi_squared(points, weights, profile, &candidate);
+
+            if candidate_chi2.is_finite() && candidate_chi2 <= chi2 {
+                let improvement = (chi2 - candidate_chi2) / chi2.max(f64::MIN_POSITIVE);
+                params = candidate;
+                chi2 = candidate_chi2;
+                lambda = (lambda / 10.0).max(1e-12);
+                if improvement < 1e-9 {
+                    return Some(params);
+                }
+                break;
+            }
+            lambda *= 10.0;
+            if lambda > 1e12 {
+                return Some(params);
+            }
+        }
+    }
+    params.iter().all(|p| p.is_finite()).then_some(params)
+}
+
+/// Solve `a x = b` by Gaussian elimination with partial pivoting
+///
+/// Returns `None` when a pivot falls to rounding level relative to the
+/// largest entry, so nearly singular systems are reported, not solved.
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    let max_abs = a.iter().flatten().map(|v| v.abs()).fold(0.0, f64::max);
+    let tolerance = max_abs * n as f64 * f64::EPSILON;
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() <= tolerance {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for (offset, row) in lower.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot_value;
+            }
+            b[col + 1 + offset] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Solve normal equations `a x = b` after scaling `a` to a unit diagonal,
+/// so the singularity test in `solve` does not depend on the parameters'
+/// units; `None` when a parameter has no effect on the model
+fn solve_normal(a: Vec<Vec<f64>>, b: Vec<f64>) -> Option<Vec<f64>> {
+    let scale: Vec<f64> = (0..b.len())
+        .map(|i| {
+            let diagonal = a[i][i];
+            (diagonal > 0.0 && diagonal.is_finite()).then(|| 1.0 / diagonal.sqrt())
+        })
+        .collect::<Option<_>>()?;
+    let a = a
+        .iter()
+        .zip(&scale)
+        .map(|(row, si)| row.iter().zip(&scale).map(|(v, sj)| v * si * sj).collect())
+        .collect();
+    let b = b.iter().zip(&scale).map(|(v, s)| v * s).collect();
+    let x = solve(a, b)?;
+    Some(x.iter().zip(&scale).map(|(x, s)| x * s).collect())
+}
+
+/// Inverse of a normal-equations matrix, column by column
+fn invert(a: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut columns = Vec::with_capacity(n);
+    for i in 0..n {
+        let mut unit = vec![0.0; n];
+        unit[i] = 1.0;
+        columns.push(solve_normal(a.clone(), unit)?);
+    }
+    Some(
+        (0..n)
+            .map(|row| columns.iter().map(|col| col[row]).collect())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples of `profile` with height 5, centre 10 and FWHM 2 on the
+    /// background 1 + 0.1x
+    fn exact_peak(profile: Profile) -> Vec<(f64, f64)> {
+        (0..=200)
+            .map(|i| {
+                let x = i as f64 * 0.1;
+                (x, model(profile, &[1.0, 0.1, 5.0, 10.0, 2.0], x))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_exact_peaks() {
+        for profile in [Profile::Gaussian, Profile::Lorentzian] {
+            let data = exact_peak(profile);
+            let fit = fit_peaks(&data, None, [0.0, 20.0], &[(9.5, 5.5, 3.0)], profile).unwrap();
+            let peak = &fit.peaks[0];
+            assert!((peak.height.value - 5.0).abs() < 1e-6, "{}", profile.name());
+            assert!(
+                (peak.center.value - 10.0).abs() < 1e-6,
+                "{}",
+                profile.name()
+            );
+            assert!((peak.fwhm.value - 2.0).abs() < 1e-6, "{}", profile.name());
+            assert!((fit.background[0].value - 1.0).abs() < 1e-6);
+            assert!((fit.background[1].value - 0.1).abs() < 1e-6);
+            assert!(fit.r_squared > 1.0 - 1e-9);
+        }
+    }
+
+    #[test]
+    fn areas_match_the_closed_forms() {
+        // Gaussian: h·w·√(π / 4 ln 2); Lorentzian: h·w·π/2
+        let gaussian = 5.0 * 2.0 * (PI / (4.0 * LN_2)).sqrt();
+        let lorentzian = 5.0 * 2.0 * PI / 2.0;
+        for (profile, area) in [
+            (Profile::Gaussian, gaussian),
+            (Profile::Lorentzian, lorentzian),
+        ] {
+            let data = exact_peak(profile);
+            let fit = fit_peaks(&data, None, [0.0, 20.0], &[(10.0, 6.0, 2.0)], profile).unwrap();
+            assert!(
+                (fit.peaks[0].area.value - area).abs() < 1e-6,
+                "{}",
+                profile.name()
+            );
+        }
+    }
+
+    #[test]
+    fn area_error_follows_the_covariance() {
+        // Pseudo-Voigt with a deterministic ripple standing in for noise
+        let data: Vec<(f64, f64)> = (0..=200)
+            .map(|i| {
+                let x = i as f64 * 0.1;
+                let y = model(Profile::PseudoVoigt, &[1.0, 0.1, 5.0, 10.0, 2.0, 0.3], x);
+                (x, y + 0.05 * (i as f64 * 2.7).sin())
+            })
+            .collect();
+        let fit = fit_peaks(
+            &data,
+            None,
+            [0.0, 20.0],
+            &[(10.0, 6.0, 2.0)],
+            Profile::PseudoVoigt,
+        )
+        .unwrap();
+
+        // Propagate through the covariance with a central-difference gradient
+        let weights = vec![1.0; data.len()];
+        let jacobian = jacobian(&data, &weights, Profile::PseudoVoigt, &fit.params);
+        let (jtj, _) = normal_equations(&jacobian, &vec![0.0; data.len()]);
+        let covariance = invert(jtj).unwrap();
+        let area = |p: &[f64]| p[2] * p[4] * area_factor(Profile::PseudoVoigt, p[5]);
+        let gradient: Vec<f64> = (0..fit.params.len())
+            .map(|i| {
+                let h = 1e-6 * fit.params[i].abs().max(1.0);
+                let mut up = fit.params.clone();
+                let mut down = fit.params.clone();
+                up[i] += h;
+                down[i] -= h;
+                (area(&up) - area(&down)) / (2.0 * h)
+            })
+            .collect();
+        let variance: f64 = (0..gradient.len())
+            .flat_map(|i| (0..gradient.len()).map(move |j| (i, j)))
+            .map(|(i, j)| gradient[i] * covariance[i][j] * gradient[j])
+            .sum();
+        let expected = (variance * fit.reduced_chi2).sqrt();
+        let error = fit.peaks[0].area.error;
+        assert!(error > 0.0);
+        assert!(
+            (error - expected).abs() < 1e-6 * expected,
+            "{error} vs {expected}"
+        );
+    }
+
+    #[test]
+    fn fits_do_not_depend_on_the_units_of_x() {
+        // The same Gaussian with X in units from 1e-12 to 1e9, some far from zero
+        let data = exact_peak(Profile::Gaussian);
+        for (scale, shift) in [
+            (1e-12, 0.0),
+            (1e-9, 0.0),
+            (1e6, 0.0),
+            (1e9, 0.0),
+            (1.0, 1e6),
+        ] {
+            let scaled: Vec<(f64, f64)> =
+                data.iter().map(|&(x, y)| (x * scale + shift, y)).collect();
+            let fit = fit_peaks(
+                &scaled,
+                None,
+                [shift, 20.0 * scale + shift],
+                &[(9.5 * scale + shift, 5.5, 3.0 * scale)],
+                Profile::Gaussian,
+            )
+            .unwrap_or_else(|| panic!("no fit at scale {scale}, shift {shift}"));
+            let peak = &fit.peaks[0];
+            assert!((peak.height.value - 5.0).abs() < 1e-6, "{scale}");
+            assert!(
+                (peak.center.value - (10.0 * scale + shift)).abs() < 1e-6 * scale,
+                "{scale}"
+            );
+            assert!(
+                (peak.fwhm.value - 2.0 * scale).abs() < 1e-6 * scale,
+                "{scale}"
+            );
+            // y = 1 + 0.1·(x - shift)/scale, peaking at 7
+            assert!((fit.background[1].value - 0.1 / scale).abs() < 1e-6 / scale);
+            let intercept = 1.0 - 0.1 * shift / scale;
+            assert!((fit.background[0].value - intercept).abs() < 1e-6 * intercept.abs().max(1.0));
+            assert!((fit.evaluate(10.0 * scale + shift) - 7.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn identical_guesses_report_a_degenerate_fit() {
+        let data = exact_peak(Profile::Gaussian);
+        let guesses = [(10.0, 6.0, 2.0), (10.0, 6.0, 2.0)];
+        assert!(fit_peaks(&data, None, [0.0, 20.0], &guesses, Profile::Gaussian).is_none());
+    }
+
+    #[test]
+    fn solve_handles_pivoting_and_singular_systems() {
+        let a = vec![
+            vec![0.0, 2.0, 1.0],
+            vec![1.0, 1.0, 0.0],
+            vec![2.0, 0.0, 3.0],
+        ];
+        // Solution (1, 2, 3)
+        let x = solve(a, vec![7.0, 3.0, 11.0]).unwrap();
+        for (value, expected) in x.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+        assert!(solve(vec![vec![1.0, 2.0], vec![2.0, 4.0]], vec![1.0, 2.0]).is_none());
+        // A pivot at rounding level of the largest entry is singular too
+        let nearly = vec![vec![1e8, 1e8], vec![1.0, 1.0 + 1e-12]];
+        assert!(solve(nearly, vec![1.0, 2.0]).is_none());
+    }
+}
@@ -0,0 +1,314 @@
+//! Heatmaps of matrix-shaped and gridded x/y/z data
+
+use ratatui::style::Color;
+
+use crate::data::DataTable;
+
+/// Fewest columns for a headerless table to count as a matrix
+const MATRIX_MIN_COLUMNS: usize = 8;
+
+/// Coordinates closer than this fraction of the largest step are one lattice line
+const COORDINATE_TOLERANCE: f64 = 1e-3;
+
+/// Colour scale from low to high values
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Inferno,
+    Grayscale,
+}
+
+/// Evenly spaced stops of the matplotlib colormaps
+const VIRIDIS: [(u8, u8, u8); 9] = [
+    (68, 1, 84),
+    (71, 45, 123),
+    (59, 82, 139),
+    (44, 114, 142),
+    (33, 145, 140),
+    (40, 174, 128),
+    (94, 201, 98),
+    (173, 220, 48),
+    (253, 231, 37),
+];
+const INFERNO: [(u8, u8, u8); 9] = [
+    (0, 0, 4),
+    (31, 12, 72),
+    (85, 15, 109),
+    (136, 34, 106),
+    (186, 54, 85),
+    (227, 89, 51),
+    (249, 140, 10),
+    (249, 201, 50),
+    (252, 255, 164),
+];
+
+impl Colormap {
+    pub fn next(self) -> Colormap {
+        match self {
+            Colormap::Viridis => Colormap::Inferno,
+            Colormap::Inferno => Colormap::Grayscale,
+            Colormap::Grayscale => Colormap::Viridis,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Inferno => "inferno",
+            Colormap::Grayscale => "grayscale",
+        }
+    }
+
+    /// Colour at `t` in [0, 1]; values outside are clamped
+    pub fn color(self, t: f64) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let stops = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Inferno => &INFERNO,
+            Colormap::Grayscale => &[(0, 0, 0), (255, 255, 255)][..],
+        };
+        let pos = t * (stops.len() - 1) as f64;
+        let i = (pos as usize).min(stops.len() - 2);
+        let frac = pos - i as f64;
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * frac).round() as u8;
+        let (a, b) = (stops[i], stops[i + 1]);
+        Color::Rgb(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+    }
+}
+
+/// Where the grid came from
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GridKind {
+    /// Headerless table of values; coordinates are column and row indices
+    Matrix,
+    /// Three columns of x, y and z covering every x/y combination
+    Xyz,
+}
+
+/// Values on a regular grid
+pub struct Grid {
+    pub kind: GridKind,
+    /// Coordinate of each grid column
+    pub x: Vec<f64>,
+    /// Coordinate of each grid row, top row first
+    pub y: Vec<f64>,
+    /// `values[row][col]` with row 0 at the top, NaN where missing
+    pub values: Vec<Vec<f64>>,
+    /// Range of the finite values
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Grid {
+    pub fn width(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.y.len()
+    }
+
+    /// Value scaled into [0, 1] across the grid's range
+    pub fn normalized(&self, value: f64) -> f64 {
+        if self.max > self.min {
+            (value - self.min) / (self.max - self.min)
+        } else {
+            0.5
+        }
+    }
+}
+
+/// Grid for a matrix-shaped table or gridded x/y/z triplets, if the table is one
+pub fn detect(table: &DataTable) -> Option<Grid> {
+    let rows = table.source_lines.len();
+    if rows < 2 {
+        return None;
+    }
+    let complete = table.unparsed.iter().all(|&n| n == 0);
+    if table.column_count() == 3 {
+        xyz_grid(table)
+    } else if !table.named && complete && table.column_count() >= MATRIX_MIN_COLUMNS {
+        Some(matrix_grid(table))
+    } else {
+        None
+    }
+}
+
+/// File rows become grid rows, top to bottom as in the file
+fn matrix_grid(table: &DataTable) -> Grid {
+    let rows = table.source_lines.len();
+    let values: Vec<Vec<f64>> = (0..rows)
+        .map(|row| table.columns.iter().map(|column| column[row]).collect())
+        .collect();
+    with_range(Grid {
+        kind: GridKind::Matrix,
+        x: (0..table.column_count()).map(|c| c as f64).collect(),
+        y: (0..rows).map(|r| r as f64).collect(),
+        values,
+        min: f64::NAN,
+        max: f64::NAN,
+    })
+}
+
+/// Requires every row to land on its own cell of the x/y lattice
+fn xyz_grid(table: &DataTable) -> Option<Grid> {
+    let (xs, ys, zs) = (&table.columns[0], &table.columns[1], &table.columns[2]);
+    let x = lattice(xs);
+    let y_ascending = lattice(ys);
+    if x.len() < 2 || y_ascending.len() < 2 || x.len() * y_ascending.len() != xs.len() {
+        return None;
+    }
+
+    let mut values = vec![vec![f64::NAN; x.len()]; y_ascending.len()];
+    let mut filled = vec![vec![false; x.len()]; y_ascending.len()];
+    for ((&xv, &yv), &zv) in xs.iter().zip(ys).zip(zs) {
+        if !xv.is_finite() || !yv.is_finite() {
+            return None;
+        }
+        let col = nearest(&x, xv);
+        // Largest y is the top row
+        let row = y_ascending.len() - 1 - nearest(&y_ascending, yv);
+        if filled[row][col] {
+            return None;
+        }
+        filled[row][col] = true;
+        values[row][col] = zv;
+    }
+    let mut y = y_ascending;
+    y.reverse();
+    Some(with_range(Grid {
+        kind: GridKind::Xyz,
+        x,
+        y,
+        values,
+        min: f64::NAN,
+        max: f64::NAN,
+    }))
+}
+
+/// Sorted lattice lines of one coordinate column; a value joins the current
+/// line when it is within the tolerance of the line's first value, so printing
+/// jitter does not split a line in two. Each line sits at the mean of its values.
+fn lattice(values: &[f64]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(f64::total_cmp);
+    let largest_gap = sorted
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .fold(0.0, f64::max);
+    let tolerance = COORDINATE_TOLERANCE * largest_gap;
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for i in 1..=sorted.len() {
+        if i == sorted.len() || sorted[i] - sorted[start] > tolerance {
+            let members = &sorted[start..i];
+            lines.push(members.iter().sum::<f64>() / members.len() as f64);
+            start = i;
+        }
+    }
+    lines
+}
+
+/// Index of the line closest to `value`
+fn nearest(lines: &[f64], value: f64) -> usize {
+    let above = lines.partition_point(|&line| line < value);
+    if above == 0 {
+        0
+    } else if above == lines.len() || value - lines[above - 1] <= lines[above] - value {
+        above - 1
+    } else {
+        above
+    }
+}
+
+fn with_range(mut grid: Grid) -> Grid {
+    let finite = || {
+        grid.values
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|v| v.is_finite())
+    };
+    let min = finite().fold(f64::INFINITY, f64::min);
+    let max = finite().fold(f64::NEG_INFINITY, f64::max);
+    grid.min = min;
+    grid.max = max;
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix_text(rows: usize, columns: usize) -> String {
+        (0..rows)
+            .map(|r| {
+                let row: Vec<String> = (0..columns).map(|c| (10 * r + c).to_string()).collect();
+                row.join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn headerless_wide_table_is_a_matrix() {
+        let grid = detect(&DataTable::parse(&matrix_text(3, 8))).unwrap();
+        assert!(grid.kind == GridKind::Matrix);
+        assert_eq!((grid.width(), grid.height()), (8, 3));
+        // File rows stay top to bottom
+        assert_eq!(grid.values[0][7], 7.0);
+        assert_eq!(grid.values[2][0], 20.0);
+        assert_eq!((grid.min, grid.max), (0.0, 27.0));
+    }
+
+    #[test]
+    fn ragged_narrow_or_named_tables_are_not_matrices() {
+        let ragged = format!("{}\n1 2 3", matrix_text(3, 8));
+        assert!(detect(&DataTable::parse(&ragged)).is_none());
+        assert!(detect(&DataTable::parse(&matrix_text(3, 7))).is_none());
+        let header = "a b c d e f g h";
+        let named = format!("{}\n{}", header, matrix_text(3, 8));
+        assert!(detect(&DataTable::parse(&named)).is_none());
+    }
+
+    #[test]
+    fn xyz_triplets_on_a_lattice_form_a_grid() {
+        // 3 × 2 lattice in scrambled order, z = 10x + y
+        let text = "2 1 21\n0 0 0\n1 1 11\n0 1 1\n2 0 20\n1 0 10";
+        let grid = detect(&DataTable::parse(text)).unwrap();
+        assert!(grid.kind == GridKind::Xyz);
+        assert_eq!(grid.x, [0.0, 1.0, 2.0]);
+        // Largest y is the top row
+        assert_eq!(grid.y, [1.0, 0.0]);
+        assert_eq!(grid.values[0], [1.0, 11.0, 21.0]);
+        assert_eq!(grid.values[1], [0.0, 10.0, 20.0]);
+    }
+
+    #[test]
+    fn ragged_xyz_grids_are_rejected() {
+        // A missing corner leaves 5 rows for a 3 × 2 lattice
+        let missing = "0 0 0\n1 0 10\n2 0 20\n0 1 1\n1 1 11";
+        assert!(detect(&DataTable::parse(missing)).is_none());
+        // Six rows, but one cell twice and another never
+        let repeated = "0 0 0\n1 0 10\n2 0 20\n0 1 1\n1 1 11\n1 1 12";
+        assert!(detect(&DataTable::parse(repeated)).is_none());
+    }
+
+    #[test]
+    fn jittered_coordinates_share_a_lattice_line() {
+        // Rounding noise in the last digits, as from a scan written with %.15g
+        let text = "0.1 0.2 1\n0.30000000000000004 0.2 3\n0.09999999999999999 0.4 2\n0.3 0.39999999999999997 4";
+        let grid = detect(&DataTable::parse(text)).unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert!((grid.x[0] - 0.1).abs() < 1e-12 && (grid.x[1] - 0.3).abs() < 1e-12);
+        assert_eq!(grid.values[0], [2.0, 4.0]);
+        assert_eq!(grid.values[1], [1.0, 3.0]);
+
+        // Uneven but distinct steps stay apart
+        let uneven = "1 0 0\n10 0 1\n1000 0 2\n1 5 3\n10 5 4\n1000 5 5";
+        let grid = detect(&DataTable::parse(uneven)).unwrap();
+        assert_eq!(grid.x, [1.0, 10.0, 1000.0]);
+    }
+}
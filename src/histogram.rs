@@ -0,0 +1,180 @@
+//! Histogram binning and distribution shape statistics
+
+/// Most bins a histogram may have
+pub const MAX_BINS: usize = 500;
+
+/// How the number of bins is chosen
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinRule {
+    /// Bin width 2·IQR/∛n, robust to outliers
+    #[default]
+    FreedmanDiaconis,
+    /// ⌈log₂ n⌉ + 1 bins, for roughly normal data
+    Sturges,
+    Manual(usize),
+}
+
+impl BinRule {
+    /// Cycle Freedman-Diaconis → Sturges → manual, keeping `bins` for manual
+    pub fn next(self, bins: usize) -> BinRule {
+        match self {
+            BinRule::FreedmanDiaconis => BinRule::Sturges,
+            BinRule::Sturges => BinRule::Manual(bins),
+            BinRule::Manual(_) => BinRule::FreedmanDiaconis,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BinRule::FreedmanDiaconis => "Freedman-Diaconis",
+            BinRule::Sturges => "Sturges",
+            BinRule::Manual(_) => "manual",
+        }
+    }
+}
+
+/// Equal-width bins over the value range, with moments of the values
+pub struct Histogram {
+    pub start: f64,
+    pub bin_width: f64,
+    pub counts: Vec<usize>,
+    pub rule: BinRule,
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Sample skewness g₁
+    pub skewness: f64,
+    /// Excess kurtosis g₂ (0 for a normal distribution)
+    pub kurtosis: f64,
+}
+
+impl Histogram {
+    /// Centre of bin `i`
+    pub fn bin_center(&self, i: usize) -> f64 {
+        self.start + (i as f64 + 0.5) * self.bin_width
+    }
+
+    pub fn end(&self) -> f64 {
+        self.start + self.counts.len() as f64 * self.bin_width
+    }
+}
+
+/// Bin the finite `values`; `None` when there are none
+pub fn build(values: &[f64], rule: BinRule) -> Option<Histogram> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len();
+    let min = sorted[0];
+    let max = sorted[n - 1];
+    let range = max - min;
+
+    let sturges = (n as f64).log2().ceil() as usize + 1;
+    let bins = match rule {
+        BinRule::Sturges => sturges,
+        BinRule::Manual(bins) => bins,
+        BinRule::FreedmanDiaconis => {
+            let iqr = quantile(&sorted, 0.75) - quantile(&sorted, 0.25);
+            let width = 2.0 * iqr / (n as f64).cbrt();
+            if width > 0.0 && range > 0.0 {
+                (range / width).ceil() as usize
+            } else {
+                sturges
+            }
+        }
+    }
+    .clamp(1, MAX_BINS);
+
+    // A single value still gets a bin of width 1 around it
+    let (start, bin_width) = if range > 0.0 {
+        (min, range / bins as f64)
+    } else {
+        (min - 0.5, 1.0 / bins as f64)
+    };
+    let mut counts = vec![0; bins];
+    for &v in &sorted {
+        let bin = ((v - start) / bin_width) as usize;
+        counts[bin.min(bins - 1)] += 1;
+    }
+
+    let mean = sorted.iter().sum::<f64>() / n as f64;
+    let moment = |k: i32| sorted.iter().map(|v| (v - mean).powi(k)).sum::<f64>() / n as f64;
+    let (m2, m3, m4) = (moment(2), moment(3), moment(4));
+    let (skewness, kurtosis) = if m2 > 0.0 {
+        (m3 / m2.powf(1.5), m4 / (m2 * m2) - 3.0)
+    } else {
+        (f64::NAN, f64::NAN)
+    };
+    let std_dev = if n > 1 {
+        (m2 * n as f64 / (n - 1) as f64).sqrt()
+    } else {
+        f64::NAN
+    };
+
+    Some(Histogram {
+        start,
+        bin_width,
+        counts,
+        rule,
+        count: n,
+        mean,
+        std_dev,
+        skewness,
+        kurtosis,
+    })
+}
+
+/// Linearly interpolated quantile of sorted values
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = q * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1, 2, …, 100 in a scrambled order, with a NaN that is left out
+    fn sample() -> Vec<f64> {
+        let mut values: Vec<f64> = (0..100).map(|i| ((i * 37) % 100 + 1) as f64).collect();
+        values.push(f64::NAN);
+        values
+    }
+
+    #[test]
+    fn sturges_uses_log2_of_the_count() {
+        // ⌈log₂ 100⌉ + 1 = 8
+        let hist = build(&sample(), BinRule::Sturges).unwrap();
+        assert_eq!(hist.counts.len(), 8);
+        assert_eq!(hist.count, 100);
+        assert_eq!(hist.counts.iter().sum::<usize>(), 100);
+        assert_eq!(hist.start, 1.0);
+        assert!((hist.end() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn freedman_diaconis_uses_the_interquartile_range() {
+        // IQR = 75.25 − 25.75 = 49.5, width = 2·49.5/∛100, ⌈99/width⌉ = 5
+        let hist = build(&sample(), BinRule::FreedmanDiaconis).unwrap();
+        assert_eq!(hist.counts.len(), 5);
+        assert_eq!(hist.counts, [20, 20, 20, 20, 20]);
+        assert!((hist.mean - 50.5).abs() < 1e-12);
+        assert!(hist.skewness.abs() < 1e-12);
+        // Uniform distribution: excess kurtosis near −1.2
+        assert!((hist.kurtosis + 1.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn manual_counts_are_clamped_and_constants_get_one_bin() {
+        let hist = build(&sample(), BinRule::Manual(10_000)).unwrap();
+        assert_eq!(hist.counts.len(), MAX_BINS);
+        let constant = build(&[2.0; 5], BinRule::FreedmanDiaconis).unwrap();
+        assert_eq!(constant.counts.iter().sum::<usize>(), 5);
+        assert!(constant.start < 2.0 && constant.end() > 2.0);
+        assert!(build(&[f64::NAN], BinRule::Sturges).is_none());
+    }
+}
@@ -0,0 +1,625 @@
+//! JCAMP-DX spectra: `##LABEL=value` records with ASDF-compressed data tables
+
+use crate::data::{DataBlock, DataTable};
+
+/// Labels holding data tables or structure rather than metadata
+const DATA_LABELS: [&str; 7] = [
+    "PAGE",
+    "XYDATA",
+    "XYPOINTS",
+    "PEAKTABLE",
+    "DATATABLE",
+    "END",
+    "ENDNTUPLES",
+];
+
+/// A parsed JCAMP-DX file
+pub struct Jcamp {
+    /// Metadata records in file order, as (label, value)
+    pub labels: Vec<(String, String)>,
+    pub table: DataTable,
+}
+
+/// Byte order mark some instrument software writes before the first record
+const BOM: char = '\u{feff}';
+
+/// Whether `content` is JCAMP-DX, which must open with a `##TITLE=` record
+pub fn is_jcamp(content: &str) -> bool {
+    content
+        .trim_start_matches(BOM)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .and_then(|line| line.strip_prefix("##"))
+        .and_then(|line| line.split_once('='))
+        .is_some_and(|(label, _)| normalize(label) == "TITLE")
+}
+
+/// Label in the spec's canonical form: upper case without spaces, dashes,
+/// slashes or underscores
+fn normalize(label: &str) -> String {
+    label
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '/' | '_'))
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// One decoded spectrum, a block of the table
+struct Spectrum {
+    title: String,
+    points: Vec<(f64, f64)>,
+    lines: Vec<usize>,
+    missing: usize,
+}
+
+/// Scaling of the current block, reset at every `##TITLE`
+#[derive(Default, Clone)]
+struct Params {
+    title: String,
+    first_x: Option<f64>,
+    last_x: Option<f64>,
+    npoints: Option<usize>,
+    x_factor: Option<f64>,
+    y_factor: Option<f64>,
+    x_units: Option<String>,
+    y_units: Option<String>,
+    /// NTUPLES columns: symbols and their per-variable lists
+    symbols: Vec<String>,
+    firsts: Vec<Option<f64>>,
+    lasts: Vec<Option<f64>>,
+    factors: Vec<Option<f64>>,
+    dims: Vec<Option<usize>>,
+    units: Vec<String>,
+    page: Option<String>,
+}
+
+impl Params {
+    /// Scaling for a table whose ordinate is NTUPLES symbol `y`, or the
+    /// block's own labels outside NTUPLES
+    fn for_symbol(&self, y: &str) -> Params {
+        let mut params = self.clone();
+        let index = |symbol: &str| {
+            self.symbols
+                .iter()
+                .position(|s| s.eq_ignore_ascii_case(symbol))
+        };
+        if let Some(x) = index("X") {
+            params.first_x = params.first_x.or(self.firsts[x]);
+            params.last_x = params.last_x.or(self.lasts[x]);
+            params.x_factor = params.x_factor.or(self.factors[x]);
+            params.npoints = params.npoints.or(self.dims[x]);
+            if params.x_units.is_none() && !self.units[x].is_empty() {
+                params.x_units = Some(self.units[x].clone());
+            }
+        }
+        if let Some(y) = index(y) {
+            params.y_factor = params.y_factor.or(self.factors[y]);
+            if params.y_units.is_none() && !self.units[y].is_empty() {
+                params.y_units = Some(self.units[y].clone());
+            }
+        }
+        params
+    }
+}
+
+/// A record's value split on commas, for the NTUPLES variable lists
+fn list<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Vec<Option<T>> {
+    value.split(',').map(|field| parse(field.trim())).collect()
+}
+
+/// Parse every spectrum in `content` into a two-column table, one block per
+/// spectrum or NTUPLES page
+pub fn parse(content: &str) -> Jcamp {
+    let lines: Vec<&str> = content.trim_start_matches(BOM).lines().collect();
+    let mut labels: Vec<(String, String)> = Vec::new();
+    let mut spectra: Vec<Spectrum> = Vec::new();
+    let mut params = Params::default();
+    let mut x_units: Option<String> = None;
+    let mut y_units: Option<String> = None;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((label, value)) = record(lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let key = normalize(label);
+        let start = i + 1;
+        // Data and continuation lines run up to the next record
+        let mut end = start;
+        while end < lines.len() && !lines[end].trim_start().starts_with("##") {
+            end += 1;
+        }
+        let mut value = strip_comment(value).trim().to_string();
+        if !DATA_LABELS.contains(&key.as_str()) {
+            for line in &lines[start..end] {
+                let text = strip_comment(line).trim();
+                if !text.is_empty() {
+                    value.push(' ');
+                    value.push_str(text);
+                }
+            }
+            // Metadata shown is the file header, up to the first data table
+            if spectra.is_empty() && !labels.iter().any(|(l, _)| normalize(l) == key) {
+                labels.push((label.trim().to_string(), value.clone()));
+            }
+        }
+
+        let number = || parse_number(&value);
+        match key.as_str() {
+            "TITLE" => {
+                params = Params {
+                    title: value.clone(),
+                    ..Params::default()
+                }
+            }
+            "FIRSTX" => params.first_x = number(),
+            "LASTX" => params.last_x = number(),
+            "NPOINTS" => params.npoints = number().map(|n| n as usize),
+            "XFACTOR" => params.x_factor = number(),
+            "YFACTOR" => params.y_factor = number(),
+            "XUNITS" => params.x_units = Some(value.clone()),
+            "YUNITS" => params.y_units = Some(value.clone()),
+            "SYMBOL" => params.symbols = value.split(',').map(|s| s.trim().to_string()).collect(),
+            "FIRST" => params.firsts = list(&value, parse_number),
+            "LAST" => params.lasts = list(&value, parse_number),
+            "FACTOR" => params.factors = list(&value, parse_number),
+            "VARDIM" => params.dims = list(&value, |f| parse_number(f).map(|n| n as usize)),
+            "UNITS" => params.units = value.split(',').map(|s| s.trim().to_string()).collect(),
+            "PAGE" => {
+                params.page = Some(value.clone());
+                // Per-page labels apply to this page only
+                params.npoints = None;
+                params.first_x = None;
+                params.last_x = None;
+            }
+            "XYDATA" | "XYPOINTS" | "PEAKTABLE" | "DATATABLE" => {
+                let form = value.to_uppercase().replace(' ', "");
+                let (y_symbol, stride, dense) = variable_list(&form);
+                // Keep the NTUPLES lists as long as the symbols
+                let n = params.symbols.len();
+                params.firsts.resize(n, None);
+                params.lasts.resize(n, None);
+                params.factors.resize(n, None);
+                params.dims.resize(n, None);
+                params.units.resize(n, String::new());
+                let table = params.for_symbol(&y_symbol);
+                let mut spectrum = if dense {
+                    decode_dense(&lines[start..end], start, &table)
+                } else {
+                    decode_pairs(&lines[start..end], start, stride, &table)
+                };
+                spectrum.title = match &params.page {
+                    Some(page) if !params.title.is_empty() => {
+                        format!("{} · {}", params.title, page)
+                    }
+                    Some(page) => page.clone(),
+                    None => params.title.clone(),
+                };
+                if x_units.is_none() {
+                    x_units = table.x_units.clone();
+                }
+                if y_units.is_none() {
+                    y_units = table.y_units.clone();
+                }
+                if !spectrum.points.is_empty() {
+                    spectra.push(spectrum);
+                }
+            }
+            _ => {}
+        }
+        i = end;
+    }
+
+    Jcamp {
+        labels,
+        table: build_table(spectra, x_units, y_units),
+    }
+}
+
+/// Label and value of a `##LABEL=value` line
+fn record(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim_start().strip_prefix("##")?;
+    // `##$$` and `##=` lines are comments
+    if rest.starts_with("$$") {
+        return None;
+    }
+    rest.split_once('=')
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.find("$$").map_or(line, |i| &line[..i])
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    strip_comment(text).trim().parse().ok()
+}
+
+/// Ordinate symbol, values per group and whether X is implicit, from a
+/// variable list such as `(X++(Y..Y))` or `(XY..XY)`
+fn variable_list(form: &str) -> (String, usize, bool) {
+    let form = form.split(',').next().unwrap_or(form);
+    if let Some(open) = form.find("++(") {
+        let y: String = form[open + 3..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        return (y, 1, true);
+    }
+    let group: String = form
+        .trim_start_matches('(')
+        .split("..")
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    let y = group
+        .chars()
+        .nth(1)
+        .map(String::from)
+        .unwrap_or_else(|| "Y".into());
+    (y, group.len().max(2), false)
+}
+
+/// One value of an ASDF data line
+#[derive(Clone, Copy)]
+enum Token {
+    /// Absolute value, AFFN or SQZ
+    Value(f64),
+    /// Difference from the previous value
+    Dif(f64),
+    /// Repeat count of the previous token, including itself
+    Dup(usize),
+    Missing,
+}
+
+/// Split a data line into tokens; SQZ, DIF and DUP characters stand for the
+/// sign and first digit of a number, so they start a new token
+fn tokenize(line: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    // Kind of the token being built and its digits so far
+    let mut current: Option<(char, String)> = None;
+    let finish = |current: &mut Option<(char, String)>, tokens: &mut Vec<Token>| {
+        if let Some((kind, text)) = current.take() {
+            let value: f64 = text.parse().unwrap_or(f64::NAN);
+            tokens.push(match kind {
+                'd' => Token::Dif(value),
+                'u' => Token::Dup(value as usize),
+                _ => Token::Value(value),
+            });
+        }
+    };
+
+    let chars: Vec<char> = strip_comment(line).chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        let digit = |d: u32, sign: &str| format!("{}{}", sign, d);
+        let start = match c {
+            '0'..='9' | '.' => {
+                match &mut current {
+                    Some((_, text)) => text.push(c),
+                    None => current = Some(('v', c.to_string())),
+                }
+                continue;
+            }
+            // Exponent of an AFFN number, as in 1.5E+03
+            'E' | 'e'
+                if matches!(current, Some(('v', _)))
+                    && matches!(chars.get(i + 1), Some('+' | '-')) =>
+            {
+                if let Some((_, text)) = &mut current {
+                    text.push('e');
+                }
+                continue;
+            }
+            '+' | '-' => {
+                let exponent = current.as_ref().is_some_and(|(_, t)| t.ends_with('e'));
+                if exponent {
+                    if let Some((_, text)) = &mut current {
+                        text.push(c);
+                    }
+                    continue;
+                }
+                Some(('v', c.to_string()))
+            }
+            '@' => Some(('v', "0".to_string())),
+            'A'..='I' => Some(('v', digit(c as u32 - 'A' as u32 + 1, ""))),
+            'a'..='i' => Some(('v', digit(c as u32 - 'a' as u32 + 1, "-"))),
+            '%' => Some(('d', "0".to_string())),
+            'J'..='R' => Some(('d', digit(c as u32 - 'J' as u32 + 1, ""))),
+            'j'..='r' => Some(('d', digit(c as u32 - 'j' as u32 + 1, "-"))),
+            'S'..='Z' => Some(('u', digit(c as u32 - 'S' as u32 + 1, ""))),
+            's' => Some(('u', "9".to_string())),
+            '?' => {
+                finish(&mut current, &mut tokens);
+                tokens.push(Token::Missing);
+                None
+            }
+            _ => None,
+        };
+        finish(&mut current, &mut tokens);
+        current = start;
+    }
+    finish(&mut current, &mut tokens);
+    tokens
+}
+
+/// Ordinates of a `(X++(Y..Y))` line after expanding DIF and DUP, whether it
+/// ended in difference form, and the count of missing values
+fn ordinates(tokens: &[Token], previous: f64) -> (Vec<f64>, bool, usize) {
+    let mut ys: Vec<f64> = Vec::new();
+    let mut last: Option<Token> = None;
+    let mut missing = 0;
+    for &token in tokens {
+        match token {
+            Token::Value(v) => ys.push(v),
+            Token::Dif(d) => {
+                let base = ys.last().copied().unwrap_or(previous);
+                ys.push(base + d);
+            }
+            Token::Dup(count) => {
+                for _ in 1..count {
+                    match last {
+                        Some(Token::Dif(d)) => {
+                            let base = ys.last().copied().unwrap_or(previous);
+                            ys.push(base + d);
+                        }
+                        Some(Token::Missing) => {
+                            ys.push(f64::NAN);
+                            missing += 1;
+                        }
+                        _ => {
+                            if let Some(&y) = ys.last() {
+                                ys.push(y);
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+            Token::Missing => {
+                ys.push(f64::NAN);
+                missing += 1;
+            }
+        }
+        last = Some(token);
+    }
+    (ys, matches!(last, Some(Token::Dif(_))), missing)
+}
+
+/// Decode `(X++(Y..Y))` lines: each starts with the X of its first ordinate,
+/// and X steps evenly from FIRSTX to LASTX across all points
+fn decode_dense(lines: &[&str], first_line: usize, params: &Params) -> Spectrum {
+    let x_factor = params.x_factor.unwrap_or(1.0);
+    let y_factor = params.y_factor.unwrap_or(1.0);
+    // Per line: its X, its source line and its ordinates
+    let mut rows: Vec<(f64, usize, Vec<f64>)> = Vec::new();
+    let mut missing = 0;
+    let mut previous = f64::NAN;
+    let mut dif = false;
+    for (offset, line) in lines.iter().enumerate() {
+        let tokens = tokenize(line);
+        let Some((&Token::Value(x), rest)) = tokens.split_first() else {
+            continue;
+        };
+        let (mut ys, ends_dif, line_missing) = ordinates(rest, previous);
+        missing += line_missing;
+        let mut x = x * x_factor;
+        // After a DIF line the first ordinate repeats the last one as a check
+        if dif && !ys.is_empty() {
+            ys.remove(0);
+            if let Some((last_x, _, last_ys)) = rows.last() {
+                let step = (x - last_x) / last_ys.len().saturating_sub(1).max(1) as f64;
+                x += step;
+            }
+        }
+        if let Some(&y) = ys.last() {
+            previous = y;
+        }
+        dif = ends_dif;
+        // A closing line may hold only the check value
+        if !ys.is_empty() {
+            rows.push((x, first_line + offset, ys));
+        }
+    }
+
+    let total: usize = rows.iter().map(|(_, _, ys)| ys.len()).sum();
+    let even = match (
+        params.first_x,
+        params.last_x,
+        params.npoints.unwrap_or(total),
+    ) {
+        (Some(first), Some(last), n) if n >= 2 => Some((first, (last - first) / (n - 1) as f64)),
+        _ => None,
+    };
+
+    let mut points = Vec::with_capacity(total);
+    let mut source = Vec::with_capacity(total);
+    for (row, (x, line, ys)) in rows.iter().enumerate() {
+        // Without FIRSTX/LASTX, step by the gap to the next line's X, or
+        // from the line before on the last line
+        let step = match (rows.get(row + 1), row.checked_sub(1).map(|r| &rows[r])) {
+            (Some((next, _, _)), _) => (next - x) / ys.len().max(1) as f64,
+            (None, Some((before, _, before_ys))) => (x - before) / before_ys.len().max(1) as f64,
+            (None, None) => 1.0,
+        };
+        for (j, &y) in ys.iter().enumerate() {
+            let x = match even {
+                Some((first, delta)) => first + points.len() as f64 * delta,
+                None => x + j as f64 * step,
+            };
+            points.push((x, y * y_factor));
+            source.push(*line);
+        }
+    }
+    Spectrum {
+        title: String::new(),
+        points,
+        lines: source,
+        missing,
+    }
+}
+
+/// Decode `(XY..XY)` style lines, `stride` values per point of which the
+/// first two are X and Y
+fn decode_pairs(lines: &[&str], first_line: usize, stride: usize, params: &Params) -> Spectrum {
+    let x_factor = params.x_factor.unwrap_or(1.0);
+    let y_factor = params.y_factor.unwrap_or(1.0);
+    let mut points = Vec::new();
+    let mut source = Vec::new();
+    let mut missing = 0;
+    for (offset, line) in lines.iter().enumerate() {
+        let values: Vec<f64> = tokenize(line)
+            .iter()
+            .map(|token| match token {
+                Token::Value(v) => *v,
+                _ => f64::NAN,
+            })
+            .collect();
+        for group in values.chunks(stride) {
+            if let [x, y, ..] = *group {
+                if y.is_nan() {
+                    missing += 1;
+                }
+                points.push((x * x_factor, y * y_factor));
+                source.push(first_line + offset);
+            }
+        }
+    }
+    Spectrum {
+        title: String::new(),
+        points,
+        lines: source,
+        missing,
+    }
+}
+
+/// X and Y columns named by the units, one block per spectrum
+fn build_table(
+    spectra: Vec<Spectrum>,
+    x_units: Option<String>,
+    y_units: Option<String>,
+) -> DataTable {
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let mut source_lines = Vec::new();
+    let mut blocks = Vec::new();
+    let mut missing = 0;
+    for spectrum in spectra {
+        let start = xs.len();
+        for (&(x, y), &line) in spectrum.points.iter().zip(&spectrum.lines) {
+            xs.push(x);
+            ys.push(y);
+            source_lines.push(line);
+        }
+        missing += spectrum.missing;
+        blocks.push(DataBlock {
+            rows: start..xs.len(),
+            title: (!spectrum.title.is_empty()).then_some(spectrum.title),
+        });
+    }
+    if xs.is_empty() {
+        return DataTable::default();
+    }
+    DataTable {
+        columns: vec![xs, ys],
+        names: vec![
+            x_units.unwrap_or_else(|| "X".to_string()),
+            y_units.unwrap_or_else(|| "Y".to_string()),
+        ],
+        named: true,
+        source_lines,
+        unparsed: vec![0, missing],
+        blocks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points of a file holding one data table after the given header
+    fn points(header: &str, data: &str) -> Vec<(f64, f64)> {
+        let content = format!("##TITLE=test\n##JCAMP-DX=4.24\n{header}\n{data}\n##END=\n");
+        let table = parse(&content).table;
+        table.columns[0]
+            .iter()
+            .zip(&table.columns[1])
+            .map(|(&x, &y)| (x, y))
+            .collect()
+    }
+
+    const DENSE: &str = "##FIRSTX=100\n##LASTX=109\n##NPOINTS=10\n##XYDATA=(X++(Y..Y))";
+
+    fn expected() -> Vec<(f64, f64)> {
+        [1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 0.0, -1.0, -2.0, -3.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| (100.0 + i as f64, y))
+            .collect()
+    }
+
+    #[test]
+    fn spec_forms_decode_alike() {
+        // The spec's example line in each of its compression forms
+        for data in [
+            "100 1 2 3 3 2 1 0 -1 -2 -3",
+            "100+1+2+3+3+2+1+0-1-2-3",
+            "100ABCCBA@abc",
+            "100AJT%jX",
+        ] {
+            assert_eq!(points(DENSE, data), expected(), "{}", data);
+        }
+    }
+
+    #[test]
+    fn dif_line_drops_the_y_check() {
+        let data = "100AJT%j\n104BjW\n109c";
+        assert_eq!(points(DENSE, data), expected());
+        // Without FIRSTX the X of each line places its points
+        let header = "##XYDATA=(X++(Y..Y))";
+        assert_eq!(points(header, data), expected());
+    }
+
+    #[test]
+    fn factors_scale_the_values() {
+        let header = "##XFACTOR=2\n##YFACTOR=0.5\n##XYDATA=(X++(Y..Y))";
+        let points = points(header, "50 2 4 6\n53 8 10");
+        let expected: Vec<(f64, f64)> = (0..5)
+            .map(|i| (100.0 + 2.0 * i as f64, 1.0 + i as f64))
+            .collect();
+        assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn xy_pairs_keep_their_own_x() {
+        let header = "##YFACTOR=10\n##XYPOINTS=(XY..XY)";
+        let points = points(header, "400.5, 1; 410, 2.5\n430,3");
+        assert_eq!(points, vec![(400.5, 10.0), (410.0, 25.0), (430.0, 30.0)]);
+    }
+
+    #[test]
+    fn peak_table_reads_the_first_two_of_each_group() {
+        let header = "##PEAKTABLE=(XYW..XYW)";
+        let points = points(header, "10,5,1 20,7,2");
+        assert_eq!(points, vec![(10.0, 5.0), (20.0, 7.0)]);
+    }
+
+    #[test]
+    fn metadata_and_detection() {
+        let content = "##TITLE= benzene\n##DATA TYPE=INFRARED SPECTRUM $$ comment\n##END=\n";
+        assert!(is_jcamp(content));
+        assert!(!is_jcamp("x,y\n1,2\n"));
+        let labels = parse(content).labels;
+        assert_eq!(labels[1], ("DATA TYPE".into(), "INFRARED SPECTRUM".into()));
+    }
+
+    #[test]
+    fn byte_order_mark_is_skipped() {
+        let content = "\u{feff}##TITLE= benzene\n##JCAMP-DX=4.24\n##END=\n";
+        assert!(is_jcamp(content));
+        let labels = parse(content).labels;
+        assert_eq!(labels[0], ("TITLE".into(), "benzene".into()));
+    }
+}
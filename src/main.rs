@@ -1,22 +1,168 @@
+mod baseline;
+mod data;
+mod export;
+mod figure;
+mod fit;
+mod font;
+mod heatmap;
+mod histogram;
+mod jcamp;
+mod molecule;
+mod pdb;
+mod peaks;
+mod plot_style;
+mod scale;
+mod scripts;
+mod smooth;
+mod spectrum;
+mod stats;
+mod transform;
+mod units;
+
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
     symbols::Marker,
     text::{Line, Span},
-    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph,
+        Row, Table, TableState,
+        canvas::{Canvas, Line as CanvasLine},
+    },
 };
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use unicode_width::UnicodeWidthChar;
 
+use baseline::{BaselineMethod, BaselineSettings};
+use data::DataTable;
+use export::{DataExport, DataFormat};
+use figure::{Figure, Layer};
+use fit::{Estimate, FitResult, Profile};
+use heatmap::{Colormap, Grid, GridKind};
+use histogram::{BinRule, Histogram};
+use molecule::Structure;
+use pdb::{JumpKind, JumpTarget, Pdb};
+use peaks::{Peak, PeakParams};
+use plot_style::{ChartStyle, PlotStyle};
+use scale::{AxisScale, AxisTransform, symlog_threshold};
+use scripts::{PlotSpec, ScriptAxis, ScriptBlock, ScriptSeries};
+use smooth::SmoothMethod;
+use spectrum::{Spectrum, Window};
+use stats::{ColumnStats, Spacing};
+use transform::Transform;
+use units::{Conversion, XUnit};
+
+/// Chart bounds (x_bounds, y_bounds) before any data has been plotted
+const DEFAULT_BOUNDS: ([f64; 2], [f64; 2]) = ([0.0, 1.0], [0.0, 1.0]);
+
+/// Dataset colors assigned to chart series in order (Atom One Dark palette)
+const SERIES_COLORS: [Color; 7] = [
+    Color::Rgb(86, 182, 194),  // Cyan
+    Color::Rgb(229, 192, 123), // Yellow
+    Color::Rgb(152, 195, 121), // Green
+    Color::Rgb(224, 108, 117), // Red
+    Color::Rgb(198, 120, 221), // Purple
+    Color::Rgb(209, 154, 102), // Orange
+    Color::Rgb(97, 175, 239),  // Blue
+];
+
+/// What the lower right panel shows
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InfoPanel {
+    Stats,
+    Columns, // Column picker
+    Peaks,   // Peak table
+    Jump,    // Chain and residue jump list of a PDB file
+}
+
+/// What a line typed into the path bar is for
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PromptKind {
+    Wavelength,
+    ExportChart,
+    ExportData,
+    ExportScripts,
+    /// The scripts named in the prompt exist, and Enter replaces them
+    ReplaceScripts,
+}
+
+impl PromptKind {
+    fn label(self) -> &'static str {
+        match self {
+            PromptKind::Wavelength => "X-ray wavelength in Å or anode (Cu, Mo, Co, Cr, Fe, Ag)",
+            PromptKind::ExportChart => "Export chart as .svg and .png, or name one",
+            PromptKind::ExportData => "Export data as .csv, .tsv or .json",
+            PromptKind::ExportScripts => "Write plot scripts .gp, .py and Origin .csv named",
+            PromptKind::ReplaceScripts => "Replace the plot scripts named",
+        }
+    }
+}
+
+/// How the blocks of a multi-block file are shown
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum BlockView {
+    /// Every block, coloured along the colormap
+    #[default]
+    Overlay,
+    /// Every block, stacked above and below the current one
+    Waterfall,
+    /// Only the current block
+    Single,
+}
+
+impl BlockView {
+    fn next(self) -> BlockView {
+        match self {
+            BlockView::Overlay => BlockView::Waterfall,
+            BlockView::Waterfall => BlockView::Single,
+            BlockView::Single => BlockView::Overlay,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            BlockView::Overlay => "overlay",
+            BlockView::Waterfall => "waterfall",
+            BlockView::Single => "single",
+        }
+    }
+}
+
+/// A Y column plotted against the selected X column
+struct ChartSeries {
+    column: usize,
+    points: Vec<(f64, f64)>,
+    lines: Vec<usize>, // Source line index of each point
+}
+
+/// A series kept on the chart while other files are opened
+struct PinnedSeries {
+    label: String, // "file:column"
+    points: Vec<(f64, f64)>,
+}
+
+/// A series ready for drawing, with points in plot space
+struct PlottedSeries {
+    name: String, // Empty to keep it out of the legend
+    color: Color,
+    points: Vec<(f64, f64)>,
+    hidden: usize, // Points the axis scales cannot show
+}
+
 /// Represents an entry in the file browser
 struct FileEntry {
     name: String,
@@ -42,16 +188,89 @@ struct App {
 
     // Stats/info
     file_stats: String,
+    column_stats: Vec<ColumnStats>, // One per data table column
+    stats_scroll: u16,
+    stats_max_scroll: u16, // From the last render, to clamp scrolling
     current_file: Option<PathBuf>,
     file_size: u64,
 
     // UI state
     show_chart: bool,
     show_recent_files: bool, // Show recent files popup
+    info_panel: InfoPanel,   // Lower right panel contents
 
     // Chart data
-    chart_data: Vec<(f64, f64)>,
-    chart_bounds: ([f64; 2], [f64; 2]), // (x_bounds, y_bounds)
+    data_table: DataTable,
+    x_column: usize,
+    y_columns: Vec<usize>,
+    y_error_column: Option<usize>, // Standard error of the primary Y column
+    x_error_column: Option<usize>,
+    column_picker_selected: usize,
+    chart_series: Vec<ChartSeries>, // One series per selected Y column
+    chart_data: Vec<(f64, f64)>,    // Primary (first selected) Y column after processing
+    y_errors: Vec<f64>, // Y error of each raw primary point, empty without an error column
+    x_errors: Vec<f64>,
+    block_points: Vec<Vec<(f64, f64)>>, // Primary column of every block, empty for one block
+    current_block: usize,               // Block the primary series comes from
+    block_view: BlockView,
+    jcamp_labels: Vec<(String, String)>, // ## metadata records of a JCAMP-DX file
+    x_unit: Option<XUnit>,               // Physical unit of the X column, None = unitless
+    x_unit_target: XUnit,                // Unit X is displayed in
+    xray_wavelength: f64,                // Å, for diffraction units
+    unit_jacobian: bool,                 // Rescale Y as a density per unit of the new X
+    prompt: Option<(PromptKind, String)>, // Line being typed in the path bar
+    chart_bounds: ([f64; 2], [f64; 2]),  // (x_bounds, y_bounds) in plot space
+    x_transform: AxisTransform,
+    y_transform: AxisTransform,
+    chart_focus: bool, // Chart captures keys for zoom and pan
+    chart_view: Option<([f64; 2], [f64; 2])>, // Zoomed view, None = fit to data
+    chart_plot_area: Rect, // Plot area of the last render, for mouse mapping
+    chart_drag: Option<((u16, u16), (u16, u16))>, // Rubber-band zoom (start, end) cells
+    chart_style: ChartStyle,
+    chart_cursor: Option<usize>, // Crosshair position as an index into chart_data
+    pinned_series: Vec<PinnedSeries>,
+    normalize_series: bool, // Scale every series to a peak magnitude of 1
+    peak_params: PeakParams,
+    peaks: Vec<Peak>, // Peaks found in chart_data while the peak table is open
+    peak_selected: usize,
+    fit_profile: Profile,
+    fit_result: Option<FitResult>,
+    baseline_method: Option<BaselineMethod>,
+    baseline: Vec<f64>, // Baseline under each raw primary point, empty when off
+    baseline_settings: BaselineSettings,
+    subtract_baseline: bool,
+    smooth_method: Option<SmoothMethod>,
+    smooth_window: usize,             // Odd number of points
+    unsmoothed_data: Vec<(f64, f64)>, // chart_data before smoothing, empty when off
+    transform: Option<Transform>,
+    integral_limits: Vec<f64>, // Up to two X values picked with the cursor
+    spectrum_view: bool,       // Chart shows the frequency spectrum of chart_data
+    spectrum_window: Window,
+    spectrum_power: bool, // Power instead of amplitude
+    spectrum: Option<Spectrum>,
+    show_histogram: bool, // Chart panel shows a histogram in place of the chart
+    histogram_column: Option<usize>, // Raw column to bin, None = processed primary series
+    histogram_rule: BinRule,
+    histogram: Option<Histogram>,
+    grid: Option<Grid>, // Matrix or gridded x/y/z content of the file
+    show_heatmap: bool, // Chart panel shows the grid as a heatmap
+    colormap: Colormap,
+    heatmap_cursor: Option<(usize, usize)>, // Grid (column, row) under the cursor
+    heatmap_area: Rect, // Pixel area of the last heatmap render, for mouse mapping
+    molecule: Vec<Structure>, // Frames of an .xyz file, empty for other files
+    molecule_frame: usize,
+    view_rotation: (f64, f64), // Yaw and pitch of the structure preview, in radians
+    pdb: Option<Pdb>,          // Structure summary of a .pdb file
+    jump_targets: Vec<JumpTarget>,
+    jump_selected: usize,
+
+    // Result of the last export or other action, shown in the path bar
+    status_message: Option<String>,
+    show_pinned: bool, // Show pinned series popup
+    pinned_selected: usize,
+    show_key_help: bool, // Show the key reference popup
+    key_help_scroll: usize,
+    chart_styles: HashMap<String, ChartStyle>, // Remembered style per file extension
 
     // Recent files
     recent_files: Vec<PathBuf>,
@@ -79,12 +298,83 @@ impl App {
             scroll_offset: 0,
             visible_height: 20,
             file_stats: "No file selected".to_string(),
+            column_stats: Vec::new(),
+            stats_scroll: 0,
+            stats_max_scroll: 0,
             current_file: None,
             file_size: 0,
             show_chart: true,
             show_recent_files: false,
+            info_panel: InfoPanel::Stats,
+            data_table: DataTable::default(),
+            x_column: 0,
+            y_columns: vec![1],
+            y_error_column: None,
+            x_error_column: None,
+            column_picker_selected: 0,
+            chart_series: Vec::new(),
             chart_data: Vec::new(),
-            chart_bounds: ([0.0, 1.0], [0.0, 1.0]),
+            y_errors: Vec::new(),
+            x_errors: Vec::new(),
+            block_points: Vec::new(),
+            current_block: 0,
+            block_view: BlockView::default(),
+            jcamp_labels: Vec::new(),
+            x_unit: None,
+            x_unit_target: XUnit::Nanometre,
+            xray_wavelength: units::DEFAULT_WAVELENGTH,
+            unit_jacobian: true,
+            prompt: None,
+            chart_bounds: DEFAULT_BOUNDS,
+            x_transform: AxisTransform::default(),
+            y_transform: AxisTransform::default(),
+            chart_focus: false,
+            chart_view: None,
+            chart_plot_area: Rect::default(),
+            chart_drag: None,
+            chart_style: ChartStyle::default(),
+            chart_cursor: None,
+            pinned_series: Vec::new(),
+            normalize_series: false,
+            peak_params: PeakParams::default(),
+            peaks: Vec::new(),
+            peak_selected: 0,
+            fit_profile: Profile::default(),
+            fit_result: None,
+            baseline_method: None,
+            baseline: Vec::new(),
+            baseline_settings: BaselineSettings::default(),
+            subtract_baseline: false,
+            smooth_method: None,
+            smooth_window: 11,
+            unsmoothed_data: Vec::new(),
+            transform: None,
+            integral_limits: Vec::new(),
+            spectrum_view: false,
+            spectrum_window: Window::default(),
+            spectrum_power: false,
+            spectrum: None,
+            show_histogram: false,
+            histogram_column: None,
+            histogram_rule: BinRule::default(),
+            histogram: None,
+            grid: None,
+            show_heatmap: false,
+            colormap: Colormap::default(),
+            heatmap_cursor: None,
+            heatmap_area: Rect::default(),
+            molecule: Vec::new(),
+            molecule_frame: 0,
+            view_rotation: (0.0, 0.0),
+            pdb: None,
+            jump_targets: Vec::new(),
+            jump_selected: 0,
+            status_message: None,
+            show_pinned: false,
+            pinned_selected: 0,
+            show_key_help: false,
+            key_help_scroll: 0,
+            chart_styles: Self::load_chart_styles(),
             recent_files: Vec::new(),
             recent_files_selected: 0,
         };
@@ -119,6 +409,57 @@ impl App {
         }
     }
 
+    /// Get the per-extension chart style file path
+    fn chart_styles_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("sci-file-viewer").join("chart_styles.txt"))
+    }
+
+    /// Load remembered chart styles from config
+    fn load_chart_styles() -> HashMap<String, ChartStyle> {
+        Self::chart_styles_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| plot_style::parse_styles(&content))
+            .unwrap_or_default()
+    }
+
+    /// Save remembered chart styles to config
+    fn save_chart_styles(&self) {
+        if let Some(path) = Self::chart_styles_path() {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let _ = fs::write(path, plot_style::format_styles(&self.chart_styles));
+        }
+    }
+
+    /// Lowercase extension of the open file, used as the chart style key
+    fn current_extension(&self) -> String {
+        self.current_file
+            .as_ref()
+            .and_then(|p| p.extension())
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+    }
+
+    /// Cycle scatter, line and line + points, remembering it for this extension
+    fn cycle_plot_style(&mut self) {
+        self.chart_style.plot = self.chart_style.plot.next();
+        self.remember_chart_style();
+    }
+
+    /// Cycle the chart marker, remembering it for this extension
+    fn cycle_marker(&mut self) {
+        self.chart_style.marker = plot_style::next_marker(self.chart_style.marker);
+        self.remember_chart_style();
+    }
+
+    fn remember_chart_style(&mut self) {
+        self.chart_styles
+            .insert(self.current_extension(), self.chart_style);
+        self.save_chart_styles();
+    }
+
     /// Navigate to home directory
     fn go_home(&mut self) {
         if let Some(home) = dirs::home_dir() {
@@ -199,8 +540,22 @@ impl App {
     fn open_file(&mut self, path: &PathBuf) {
         self.current_file = Some(path.clone());
         self.scroll_offset = 0;
-        self.chart_data.clear();
-        
+        self.stats_scroll = 0;
+        self.data_table = DataTable::default();
+        self.jcamp_labels.clear();
+        self.molecule.clear();
+        self.pdb = None;
+        self.jump_targets.clear();
+        if self.info_panel == InfoPanel::Jump {
+            self.info_panel = InfoPanel::Stats;
+        }
+        self.rebuild_chart_series(); // Pinned series stay on the chart
+        self.chart_style = self
+            .chart_styles
+            .get(&self.current_extension())
+            .copied()
+            .unwrap_or_default();
+
         // Add to recent files
         self.add_to_recent_files(path);
 
@@ -221,27 +576,35 @@ impl App {
                     self.file_content.push("(empty file)".to_string());
                 }
 
-                // Try to parse two-column numeric data
+                // Try to parse multi-column numeric data
                 self.parse_chart_data(&content);
+                self.column_stats = self
+                    .data_table
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(col, values)| {
+                        let unparsed = self.data_table.unparsed.get(col).copied().unwrap_or(0);
+                        stats::describe(values, unparsed)
+                    })
+                    .collect();
 
                 // Update stats with size, lines, dates, and chart info
                 let line_count = self.file_content.len();
                 let chart_info = if !self.chart_data.is_empty() {
-                    format!("\nData points: {}", self.chart_data.len())
+                    format!(
+                        "\nData points: {}\nColumns: {}",
+                        self.chart_data.len(),
+                        self.data_table.column_count()
+                    )
                 } else {
                     String::new()
                 };
-                self.file_stats = format!(
-                    "{}\nLines: {}{}",
-                    file_metadata,
-                    line_count,
-                    chart_info
-                );
+                self.file_stats = format!("{}\nLines: {}{}", file_metadata, line_count, chart_info);
             }
             Err(_e) => {
-                self.file_content = vec![
-                    "Binary file — no text content to display".to_string(),
-                ];
+                self.file_content = vec!["Binary file — no text content to display".to_string()];
+                self.column_stats.clear();
                 self.file_stats = file_metadata;
             }
         }
@@ -286,233 +649,2522 @@ impl App {
         (format_datetime(created), format_datetime(modified))
     }
 
-    /// Parse two-column numeric data from file content
+    /// Parse multi-column numeric data from file content
     fn parse_chart_data(&mut self, content: &str) {
-        let mut data: Vec<(f64, f64)> = Vec::new();
+        // Molecular coordinates are a structure, not columns to plot
+        self.molecule = if self.current_extension() == "xyz" {
+            molecule::parse_xyz(content).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.molecule_frame = 0;
+        // PDB files keep their raw text, summarised in the stats panel
+        self.pdb = (self.current_extension() == "pdb").then(|| pdb::parse(content));
+        self.jump_targets = self.pdb.as_ref().map(Pdb::jump_targets).unwrap_or_default();
+        self.jump_selected = 0;
+        if !self.molecule.is_empty() || self.pdb.is_some() {
+            self.data_table = DataTable::default();
+            self.jcamp_labels.clear();
+        } else if jcamp::is_jcamp(content) {
+            // JCAMP-DX spectra are compressed and scaled, not plain columns
+            let jcamp = jcamp::parse(content);
+            self.data_table = jcamp.table;
+            self.jcamp_labels = jcamp.labels;
+        } else {
+            self.data_table = DataTable::parse(content);
+            self.jcamp_labels.clear();
+        }
+        self.x_column = 0;
+        self.y_columns = vec![1];
+        self.y_error_column = self.data_table.error_column();
+        self.x_error_column = None;
+        self.column_picker_selected = 0;
+        self.histogram_column = None;
+        self.current_block = 0;
+        self.grid = heatmap::detect(&self.data_table);
+        // Wide headerless tables are as often multi-channel columns as a
+        // matrix, so only x/y/z grids open as a heatmap
+        self.show_heatmap = self.grid.as_ref().is_some_and(|g| g.kind == GridKind::Xyz);
+        if self
+            .grid
+            .as_ref()
+            .is_some_and(|g| g.kind == GridKind::Matrix)
+        {
+            self.status_message = Some("Matrix-shaped data: press G for a heatmap".to_string());
+        }
+        self.heatmap_cursor = None;
+        self.rebuild_chart_series();
+    }
 
-        for line in content.lines() {
-            let line = line.trim();
+    /// Rebuild the plotted series from the column table and current selection
+    fn rebuild_chart_series(&mut self) {
+        // Multi-block files plot the current block
+        let rows = self
+            .data_table
+            .block_rows(self.multi_block().then_some(self.current_block));
 
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
-                continue;
+        // Only consider it valid chart data if we have at least 2 points
+        self.chart_series = self
+            .y_columns
+            .iter()
+            .map(|&column| {
+                let (points, lines) =
+                    self.data_table
+                        .xy_points(self.x_column, column, rows.clone());
+                ChartSeries {
+                    column,
+                    points,
+                    lines,
+                }
+            })
+            .filter(|series| series.points.len() >= 2)
+            .collect();
+
+        // Errors line up with the primary series' raw points
+        let primary = self.chart_series.first().map(|series| series.column);
+        let errors = |column: Option<usize>| match (primary, column) {
+            (Some(y), Some(column)) => {
+                self.data_table
+                    .values_at_points(self.x_column, y, column, rows.clone())
             }
+            _ => Vec::new(),
+        };
+        self.y_errors = errors(self.y_error_column);
+        self.x_errors = errors(self.x_error_column);
 
-            // Split by whitespace, comma, or tab
-            let parts: Vec<&str> = line
-                .split(|c: char| c.is_whitespace() || c == ',')
-                .filter(|s| !s.is_empty())
-                .collect();
+        self.block_points = match primary {
+            Some(y) if self.multi_block() => self
+                .data_table
+                .blocks
+                .iter()
+                .map(|block| {
+                    self.data_table
+                        .xy_points(self.x_column, y, block.rows.clone())
+                        .0
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
 
-            // We need exactly 2 numeric columns (or at least 2 parseable numbers)
-            if parts.len() >= 2
-                && let (Ok(x), Ok(y)) = (parts[0].parse::<f64>(), parts[1].parse::<f64>())
-                && x.is_finite()
-                && y.is_finite()
+        // Unit conversion applies to the raw points, so every later step
+        // works in the displayed unit
+        if let Some(conversion) = self.unit_conversion() {
+            let rescale = self.unit_jacobian;
+            for (index, series) in self.chart_series.iter_mut().enumerate() {
+                let (points, kept) = conversion.convert_points(&series.points, rescale);
+                series.lines = kept.iter().map(|&(i, _)| series.lines[i]).collect();
+                series.points = points;
+                if index == 0 {
+                    let y_scale = |j: f64| if rescale { j } else { 1.0 };
+                    if !self.y_errors.is_empty() {
+                        self.y_errors = kept
+                            .iter()
+                            .map(|&(i, j)| self.y_errors[i] * y_scale(j))
+                            .collect();
+                    }
+                    if !self.x_errors.is_empty() {
+                        self.x_errors = kept.iter().map(|&(i, j)| self.x_errors[i] / j).collect();
+                    }
+                }
+            }
+            for points in &mut self.block_points {
+                *points = conversion.convert_points(points, rescale).0;
+            }
+            if self
+                .chart_series
+                .first()
+                .is_some_and(|series| series.points.len() < 2)
             {
-                data.push((x, y));
+                self.y_errors.clear();
+                self.x_errors.clear();
             }
+            self.chart_series.retain(|series| series.points.len() >= 2);
         }
 
-        // Only consider it valid chart data if we have at least 2 points
-        if data.len() >= 2 {
-            // Calculate bounds
-            let x_min = data.iter().map(|(x, _)| *x).fold(f64::INFINITY, f64::min);
-            let x_max = data
-                .iter()
-                .map(|(x, _)| *x)
-                .fold(f64::NEG_INFINITY, f64::max);
-            let y_min = data.iter().map(|(_, y)| *y).fold(f64::INFINITY, f64::min);
-            let y_max = data
-                .iter()
-                .map(|(_, y)| *y)
-                .fold(f64::NEG_INFINITY, f64::max);
-
-            // Add small padding to bounds (5%)
-            let x_padding = (x_max - x_min).abs() * 0.05;
-            let y_padding = (y_max - y_min).abs() * 0.05;
+        // Symlog thresholds follow the magnitudes of the plotted data
+        let points = || self.chart_series.iter().flat_map(|s| s.points.iter());
+        self.x_transform.linthresh = symlog_threshold(points().map(|p| p.0));
+        self.y_transform.linthresh = symlog_threshold(points().map(|p| p.1));
 
-            // Handle case where all values are the same
-            let x_bounds = if x_max == x_min {
-                [x_min - 1.0, x_max + 1.0]
-            } else {
-                [x_min - x_padding, x_max + x_padding]
-            };
+        self.chart_cursor = None;
+        self.integral_limits.clear();
+        self.process_chart_data();
+    }
 
-            let y_bounds = if y_max == y_min {
-                [y_min - 1.0, y_max + 1.0]
-            } else {
-                [y_min - y_padding, y_max + y_padding]
-            };
+    /// Re-bin the histogram's values while it is shown
+    fn refresh_histogram(&mut self) {
+        self.histogram = if self.show_histogram {
+            histogram::build(&self.histogram_values(), self.histogram_rule)
+        } else {
+            None
+        };
+    }
 
-            self.chart_bounds = (x_bounds, y_bounds);
-            self.chart_data = data;
+    /// Values the histogram bins: the processed primary series inside the
+    /// visible X range, or a raw column over the current block's rows
+    fn histogram_values(&self) -> Vec<f64> {
+        match self.histogram_column {
+            Some(column) => {
+                let rows = self
+                    .data_table
+                    .block_rows(self.multi_block().then_some(self.current_block));
+                self.data_table
+                    .columns
+                    .get(column)
+                    .map(|values| values[rows].to_vec())
+                    .unwrap_or_default()
+            }
+            None => {
+                let (x_bounds, _) = self.view_bounds();
+                self.chart_data
+                    .iter()
+                    .filter(|&&(x, _)| {
+                        self.x_transform
+                            .forward(x)
+                            .is_some_and(|x| x >= x_bounds[0] && x <= x_bounds[1])
+                    })
+                    .map(|p| p.1)
+                    .collect()
+            }
         }
     }
 
-    /// Downsample data while preserving peaks (local minima and maxima)
-    fn downsample_with_peaks(data: &[(f64, f64)], target_points: usize) -> Vec<(f64, f64)> {
-        if data.len() <= target_points {
-            return data.to_vec();
+    /// Name of the histogram's values and where they come from
+    fn histogram_source(&self) -> (String, String) {
+        match self.histogram_column {
+            Some(column) => {
+                let scope = if self.multi_block() {
+                    format!(
+                        "raw, block {}/{}",
+                        self.current_block + 1,
+                        self.data_table.blocks.len()
+                    )
+                } else {
+                    "raw".to_string()
+                };
+                (self.data_table.column_name(column).to_string(), scope)
+            }
+            None if self.chart_series.is_empty() => (String::new(), String::new()),
+            None => {
+                let scope = if self.chart_view.is_some() {
+                    "as plotted, visible X range"
+                } else {
+                    "as plotted"
+                };
+                (self.series_name(0), scope.to_string())
+            }
         }
+    }
 
-        let mut result: Vec<(f64, f64)> = Vec::with_capacity(target_points);
+    /// Bin the processed primary series, then each raw column in turn
+    fn cycle_histogram_column(&mut self) {
+        let count = self.data_table.column_count();
+        self.histogram_column = match self.histogram_column {
+            None if count > 0 => Some(0),
+            Some(column) if column + 1 < count => Some(column + 1),
+            _ => None,
+        };
+        self.refresh_histogram();
+    }
 
-        // Always include first point
-        result.push(data[0]);
+    /// Conversion from the X column's unit to the displayed one, if they differ
+    fn unit_conversion(&self) -> Option<Conversion> {
+        let from = self.x_unit?;
+        (self.x_unit_target != from).then_some(Conversion {
+            from,
+            to: self.x_unit_target,
+            wavelength: self.xray_wavelength,
+        })
+    }
 
-        // Calculate bucket size
-        let bucket_size = (data.len() - 2) as f64 / (target_points - 2) as f64;
+    /// Declare the X column's unit, showing it unconverted
+    fn cycle_x_unit(&mut self) {
+        self.x_unit = XUnit::cycle(self.x_unit);
+        if let Some(unit) = self.x_unit {
+            self.x_unit_target = unit;
+        }
+        self.rebuild_chart_series();
+    }
 
-        for i in 1..(target_points - 1) {
-            let start = (1.0 + (i - 1) as f64 * bucket_size) as usize;
-            let end = (1.0 + i as f64 * bucket_size) as usize;
-            let end = end.min(data.len() - 1);
+    /// Display X in the next unit of the declared unit's family
+    fn cycle_unit_target(&mut self) {
+        if self.x_unit.is_none() {
+            self.status_message = Some("Set the X column's unit with u first".to_string());
+            return;
+        }
+        self.x_unit_target = self.x_unit_target.next_in_family();
+        self.rebuild_chart_series();
+    }
 
-            if start >= end {
-                continue;
-            }
+    fn toggle_unit_jacobian(&mut self) {
+        self.unit_jacobian = !self.unit_jacobian;
+        if self.unit_conversion().is_some() {
+            self.rebuild_chart_series();
+        }
+    }
 
-            // Find min and max Y in this bucket
-            let mut min_idx = start;
-            let mut max_idx = start;
-            let mut min_y = data[start].1;
-            let mut max_y = data[start].1;
+    /// Act on the line typed into the path bar
+    fn finish_prompt(&mut self) {
+        let Some((kind, input)) = self.prompt.take() else {
+            return;
+        };
+        match kind {
+            PromptKind::Wavelength => self.set_wavelength(&input),
+            PromptKind::ExportChart => self.export_chart(&input),
+            PromptKind::ExportData => self.export_data(&input),
+            PromptKind::ExportScripts => self.export_scripts(&input, false),
+            PromptKind::ReplaceScripts => self.export_scripts(&input, true),
+        }
+    }
 
-            for (j, point) in data.iter().enumerate().take(end).skip(start) {
-                if point.1 < min_y {
-                    min_y = point.1;
-                    min_idx = j;
-                }
-                if point.1 > max_y {
-                    max_y = point.1;
-                    max_idx = j;
-                }
+    /// Apply a typed X-ray wavelength or anode symbol
+    fn set_wavelength(&mut self, input: &str) {
+        match units::parse_wavelength(input) {
+            Some(wavelength) => {
+                self.xray_wavelength = wavelength;
+                self.status_message = Some(format!("X-ray wavelength λ = {} Å", wavelength));
+                self.rebuild_chart_series();
             }
-
-            // Add min and max in X order to preserve shape
-            if min_idx < max_idx {
-                result.push(data[min_idx]);
-                if min_idx != max_idx {
-                    result.push(data[max_idx]);
-                }
-            } else {
-                result.push(data[max_idx]);
-                if min_idx != max_idx {
-                    result.push(data[min_idx]);
-                }
+            None => {
+                self.status_message = Some(format!("Not a wavelength or anode: {}", input.trim()))
             }
         }
+    }
 
-        // Always include last point
-        result.push(data[data.len() - 1]);
-
-        result
+    /// Ask where to export the chart, offering the file's path without its extension
+    fn start_chart_export(&mut self) {
+        let base = match &self.current_file {
+            Some(path) => path.with_extension(""),
+            None => self.current_directory.join("chart"),
+        };
+        self.prompt = Some((PromptKind::ExportChart, base.display().to_string()));
     }
 
-    /// Reveal a file in the tree by switching to its directory and selecting it
-    fn reveal_file_in_tree(&mut self, path: &PathBuf) {
-        if let Some(parent) = path.parent() {
-            self.current_directory = parent.to_path_buf();
-            self.refresh_directory();
-            if let Some(pos) = self
-                .entries
+    /// Write the chart as SVG and PNG, or only the format the path's extension names
+    fn export_chart(&mut self, input: &str) {
+        let path = PathBuf::from(input.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        let targets = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg" | "png") => vec![path],
+            _ => ["svg", "png"]
                 .iter()
-                .position(|entry| !entry.is_dir && entry.path == *path)
-            {
-                self.selected_index = pos;
+                .map(|ext| {
+                    let mut name = path.clone().into_os_string();
+                    name.push(".");
+                    name.push(ext);
+                    PathBuf::from(name)
+                })
+                .collect(),
+        };
+
+        // Export at the figure's width, so error bars and downsampling match it
+        let width = figure::WIDTH as usize;
+        let figure = chart_figure(self, width * 2, width);
+        let mut written = Vec::new();
+        for target in targets {
+            let result = if target.extension().is_some_and(|ext| ext == "png") {
+                figure::write_png(&figure, &target)
+            } else {
+                figure::write_svg(&figure, &target)
+            };
+            if let Err(err) = result {
+                self.status_message =
+                    Some(format!("Export to {} failed: {}", target.display(), err));
+                return;
             }
+            written.push(target.display().to_string());
         }
+        self.status_message = Some(format!("Saved chart to {}", written.join(" and ")));
     }
-}
 
-fn main() -> Result<(), io::Error> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    /// Ask where to export the processed data, offering `<name>_processed.csv`
+    fn start_data_export(&mut self) {
+        let path = match &self.current_file {
+            Some(path) => {
+                let stem = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                path.with_file_name(format!("{}_processed.csv", stem))
+            }
+            None => self.current_directory.join("processed.csv"),
+        };
+        self.prompt = Some((PromptKind::ExportData, path.display().to_string()));
+    }
 
-    // Create app state
-    let mut app = App::new();
+    fn export_data(&mut self, input: &str) {
+        let path = PathBuf::from(input.trim());
+        let Some(format) = DataFormat::from_path(&path) else {
+            self.status_message = Some("Name a .csv, .tsv or .json file".to_string());
+            return;
+        };
+        if self.current_file.as_ref() == Some(&path) {
+            self.status_message = Some("Will not overwrite the source file".to_string());
+            return;
+        }
+        self.status_message = Some(match self.data_export().write(&path, format) {
+            Ok(()) => format!("Data saved to {}", path.display()),
+            Err(e) => format!("Could not save data: {}", e),
+        });
+    }
 
-    // Run the app
-    let res = run_app(&mut terminal, &mut app);
+    /// Processed primary series in the visible X range, the other selected
+    /// columns at the same rows, and notes on how it was produced
+    fn data_export(&self) -> DataExport {
+        let (x_bounds, _) = self.view_bounds();
+        let visible = |x: f64| {
+            self.x_transform
+                .forward(x)
+                .is_some_and(|x| x >= x_bounds[0] && x <= x_bounds[1])
+        };
+        let primary_factor = self.series_factor(&self.chart_data);
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+        // Source lines tie processed points to the other series; a spectrum has none
+        let lines: &[usize] = match self.chart_series.first() {
+            Some(series) if !self.spectrum_view => &series.lines,
+            _ => &[],
+        };
+        let y_errors = self.chart_errors().filter(|_| !lines.is_empty());
+        let x_errors = (!self.x_errors.is_empty() && !lines.is_empty()).then_some(&self.x_errors);
+        let others = if lines.is_empty() {
+            1..1
+        } else {
+            1..self.shown_series_count()
+        };
 
-    if let Err(err) = res {
-        println!("Error: {:?}", err);
+        let mut columns = vec![self.x_axis_name(), self.series_name(0)];
+        if let (Some(_), Some(column)) = (y_errors, self.y_error_column) {
+            columns.push(format!("± {}", self.data_table.column_name(column)));
+        }
+        if let (Some(_), Some(column)) = (x_errors, self.x_error_column) {
+            columns.push(format!("± {}", self.data_table.column_name(column)));
+        }
+        columns.extend(others.clone().map(|i| self.series_name(i)));
+
+        let rows: Vec<Vec<f64>> = self
+            .chart_data
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(x, _))| visible(x))
+            .map(|(i, &(x, y))| {
+                let mut row = vec![x, y * primary_factor];
+                if let Some(errors) = y_errors {
+                    row.push(errors[i] * primary_factor);
+                }
+                if let Some(errors) = x_errors {
+                    row.push(errors[i]);
+                }
+                for series in &self.chart_series[others.clone()] {
+                    let factor = self.series_factor(&series.points);
+                    let value = series
+                        .lines
+                        .binary_search(&lines[i])
+                        .map_or(f64::NAN, |j| series.points[j].1 * factor);
+                    row.push(value);
+                }
+                row
+            })
+            .collect();
+
+        let steps = self.processing_steps();
+        let mut provenance = Vec::new();
+        if let Some(path) = &self.current_file {
+            provenance.push(("Source", path.display().to_string()));
+        }
+        if let Some(block) = self
+            .data_table
+            .blocks
+            .get(self.current_block)
+            .filter(|_| self.multi_block())
+        {
+            let title = block
+                .title
+                .as_ref()
+                .map(|t| format!(": {}", t))
+                .unwrap_or_default();
+            provenance.push((
+                "Block",
+                format!(
+                    "{} of {}{}",
+                    self.current_block + 1,
+                    self.data_table.blocks.len(),
+                    title
+                ),
+            ));
+        }
+        let mut processing = if steps.is_empty() {
+            "none".to_string()
+        } else {
+            steps.join("; ")
+        };
+        // Baseline, smoothing, transforms and spectra follow the first Y only
+        let primary_only = (self.subtract_baseline && self.baseline_method.is_some())
+            || self.smooth_method.is_some()
+            || self.transform.is_some()
+            || self.spectrum_view;
+        if primary_only && !others.is_empty() {
+            processing.push_str(&format!(
+                " (on {} only; other Y columns are only unit converted and normalized)",
+                self.series_name(0)
+            ));
+        }
+        provenance.push(("Processing", processing));
+        if self.chart_view.is_some() {
+            provenance.push((
+                "X range",
+                format!(
+                    "{} to {}",
+                    format_significant(self.x_transform.inverse(x_bounds[0]), 6),
+                    format_significant(self.x_transform.inverse(x_bounds[1]), 6)
+                ),
+            ));
+        }
+        provenance.push(("Points", rows.len().to_string()));
+        provenance.push((
+            "Exported",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        ));
+
+        DataExport {
+            provenance,
+            columns,
+            rows,
+        }
     }
 
-    Ok(())
-}
+    /// Ask where to write the plot scripts, offering `<name>_plot`
+    fn start_scripts_export(&mut self) {
+        let Some(path) = self
+            .current_file
+            .as_ref()
+            .filter(|_| !self.chart_series.is_empty())
+        else {
+            self.status_message = Some("No chart to write scripts for".to_string());
+            return;
+        };
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let base = path.with_file_name(format!("{}_plot", stem));
+        self.prompt = Some((PromptKind::ExportScripts, base.display().to_string()));
+    }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> io::Result<()> {
-    loop {
-        terminal.draw(|f| ui(f, app))?;
+    /// Write gnuplot and matplotlib scripts that redraw the chart from the
+    /// source file, and a table of the plotted values for Origin, as
+    /// `<base>.gp`, `<base>.py` and `<base>.csv`
+    ///
+    /// Existing files are only replaced once `replace` confirms it.
+    fn export_scripts(&mut self, input: &str, replace: bool) {
+        let base = PathBuf::from(input.trim());
+        let Some(path) = self
+            .current_file
+            .clone()
+            .filter(|_| !self.chart_series.is_empty() && !base.as_os_str().is_empty())
+        else {
+            return;
+        };
+        let with_suffix = |suffix: &str| {
+            let mut name = base.clone().into_os_string();
+            name.push(suffix);
+            PathBuf::from(name)
+        };
+        let gnuplot_path = with_suffix(".gp");
+        let python_path = with_suffix(".py");
+        let origin_path = with_suffix(".csv");
+        // JCAMP-DX data is compressed and scaled, so the scripts read a
+        // decoded copy of the table, `<base>_data.tsv`, instead of the file
+        let data_path = (!self.jcamp_labels.is_empty()).then(|| with_suffix("_data.tsv"));
+        let mut targets = vec![&gnuplot_path, &python_path, &origin_path];
+        targets.extend(&data_path);
+        if targets.contains(&&path) {
+            self.status_message = Some("Will not overwrite the source file".to_string());
+            return;
+        }
+        let existing: Vec<String> = targets
+            .iter()
+            .filter(|target| target.exists())
+            .map(|target| target.display().to_string())
+            .collect();
+        if !replace && !existing.is_empty() {
+            self.status_message = Some(format!(
+                "{} already exist; Enter replaces them, Esc keeps them",
+                existing.join(", ")
+            ));
+            self.prompt = Some((PromptKind::ReplaceScripts, input.to_string()));
+            return;
+        }
 
-        if let Event::Key(key) = event::read()? {
-            // Handle recent files popup first
-            if app.show_recent_files {
-                match key.code {
-                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => {
-                        app.show_recent_files = false;
-                        continue;
-                    }
-                    KeyCode::Up => {
-                        if app.recent_files.is_empty() {
-                            continue;
-                        }
-                        app.recent_files_selected = app.recent_files_selected.checked_sub(1)
-                            .unwrap_or(app.recent_files.len() - 1);
-                        continue;
-                    }
-                    KeyCode::Down => {
-                        if app.recent_files.is_empty() {
-                            continue;
-                        }
-                        app.recent_files_selected = (app.recent_files_selected + 1) % app.recent_files.len();
-                        continue;
-                    }
-                    KeyCode::Enter => {
-                        if !app.recent_files.is_empty() {
-                            if let Some(path) = app.recent_files.get(app.recent_files_selected) {
-                                let path = path.clone();
-                                app.show_recent_files = false;
-                                app.reveal_file_in_tree(&path);
-                                app.open_file(&path);
-                            }
-                        }
-                        continue;
-                    }
-                    _ => {}
+        let (source, lines) = match data_path {
+            None => (path.clone(), self.data_table.source_lines.clone()),
+            Some(data_path) => {
+                let decoded = self.decoded_table(&path);
+                if let Err(e) = decoded.write(&data_path, DataFormat::Tsv) {
+                    self.status_message = Some(format!("Could not save the decoded table: {}", e));
+                    return;
                 }
+                // The provenance lines and the header come before the rows
+                let first = decoded.provenance.len() + 1;
+                (data_path, (first..first + decoded.rows.len()).collect())
             }
-            
-            match key.code {
-                KeyCode::Char('q') => {
-                    app.save_last_directory();
-                    return Ok(());
-                }
-                KeyCode::Up => {
-                    if !app.entries.is_empty() {
-                        app.selected_index = app.selected_index.checked_sub(1)
-                            .unwrap_or(app.entries.len() - 1);
-                    }
-                }
-                KeyCode::Down => {
-                    if !app.entries.is_empty() {
-                        app.selected_index = (app.selected_index + 1) % app.entries.len();
+        };
+
+        // Every block on screen, or the current one alone, with its rows
+        let script_block = |rows: &Range<usize>, color: Color, offset: f64| ScriptBlock {
+            first_line: lines[rows.start] + 1,
+            last_line: lines[rows.end - 1] + 1,
+            rows: rows.len(),
+            color: figure::rgb(color),
+            offset,
+        };
+        let (blocks, ranges, current): (Vec<ScriptBlock>, Vec<Range<usize>>, usize) = if self
+            .block_curves_shown()
+        {
+            // The scripts plot raw values, so the waterfall steps by the
+            // widest raw Y span rather than the processed one
+            let primary = self.chart_series[0].column;
+            let step = if self.block_view == BlockView::Waterfall {
+                self.data_table
+                    .blocks
+                    .iter()
+                    .map(|block| {
+                        let (points, _) =
+                            self.data_table
+                                .xy_points(self.x_column, primary, block.rows.clone());
+                        let (lo, hi) = points
+                            .iter()
+                            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                                (lo.min(p.1), hi.max(p.1))
+                            });
+                        if hi > lo { hi - lo } else { 0.0 }
+                    })
+                    .fold(0.0, f64::max)
+            } else {
+                0.0
+            };
+            let blocks = self
+                .data_table
+                .blocks
+                .iter()
+                .enumerate()
+                .map(|(i, block)| {
+                    let shift = (i as f64 - self.current_block as f64) * step;
+                    script_block(&block.rows, self.block_color(i), shift)
+                })
+                .collect();
+            let ranges = self.data_table.blocks.iter().map(|b| b.rows.clone());
+            (blocks, ranges.collect(), self.current_block)
+        } else {
+            let rows = self
+                .data_table
+                .block_rows(self.multi_block().then_some(self.current_block));
+            (
+                vec![script_block(&rows, SERIES_COLORS[0], 0.0)],
+                vec![rows],
+                0,
+            )
+        };
+        let comma = self.jcamp_labels.is_empty()
+            && self
+                .file_content
+                .get(blocks[current].first_line - 1)
+                .is_some_and(|line| line.contains(','));
+
+        // Ranges only carry over where the scripts plot the same quantity
+        let notes = self.processing_steps();
+        let x_changed = self.unit_conversion().is_some() || self.spectrum_view;
+        let (x_bounds, y_bounds) = self.view_bounds();
+        let range = |transform: &AxisTransform, bounds: [f64; 2]| {
+            bounds.map(|b| {
+                let value = transform.inverse(b);
+                format_significant(value, 6).parse().unwrap_or(value)
+            })
+        };
+        let columns: Vec<usize> = self
+            .chart_series
+            .iter()
+            .take(self.shown_series_count())
+            .map(|series| series.column)
+            .collect();
+        let spec = PlotSpec {
+            source: source.display().to_string(),
+            blocks,
+            current,
+            comma,
+            title: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            x_column: self.x_column,
+            series: columns
+                .iter()
+                .enumerate()
+                .map(|(i, &column)| ScriptSeries {
+                    column,
+                    name: self.data_table.column_name(column).to_string(),
+                    color: figure::rgb(SERIES_COLORS[i % SERIES_COLORS.len()]),
+                })
+                .collect(),
+            y_error: self.y_error_column,
+            x_error: self.x_error_column,
+            style: self.chart_style.plot,
+            x: ScriptAxis {
+                label: if x_changed {
+                    self.data_table.column_name(self.x_column).to_string()
+                } else {
+                    self.x_axis_name()
+                },
+                range: (!x_changed).then(|| range(&self.x_transform, x_bounds)),
+                scale: self.x_transform.scale,
+                linthresh: self.x_transform.linthresh,
+            },
+            y: ScriptAxis {
+                label: columns
+                    .iter()
+                    .map(|&column| self.data_table.column_name(column))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                range: notes.is_empty().then(|| range(&self.y_transform, y_bounds)),
+                scale: self.y_transform.scale,
+                linthresh: self.y_transform.linthresh,
+            },
+            notes,
+            written: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+
+        // Origin cannot read line ranges of a text file, so it gets the
+        // plotted values, one X/Y column pair per other block
+        let name = |column: usize| self.data_table.column_name(column).to_string();
+        let primary = columns[0];
+        let mut table: Vec<(String, Vec<f64>)> = Vec::new();
+        for (i, (block, rows)) in spec.blocks.iter().zip(&ranges).enumerate() {
+            let (points, _) = self
+                .data_table
+                .xy_points(self.x_column, primary, rows.clone());
+            let at_points = |column: usize| {
+                self.data_table
+                    .values_at_points(self.x_column, primary, column, rows.clone())
+            };
+            let xs = points.iter().map(|p| p.0).collect();
+            let ys = points.iter().map(|p| p.1 + block.offset).collect();
+            if i == current {
+                table.push((name(self.x_column), xs));
+                table.push((name(primary), ys));
+                for column in [self.y_error_column, self.x_error_column]
+                    .into_iter()
+                    .flatten()
+                {
+                    table.push((format!("± {}", name(column)), at_points(column)));
+                }
+                for &column in &columns[1..] {
+                    table.push((name(column), at_points(column)));
+                }
+            } else {
+                let label = if block.offset == 0.0 {
+                    format!("block {}", i + 1)
+                } else {
+                    format!("block {}, {:+}", i + 1, block.offset)
+                };
+                table.push((format!("{} ({})", name(self.x_column), label), xs));
+                table.push((format!("{} ({})", name(primary), label), ys));
+            }
+        }
+
+        let result = fs::write(&gnuplot_path, scripts::gnuplot(&spec))
+            .and_then(|()| fs::write(&python_path, scripts::matplotlib(&spec)))
+            .and_then(|()| fs::write(&origin_path, scripts::origin_csv(&table)));
+        self.status_message = Some(match result {
+            Ok(()) if source != path => format!(
+                "Scripts saved to {}, .py and .csv, reading {}",
+                gnuplot_path.display(),
+                source.display()
+            ),
+            Ok(()) => format!("Scripts saved to {}, .py and .csv", gnuplot_path.display()),
+            Err(e) => format!("Could not save scripts: {}", e),
+        });
+    }
+
+    /// Every column of the parsed table, as written for the scripts when
+    /// the source file cannot be read as plain columns
+    fn decoded_table(&self, path: &Path) -> DataExport {
+        let rows = (0..self.data_table.source_lines.len())
+            .map(|row| {
+                self.data_table
+                    .columns
+                    .iter()
+                    .map(|column| column[row])
+                    .collect()
+            })
+            .collect();
+        DataExport {
+            provenance: vec![
+                ("Source", path.display().to_string()),
+                ("Decoded", "JCAMP-DX data, for the plot scripts".to_string()),
+            ],
+            columns: self.data_table.names.clone(),
+            rows,
+        }
+    }
+
+    /// Processing between the file's columns and chart_data, in order
+    fn processing_steps(&self) -> Vec<String> {
+        let mut steps = Vec::new();
+        if let Some(conversion) = self.unit_conversion() {
+            let mut step = format!(
+                "X converted {} → {}",
+                conversion.from.symbol(),
+                conversion.to.symbol()
+            );
+            if conversion.from.needs_wavelength() {
+                step.push_str(&format!(" at λ = {} Å", self.xray_wavelength));
+            }
+            if self.unit_jacobian {
+                step.push_str(&format!(", Y × {}", conversion.jacobian_label()));
+            }
+            steps.push(step);
+        }
+        if let Some(method) = self.baseline_method
+            && self.subtract_baseline
+        {
+            steps.push(format!(
+                "{} baseline subtracted",
+                self.baseline_description(method)
+            ));
+        }
+        if let Some(method) = self.smooth_method {
+            steps.push(format!(
+                "{} smoothing over {} points",
+                method.name(),
+                self.smooth_window
+            ));
+        }
+        if let Some(transform) = self.transform {
+            steps.push(transform.name().to_string());
+        }
+        if self.spectrum_view {
+            let quantity = if self.spectrum_power {
+                "power"
+            } else {
+                "amplitude"
+            };
+            steps.push(format!(
+                "{} {} spectrum",
+                self.spectrum_window.name(),
+                quantity
+            ));
+        }
+        if self.normalize_series {
+            steps.push("each series scaled to a peak of 1".to_string());
+        }
+        steps
+    }
+
+    /// Whether the file holds several blocks of rows
+    fn multi_block(&self) -> bool {
+        self.data_table.blocks.len() >= 2
+    }
+
+    /// Switch between overlay, waterfall and single-block display
+    fn cycle_block_view(&mut self) {
+        self.block_view = self.block_view.next();
+        self.fit_chart_bounds();
+    }
+
+    /// Make another block the primary series, wrapping around at the ends
+    fn step_block(&mut self, delta: isize) {
+        let count = self.data_table.blocks.len();
+        if count < 2 {
+            return;
+        }
+        self.current_block =
+            (self.current_block as isize + delta).rem_euclid(count as isize) as usize;
+        self.rebuild_chart_series();
+    }
+
+    /// Show another frame of a multi-frame XYZ file and scroll the content to it
+    fn step_frame(&mut self, delta: isize) {
+        let count = self.molecule.len();
+        if count < 2 {
+            return;
+        }
+        self.molecule_frame =
+            (self.molecule_frame as isize + delta).rem_euclid(count as isize) as usize;
+        self.scroll_offset = self.molecule[self.molecule_frame].line;
+    }
+
+    /// Turn the structure preview by `yaw` and `pitch` degrees
+    fn rotate_view(&mut self, yaw: f64, pitch: f64) {
+        let (old_yaw, old_pitch) = self.view_rotation;
+        self.view_rotation = (
+            (old_yaw + yaw.to_radians()).rem_euclid(std::f64::consts::TAU),
+            (old_pitch + pitch.to_radians()).rem_euclid(std::f64::consts::TAU),
+        );
+    }
+
+    /// Vertical distance between neighbouring blocks of the waterfall: half
+    /// the largest block's Y span
+    fn waterfall_step(&self) -> f64 {
+        self.block_points
+            .iter()
+            .map(|points| {
+                let factor = self.series_factor(points);
+                let (lo, hi) = points
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                        (lo.min(p.1 * factor), hi.max(p.1 * factor))
+                    });
+                if hi > lo { hi - lo } else { 0.0 }
+            })
+            .fold(0.0, f64::max)
+            / 2.0
+    }
+
+    /// Whether the other blocks are drawn around the current one
+    fn block_curves_shown(&self) -> bool {
+        self.multi_block() && self.block_view != BlockView::Single && self.primary_in_data_units()
+    }
+
+    /// Colour of block `i` along the colormap
+    fn block_color(&self, i: usize) -> Color {
+        let last = self.data_table.blocks.len().saturating_sub(1).max(1) as f64;
+        // The dark end of the colormaps disappears on the background
+        self.colormap.color(0.2 + 0.8 * i as f64 / last)
+    }
+
+    /// The other blocks in plot space, coloured along the colormap, to draw
+    /// beneath the current one; the waterfall offsets them from it
+    fn block_curves(&self) -> Vec<PlottedSeries> {
+        if !self.block_curves_shown() {
+            return Vec::new();
+        }
+        let step = if self.block_view == BlockView::Waterfall {
+            self.waterfall_step()
+        } else {
+            0.0
+        };
+        self.block_points
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != self.current_block)
+            .map(|(i, points)| {
+                let factor = self.series_factor(points);
+                let shift = (i as f64 - self.current_block as f64) * step;
+                let plotted: Vec<(f64, f64)> = points
+                    .iter()
+                    .filter_map(|&(x, y)| {
+                        Some((
+                            self.x_transform.forward(x)?,
+                            self.y_transform.forward(y * factor + shift)?,
+                        ))
+                    })
+                    .collect();
+                PlottedSeries {
+                    name: String::new(),
+                    color: self.block_color(i),
+                    hidden: points.len() - plotted.len(),
+                    points: plotted,
+                }
+            })
+            .collect()
+    }
+
+    /// Switch between the heatmap and the line chart of gridded data
+    fn toggle_heatmap(&mut self) {
+        if self.grid.is_none() {
+            self.status_message = Some("No matrix or gridded x/y/z data".to_string());
+            return;
+        }
+        self.show_heatmap = !self.show_heatmap;
+        self.chart_focus = false;
+    }
+
+    /// Move the heatmap cursor by whole grid cells, starting from the centre
+    fn move_heatmap_cursor(&mut self, dx: isize, dy: isize) {
+        let Some(grid) = &self.grid else {
+            return;
+        };
+        let (col, row) = self
+            .heatmap_cursor
+            .unwrap_or((grid.width() / 2, grid.height() / 2));
+        self.heatmap_cursor = Some((
+            col.saturating_add_signed(dx).min(grid.width() - 1),
+            row.saturating_add_signed(dy).min(grid.height() - 1),
+        ));
+    }
+
+    /// Grid cell drawn at a screen cell, using its upper pixel
+    fn heatmap_cell_at(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        let grid = self.grid.as_ref()?;
+        let area = self.heatmap_area;
+        if column < area.x || column >= area.right() || row < area.y || row >= area.bottom() {
+            return None;
+        }
+        let x = (column - area.x) as usize * grid.width() / area.width as usize;
+        let y = 2 * (row - area.y) as usize * grid.height() / (2 * area.height as usize);
+        Some((x, y))
+    }
+
+    /// Show or hide the histogram in place of the chart
+    fn toggle_histogram(&mut self) {
+        self.show_histogram = !self.show_histogram;
+        self.chart_focus = false;
+        self.refresh_histogram();
+    }
+
+    /// Switch between the automatic bin rules and a manual bin count
+    fn cycle_bin_rule(&mut self) {
+        let bins = self.histogram.as_ref().map_or(10, |h| h.counts.len());
+        self.histogram_rule = self.histogram_rule.next(bins);
+        self.refresh_histogram();
+    }
+
+    /// Change the bin count by `delta`, switching to a manual count
+    fn adjust_bins(&mut self, delta: isize) {
+        let Some(bins) = self.histogram.as_ref().map(|h| h.counts.len()) else {
+            return;
+        };
+        let bins = bins
+            .saturating_add_signed(delta)
+            .clamp(1, histogram::MAX_BINS);
+        self.histogram_rule = BinRule::Manual(bins);
+        self.refresh_histogram();
+    }
+
+    /// Recompute chart_data from the primary column's raw points
+    ///
+    /// Apart from the spectrum, processing keeps one point per raw point, so
+    /// the cursor index and its source line stay valid.
+    fn process_chart_data(&mut self) {
+        let raw = self
+            .chart_series
+            .first()
+            .map(|series| &series.points[..])
+            .unwrap_or_default();
+
+        self.baseline = match self.baseline_method {
+            Some(method) => {
+                baseline::estimate(raw, method, self.baseline_settings).unwrap_or_else(|| {
+                    self.status_message = Some(format!(
+                        "The {} baseline could not be solved for this data",
+                        method.name()
+                    ));
+                    Vec::new()
+                })
+            }
+            None => Vec::new(),
+        };
+        let data: Vec<(f64, f64)> = if self.subtract_baseline && !self.baseline.is_empty() {
+            raw.iter()
+                .zip(&self.baseline)
+                .map(|(&(x, y), b)| (x, y - b))
+                .collect()
+        } else {
+            raw.to_vec()
+        };
+
+        // Smoothing comes first, so it also smooths before differentiating
+        let mut data = data;
+        let mut unsmoothed = Vec::new();
+        if let Some(method) = self.smooth_method {
+            let smoothed = smooth::smooth(&data, method, self.smooth_window);
+            let smoothed_data = data.iter().zip(smoothed).map(|(p, y)| (p.0, y)).collect();
+            unsmoothed = std::mem::replace(&mut data, smoothed_data);
+        }
+        if let Some(transform) = self.transform {
+            let transformed = transform.apply(&data);
+            data = data
+                .iter()
+                .zip(transformed)
+                .map(|(p, y)| (p.0, y))
+                .collect();
+            // Untransformed points do not share the transformed units
+            unsmoothed.clear();
+        }
+        self.spectrum = None;
+        if self.spectrum_view {
+            match spectrum::compute(&data, self.spectrum_window, self.spectrum_power) {
+                Ok(spectrum) => {
+                    data = spectrum.points.clone();
+                    unsmoothed.clear();
+                    self.spectrum = Some(spectrum);
+                }
+                Err(message) => {
+                    self.status_message = Some(message);
+                    self.spectrum_view = false;
+                }
+            }
+        }
+        self.chart_data = data;
+        self.unsmoothed_data = unsmoothed;
+
+        self.fit_result = None;
+        self.refresh_peaks();
+        self.fit_chart_bounds();
+        self.refresh_histogram();
+    }
+
+    /// Switch to the next baseline algorithm, or off after the last one
+    fn cycle_baseline(&mut self) {
+        self.baseline_method = BaselineMethod::cycle(self.baseline_method);
+        if self.baseline_method.is_none() {
+            self.subtract_baseline = false;
+        }
+        self.process_chart_data();
+    }
+
+    /// Toggle between the raw data and the data minus its baseline
+    fn toggle_baseline_subtraction(&mut self) {
+        if self.baseline_method.is_none() {
+            self.status_message = Some("Choose a baseline with b first".to_string());
+            return;
+        }
+        self.subtract_baseline = !self.subtract_baseline;
+        self.process_chart_data();
+    }
+
+    /// Baseline settings as applied to the primary column's raw points
+    fn baseline_description(&self, method: BaselineMethod) -> String {
+        let points = self.chart_series.first().map_or(0, |s| s.points.len());
+        method.describe(points, self.baseline_settings)
+    }
+
+    /// Raise or lower the current baseline's polynomial degree, or widen or
+    /// narrow its window by `steps` factors of 1.25
+    fn adjust_baseline_setting(&mut self, steps: i32) {
+        let Some(method) = self.baseline_method else {
+            self.status_message = Some("Choose a baseline with b first".to_string());
+            return;
+        };
+        let settings = &mut self.baseline_settings;
+        if method.uses_degree() {
+            settings.degree = settings
+                .degree
+                .saturating_add_signed(steps as isize)
+                .clamp(baseline::MIN_DEGREE, baseline::MAX_DEGREE);
+        } else {
+            settings.window = (settings.window * 1.25f64.powi(steps))
+                .clamp(baseline::MIN_WINDOW, baseline::MAX_WINDOW);
+        }
+        self.process_chart_data();
+    }
+
+    /// Switch to the next smoothing filter, or off after the last one
+    fn cycle_smoothing(&mut self) {
+        self.smooth_method = SmoothMethod::cycle(self.smooth_method);
+        self.process_chart_data();
+    }
+
+    /// Widen or narrow the smoothing window by `steps` points on each side
+    fn adjust_smooth_window(&mut self, steps: isize) {
+        if self.smooth_method.is_none() {
+            self.status_message = Some("Choose a smoothing filter with s first".to_string());
+            return;
+        }
+        self.smooth_window = self
+            .smooth_window
+            .saturating_add_signed(2 * steps)
+            .clamp(smooth::MIN_WINDOW, smooth::MAX_WINDOW);
+        self.process_chart_data();
+    }
+
+    /// Switch to the next transform, or back to the data after the integral
+    fn cycle_transform(&mut self) {
+        self.transform = Transform::cycle(self.transform);
+        self.integral_limits.clear();
+        self.process_chart_data();
+    }
+
+    /// Use the crosshair X as an integration limit; a third pick starts over
+    fn add_integral_limit(&mut self) {
+        let Some(&(x, _)) = self.chart_cursor.and_then(|i| self.chart_data.get(i)) else {
+            self.status_message = Some("Place the cursor with v first".to_string());
+            return;
+        };
+        if self.integral_limits.len() == 2 {
+            self.integral_limits.clear();
+        }
+        self.integral_limits.push(x);
+    }
+
+    /// Integral of chart_data between the two picked limits
+    fn limits_integral(&self) -> Option<f64> {
+        match self.integral_limits[..] {
+            [a, b] => transform::integrate(&self.chart_data, a, b),
+            _ => None,
+        }
+    }
+
+    /// Show or hide the spectrum in place of the data
+    fn toggle_spectrum_view(&mut self) {
+        self.spectrum_view = !self.spectrum_view;
+        self.chart_cursor = None;
+        self.integral_limits.clear();
+        self.process_chart_data();
+    }
+
+    fn cycle_spectrum_window(&mut self) {
+        if !self.spectrum_view {
+            self.status_message = Some("Show the spectrum with S first".to_string());
+            return;
+        }
+        self.spectrum_window = self.spectrum_window.next();
+        self.process_chart_data();
+    }
+
+    /// Switch the spectrum between amplitude and power
+    fn toggle_spectrum_power(&mut self) {
+        if !self.spectrum_view {
+            self.status_message = Some("Show the spectrum with S first".to_string());
+            return;
+        }
+        self.spectrum_power = !self.spectrum_power;
+        self.process_chart_data();
+    }
+
+    /// Whether chart_data is in the primary column's own units
+    fn primary_in_data_units(&self) -> bool {
+        self.transform.is_none() && !self.spectrum_view
+    }
+
+    /// Y errors of chart_data, while it is still in data units
+    fn chart_errors(&self) -> Option<&[f64]> {
+        (!self.y_errors.is_empty() && self.primary_in_data_units()).then_some(&self.y_errors[..])
+    }
+
+    /// Error bars of the primary series in plot space, one segment each
+    ///
+    /// When bars would crowd together, each of the `columns` plot columns gets a
+    /// single vertical segment spanning its bars instead, and together they
+    /// shade a band. Returns the segments and whether they form a band.
+    fn error_bar_segments(
+        &self,
+        (x_bounds, y_bounds): ([f64; 2], [f64; 2]),
+        columns: usize,
+    ) -> (Vec<[(f64, f64); 2]>, bool) {
+        if !self.primary_in_data_units() || (self.y_errors.is_empty() && self.x_errors.is_empty()) {
+            return (Vec::new(), false);
+        }
+        let points = self.series_points(0);
+        let factor = self.series_factor(points);
+        let error =
+            |errors: &[f64], i: usize| errors.get(i).copied().filter(|e| e.is_finite() && *e > 0.0);
+        let clip_x = |x: f64| x.clamp(x_bounds[0], x_bounds[1]);
+        let clip_y = |y: f64| y.clamp(y_bounds[0], y_bounds[1]);
+        // A log axis cannot show the lower end of a bar reaching below zero
+        let vertical = |i: usize| {
+            let (_, y) = points[i];
+            let sigma = error(&self.y_errors, i)?;
+            let hi = self.y_transform.forward((y + sigma) * factor)?;
+            let lo = self
+                .y_transform
+                .forward((y - sigma) * factor)
+                .unwrap_or(y_bounds[0]);
+            Some((clip_y(lo), clip_y(hi)))
+        };
+        let visible: Vec<(usize, f64)> = points
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(x, _))| {
+                let px = self.x_transform.forward(x)?;
+                (px >= x_bounds[0] && px <= x_bounds[1]).then_some((i, px))
+            })
+            .collect();
+
+        if visible.len() * 2 > columns {
+            // The canvas maps the X range onto columns - 1 cells
+            let cell = (x_bounds[1] - x_bounds[0]) / columns.saturating_sub(1).max(1) as f64;
+            let mut spans: Vec<Option<(f64, f64)>> = vec![None; columns];
+            for &(i, px) in &visible {
+                let Some((lo, hi)) = vertical(i) else {
+                    continue;
+                };
+                let column = (((px - x_bounds[0]) / cell) as usize).min(columns - 1);
+                let span = spans[column].get_or_insert((lo, hi));
+                *span = (span.0.min(lo), span.1.max(hi));
+            }
+            let segments = spans
+                .iter()
+                .enumerate()
+                .filter_map(|(column, span)| {
+                    let (lo, hi) = (*span)?;
+                    let x = clip_x(x_bounds[0] + (column as f64 + 0.5) * cell);
+                    Some([(x, lo), (x, hi)])
+                })
+                .collect();
+            return (segments, true);
+        }
+
+        let mut segments = Vec::new();
+        for &(i, px) in &visible {
+            if let Some((lo, hi)) = vertical(i) {
+                segments.push([(px, lo), (px, hi)]);
+            }
+            let (x, y) = points[i];
+            if let (Some(sigma), Some(py)) = (
+                error(&self.x_errors, i),
+                self.y_transform.forward(y * factor),
+            ) && let Some(right) = self.x_transform.forward(x + sigma)
+            {
+                let left = self.x_transform.forward(x - sigma).unwrap_or(x_bounds[0]);
+                segments.push([(clip_x(left), py), (clip_x(right), py)]);
+            }
+        }
+        (segments, false)
+    }
+
+    /// X axis quantity of chart_data
+    fn x_axis_name(&self) -> String {
+        let column = self.data_table.column_name(self.x_column);
+        let name = match (self.x_unit, self.unit_conversion()) {
+            (_, Some(conversion)) => {
+                format!("{} ({})", conversion.to.quantity(), conversion.to.symbol())
+            }
+            (Some(unit), None) => format!("{} ({})", column, unit.symbol()),
+            (None, None) => column.to_string(),
+        };
+        if self.spectrum_view {
+            format!("frequency (1/{})", name)
+        } else {
+            name
+        }
+    }
+
+    /// Current file's series on the chart; transforms only apply to the primary one
+    fn shown_series_count(&self) -> usize {
+        if !self.primary_in_data_units() {
+            self.chart_series.len().min(1)
+        } else {
+            self.chart_series.len()
+        }
+    }
+
+    /// Points drawn for the current file's series
+    ///
+    /// The primary series is drawn as processed, minus smoothing: the smoothed
+    /// curve is drawn over it separately.
+    fn series_points(&self, index: usize) -> &[(f64, f64)] {
+        if index == 0 && !self.unsmoothed_data.is_empty() {
+            &self.unsmoothed_data
+        } else if index == 0 {
+            &self.chart_data
+        } else {
+            &self.chart_series[index].points
+        }
+    }
+
+    /// Legend name of a current file's series
+    fn series_name(&self, index: usize) -> String {
+        let name = self
+            .data_table
+            .column_name(self.chart_series[index].column)
+            .to_string();
+        // Every series shares the converted X axis
+        let name = match self.unit_conversion().filter(|_| self.unit_jacobian) {
+            Some(conversion) => format!("{} × {}", name, conversion.jacobian_label()),
+            None => name,
+        };
+        if index > 0 {
+            return name;
+        }
+        let name = if self.subtract_baseline {
+            format!("{} − baseline", name)
+        } else {
+            name
+        };
+        let name = match self.transform {
+            Some(transform) => transform.label(&name, self.data_table.column_name(self.x_column)),
+            None => name,
+        };
+        if self.spectrum_view {
+            let quantity = if self.spectrum_power {
+                "power"
+            } else {
+                "amplitude"
+            };
+            format!("{} of {}", quantity, name)
+        } else {
+            name
+        }
+    }
+
+    /// Re-run peak finding on chart_data while the peak table is shown
+    fn refresh_peaks(&mut self) {
+        if self.info_panel == InfoPanel::Peaks {
+            self.peaks = peaks::find_peaks(&self.chart_data, &self.peak_params);
+        } else {
+            self.peaks.clear();
+        }
+        self.peak_selected = self.peak_selected.min(self.peaks.len().saturating_sub(1));
+    }
+
+    /// Show or hide the peak table
+    fn toggle_peak_table(&mut self) {
+        self.info_panel = if self.info_panel == InfoPanel::Peaks {
+            InfoPanel::Stats
+        } else {
+            InfoPanel::Peaks
+        };
+        self.peak_selected = 0;
+        self.refresh_peaks();
+    }
+
+    /// Show or hide the chain and residue jump list of a PDB file
+    fn toggle_jump_list(&mut self) {
+        if self.info_panel == InfoPanel::Jump {
+            self.info_panel = InfoPanel::Stats;
+        } else if self.jump_targets.is_empty() {
+            self.status_message = Some("No chains or ligands to jump to".to_string());
+        } else {
+            self.info_panel = InfoPanel::Jump;
+            self.select_jump(self.jump_selected);
+        }
+    }
+
+    /// Select a jump list entry and scroll the content viewer to its line
+    fn select_jump(&mut self, index: usize) {
+        let Some(target) = self.jump_targets.get(index) else {
+            return;
+        };
+        self.jump_selected = index;
+        let max_scroll = self.file_content.len().saturating_sub(self.visible_height);
+        self.scroll_offset = target.line.min(max_scroll);
+    }
+
+    /// Select the previous or next chain of the jump list
+    fn step_jump_chain(&mut self, delta: isize) {
+        let chains: Vec<usize> = (0..self.jump_targets.len())
+            .filter(|&i| self.jump_targets[i].kind == JumpKind::Chain)
+            .collect();
+        let next = if delta < 0 {
+            chains.iter().rev().find(|&&i| i < self.jump_selected)
+        } else {
+            chains.iter().find(|&&i| i > self.jump_selected)
+        };
+        if let Some(&index) = next {
+            self.select_jump(index);
+        }
+    }
+
+    /// Line of the selected jump list entry while the list is open
+    fn jump_line(&self) -> Option<usize> {
+        (self.info_panel == InfoPanel::Jump)
+            .then(|| self.jump_targets.get(self.jump_selected))
+            .flatten()
+            .map(|target| target.line)
+    }
+
+    /// Scale the minimum prominence, kept between 0.1% and 100% of the Y range
+    fn adjust_peak_prominence(&mut self, factor: f64) {
+        self.peak_params.prominence = (self.peak_params.prominence * factor).clamp(0.001, 1.0);
+        self.refresh_peaks();
+    }
+
+    fn adjust_peak_distance(&mut self, delta: isize) {
+        self.peak_params.min_distance = self
+            .peak_params
+            .min_distance
+            .saturating_add_signed(delta)
+            .max(1);
+        self.refresh_peaks();
+    }
+
+    /// Shift the height threshold in 5% steps of the Y range
+    fn adjust_peak_threshold(&mut self, delta: f64) {
+        self.peak_params.threshold = (self.peak_params.threshold + delta).clamp(0.0, 0.95);
+        self.refresh_peaks();
+    }
+
+    /// Fit the chart bounds to the plotted data and drop any zoom
+    fn fit_chart_bounds(&mut self) {
+        let plotted = self.plotted_series();
+        match Self::compute_bounds(
+            plotted.iter().map(|p| &p.points[..]),
+            &self.x_transform,
+            &self.y_transform,
+        ) {
+            Some(bounds) => self.chart_bounds = bounds,
+            None => {
+                // Keep no bounds from the previous file or scale
+                self.chart_bounds = DEFAULT_BOUNDS;
+                if plotted.iter().any(|series| series.hidden > 0) {
+                    self.status_message =
+                        Some("No points can be shown on these axis scales".to_string());
+                }
+            }
+        }
+        self.chart_view = None;
+    }
+
+    /// Current file's series followed by the pinned ones, mapped into plot space
+    fn plotted_series(&self) -> Vec<PlottedSeries> {
+        let current =
+            (0..self.shown_series_count()).map(|i| (self.series_name(i), self.series_points(i)));
+        let pinned = self
+            .pinned_series
+            .iter()
+            .map(|series| (series.label.clone(), &series.points[..]));
+        let mut plotted = self.block_curves();
+        plotted.extend(
+            current
+                .chain(pinned)
+                .enumerate()
+                .map(|(i, (name, points))| {
+                    let plotted = self.plot_points(points, self.series_factor(points));
+                    PlottedSeries {
+                        name,
+                        color: SERIES_COLORS[i % SERIES_COLORS.len()],
+                        hidden: points.len() - plotted.len(),
+                        points: plotted,
+                    }
+                }),
+        );
+        plotted
+    }
+
+    /// Y multiplier for a series: 1, or 1/peak magnitude when normalizing
+    fn series_factor(&self, points: &[(f64, f64)]) -> f64 {
+        if !self.normalize_series {
+            return 1.0;
+        }
+        let peak = points.iter().map(|p| p.1.abs()).fold(0.0, f64::max);
+        if peak > 0.0 { 1.0 / peak } else { 1.0 }
+    }
+
+    /// Map data points into plot space, dropping values the axis scales cannot show
+    fn plot_points(&self, points: &[(f64, f64)], y_factor: f64) -> Vec<(f64, f64)> {
+        points
+            .iter()
+            .filter_map(|&(x, y)| {
+                Some((
+                    self.x_transform.forward(x)?,
+                    self.y_transform.forward(y * y_factor)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Whether there is anything to plot, from this file or pinned
+    fn has_chart(&self) -> bool {
+        !self.chart_data.is_empty() || !self.pinned_series.is_empty()
+    }
+
+    /// Pin the current file's plotted columns so they stay on the chart
+    fn pin_current_series(&mut self) {
+        let file_name = self
+            .current_file
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for i in 0..self.shown_series_count() {
+            let label = format!("{}:{}", file_name, self.series_name(i));
+            if !self.pinned_series.iter().any(|p| p.label == label) {
+                self.pinned_series.push(PinnedSeries {
+                    label,
+                    points: self.series_points(i).to_vec(),
+                });
+            }
+        }
+        self.fit_chart_bounds();
+    }
+
+    /// Remove the highlighted pinned series
+    fn unpin_selected(&mut self) {
+        if self.pinned_selected < self.pinned_series.len() {
+            self.pinned_series.remove(self.pinned_selected);
+            self.pinned_selected = self
+                .pinned_selected
+                .min(self.pinned_series.len().saturating_sub(1));
+            self.fit_chart_bounds();
+        }
+    }
+
+    /// Visible X range of the chart in data units
+    fn visible_x_range(&self) -> [f64; 2] {
+        let (x_bounds, _) = self.view_bounds();
+        [
+            self.x_transform.inverse(x_bounds[0]),
+            self.x_transform.inverse(x_bounds[1]),
+        ]
+    }
+
+    /// Fit peaks of chart_data inside the visible X range
+    ///
+    /// Starting values come from the peak finder; without any detected peak a
+    /// single peak is placed at the highest point.
+    fn fit_visible_range(&mut self) {
+        let x_range = self.visible_x_range();
+        let in_range: Vec<(f64, f64)> = self
+            .chart_data
+            .iter()
+            .copied()
+            .filter(|p| p.0 >= x_range[0] && p.0 <= x_range[1])
+            .collect();
+        let mut guesses: Vec<(f64, f64, f64)> = peaks::find_peaks(&in_range, &self.peak_params)
+            .iter()
+            .map(|p| (p.x, p.height, p.fwhm))
+            .collect();
+        if guesses.is_empty()
+            && let Some(&(x, y)) = in_range.iter().max_by(|a, b| a.1.total_cmp(&b.1))
+        {
+            guesses.push((x, y, (x_range[1] - x_range[0]) / 10.0));
+        }
+
+        self.fit_result = fit::fit_peaks(
+            &self.chart_data,
+            self.chart_errors(),
+            x_range,
+            &guesses,
+            self.fit_profile,
+        );
+        if self.fit_result.is_none() {
+            let params = 2 + self.fit_profile.param_count() * guesses.len();
+            self.status_message = Some(if in_range.len() <= params {
+                "Fit failed: not enough points in view".to_string()
+            } else {
+                "Fit failed: no unique solution in this range".to_string()
+            });
+        }
+    }
+
+    /// Switch the peak profile, refitting an existing fit over the same range
+    fn cycle_fit_profile(&mut self) {
+        self.fit_profile = self.fit_profile.next();
+        if let Some(x_range) = self.fit_result.as_ref().map(|r| r.x_range) {
+            let guesses: Vec<(f64, f64, f64)> = self
+                .fit_result
+                .iter()
+                .flat_map(|r| &r.peaks)
+                .map(|p| (p.center.value, p.height.value, p.fwhm.value))
+                .collect();
+            self.fit_result = fit::fit_peaks(
+                &self.chart_data,
+                self.chart_errors(),
+                x_range,
+                &guesses,
+                self.fit_profile,
+            );
+        }
+    }
+
+    /// Write the fit report next to the data file as `<name>_fit.txt`
+    fn export_fit(&mut self) {
+        let (Some(result), Some(path)) = (&self.fit_result, &self.current_file) else {
+            self.status_message = Some("No fit to export".to_string());
+            return;
+        };
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let out_path = path.with_file_name(format!("{}_fit.txt", stem));
+        let report = result.report(&path.display().to_string());
+        self.status_message = Some(match fs::write(&out_path, report) {
+            Ok(()) => format!("Fit saved to {}", out_path.display()),
+            Err(e) => format!("Could not save fit: {}", e),
+        });
+    }
+
+    /// Toggle scaling every series to a shared peak of 1
+    fn toggle_normalize(&mut self) {
+        self.normalize_series = !self.normalize_series;
+        self.fit_chart_bounds();
+    }
+
+    /// Cycle the X axis through linear, log10 and symlog
+    fn cycle_x_scale(&mut self) {
+        self.x_transform.scale = self.x_transform.scale.next();
+        self.fit_chart_bounds();
+    }
+
+    /// Cycle the Y axis through linear, log10 and symlog
+    fn cycle_y_scale(&mut self) {
+        self.y_transform.scale = self.y_transform.scale.next();
+        self.fit_chart_bounds();
+    }
+
+    /// Currently visible chart range: the zoomed view or the full data extent
+    fn view_bounds(&self) -> ([f64; 2], [f64; 2]) {
+        self.chart_view.unwrap_or(self.chart_bounds)
+    }
+
+    /// Scale the view around `center` (factor < 1 zooms in)
+    fn zoom_chart(&mut self, factor: f64, center: (f64, f64)) {
+        let (x_bounds, y_bounds) = self.view_bounds();
+        let scale = |b: [f64; 2], c: f64| [c - (c - b[0]) * factor, c + (b[1] - c) * factor];
+        self.set_chart_view(scale(x_bounds, center.0), scale(y_bounds, center.1));
+    }
+
+    /// Zoom around the middle of the current view
+    fn zoom_chart_center(&mut self, factor: f64) {
+        let (x_bounds, y_bounds) = self.view_bounds();
+        let center = (
+            (x_bounds[0] + x_bounds[1]) / 2.0,
+            (y_bounds[0] + y_bounds[1]) / 2.0,
+        );
+        self.zoom_chart(factor, center);
+    }
+
+    /// Shift the view by a fraction of its width and height
+    fn pan_chart(&mut self, dx: f64, dy: f64) {
+        let (x_bounds, y_bounds) = self.view_bounds();
+        let x_shift = (x_bounds[1] - x_bounds[0]) * dx;
+        let y_shift = (y_bounds[1] - y_bounds[0]) * dy;
+        self.set_chart_view(
+            [x_bounds[0] + x_shift, x_bounds[1] + x_shift],
+            [y_bounds[0] + y_shift, y_bounds[1] + y_shift],
+        );
+    }
+
+    /// Apply a new view, ignoring degenerate ranges beyond floating point resolution
+    fn set_chart_view(&mut self, x_bounds: [f64; 2], y_bounds: [f64; 2]) {
+        let valid = |b: [f64; 2]| {
+            b[0].is_finite() && b[1].is_finite() && b[1] - b[0] > b[0].abs().max(1e-300) * 1e-12
+        };
+        if valid(x_bounds) && valid(y_bounds) {
+            self.chart_view = Some((x_bounds, y_bounds));
+        }
+    }
+
+    /// Map a terminal cell inside the plot area to chart coordinates
+    fn screen_to_chart(&self, column: u16, row: u16) -> Option<(f64, f64)> {
+        let area = self.chart_plot_area;
+        if area.width == 0
+            || area.height == 0
+            || column < area.x
+            || column >= area.right()
+            || row < area.y
+            || row >= area.bottom()
+        {
+            return None;
+        }
+        let (x_bounds, y_bounds) = self.view_bounds();
+        let fx = (f64::from(column - area.x) + 0.5) / f64::from(area.width);
+        let fy = (f64::from(row - area.y) + 0.5) / f64::from(area.height);
+        Some((
+            x_bounds[0] + fx * (x_bounds[1] - x_bounds[0]),
+            y_bounds[1] - fy * (y_bounds[1] - y_bounds[0]),
+        ))
+    }
+
+    /// Index of the chart_data point whose plotted X is nearest to `x` (plot space)
+    fn nearest_point(&self, x: f64) -> Option<usize> {
+        self.chart_data
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| Some((i, (self.x_transform.forward(p.0)? - x).abs())))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Show or hide the crosshair, starting at the middle of the view
+    fn toggle_chart_cursor(&mut self) {
+        if self.chart_cursor.is_some() {
+            self.chart_cursor = None;
+        } else {
+            let (x_bounds, _) = self.view_bounds();
+            self.set_chart_cursor(self.nearest_point((x_bounds[0] + x_bounds[1]) / 2.0));
+        }
+    }
+
+    /// Step the crosshair to the neighbouring point in X order
+    fn step_chart_cursor(&mut self, forward: bool) {
+        let Some(current) = self.chart_cursor else {
+            return;
+        };
+        let Some(cx) = self.chart_data.get(current).map(|p| p.0) else {
+            return;
+        };
+        let x_transform = self.x_transform;
+        let candidates = self
+            .chart_data
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| x_transform.forward(p.0).is_some())
+            .filter(|(_, p)| if forward { p.0 > cx } else { p.0 < cx });
+        let next = if forward {
+            candidates.min_by(|a, b| a.1.0.total_cmp(&b.1.0))
+        } else {
+            candidates.max_by(|a, b| a.1.0.total_cmp(&b.1.0))
+        };
+        if let Some((i, _)) = next {
+            self.set_chart_cursor(Some(i));
+        }
+    }
+
+    /// Jump the crosshair by a fraction of the visible X range
+    fn jump_chart_cursor(&mut self, fraction: f64) {
+        let Some(cx) = self
+            .chart_cursor
+            .and_then(|i| self.chart_data.get(i))
+            .and_then(|p| self.x_transform.forward(p.0))
+        else {
+            return;
+        };
+        let (x_bounds, _) = self.view_bounds();
+        let target = cx + (x_bounds[1] - x_bounds[0]) * fraction;
+        self.set_chart_cursor(self.nearest_point(target));
+    }
+
+    /// Move the crosshair and scroll its source line into view
+    fn set_chart_cursor(&mut self, index: Option<usize>) {
+        self.chart_cursor = index;
+        if let Some(line) = self.cursor_source_line()
+            && (line < self.scroll_offset || line >= self.scroll_offset + self.visible_height)
+        {
+            let max_scroll = self.file_content.len().saturating_sub(self.visible_height);
+            self.scroll_offset = line.saturating_sub(self.visible_height / 2).min(max_scroll);
+        }
+    }
+
+    /// Zero-based source line of the point under the crosshair
+    fn cursor_source_line(&self) -> Option<usize> {
+        if self.spectrum_view {
+            return None;
+        }
+        let index = self.chart_cursor?;
+        self.chart_series.first()?.lines.get(index).copied()
+    }
+
+    /// Handle mouse wheel zoom and rubber-band zoom over the chart
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if !self.show_chart || !self.has_chart() {
+            return;
+        }
+        if self.show_heatmap && !self.show_histogram {
+            // A click reads out the value under it
+            if let MouseEventKind::Down(MouseButton::Left) = mouse.kind
+                && let Some(cell) = self.heatmap_cell_at(mouse.column, mouse.row)
+            {
+                self.chart_focus = true;
+                self.heatmap_cursor = Some(cell);
+            }
+            return;
+        }
+        let cell = (mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::ScrollUp => {
+                if let Some(center) = self.screen_to_chart(cell.0, cell.1) {
+                    self.zoom_chart(0.8, center);
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                if let Some(center) = self.screen_to_chart(cell.0, cell.1) {
+                    self.zoom_chart(1.25, center);
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left)
+                if self.screen_to_chart(cell.0, cell.1).is_some() =>
+            {
+                self.chart_focus = true;
+                self.chart_drag = Some((cell, cell));
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some((start, _)) = self.chart_drag {
+                    // Keep the band inside the plot area
+                    let area = self.chart_plot_area;
+                    let end = (
+                        cell.0.clamp(area.x, area.right().saturating_sub(1)),
+                        cell.1.clamp(area.y, area.bottom().saturating_sub(1)),
+                    );
+                    self.chart_drag = Some((start, end));
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let Some((start, end)) = self.chart_drag.take() else {
+                    return;
+                };
+                if start == end {
+                    // A click places the crosshair
+                    if let Some((x, _)) = self.screen_to_chart(start.0, start.1) {
+                        self.set_chart_cursor(self.nearest_point(x));
+                    }
+                } else if start.0 != end.0
+                    && start.1 != end.1
+                    && let (Some(a), Some(b)) = (
+                        self.screen_to_chart(start.0, start.1),
+                        self.screen_to_chart(end.0, end.1),
+                    )
+                {
+                    self.set_chart_view([a.0.min(b.0), a.0.max(b.0)], [a.1.min(b.1), a.1.max(b.1)]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Calculate (x_bounds, y_bounds) covering every point of every series
+    fn compute_bounds<'a>(
+        series: impl Iterator<Item = &'a [(f64, f64)]>,
+        x_transform: &AxisTransform,
+        y_transform: &AxisTransform,
+    ) -> Option<([f64; 2], [f64; 2])> {
+        let mut x_min = f64::INFINITY;
+        let mut x_max = f64::NEG_INFINITY;
+        let mut y_min = f64::INFINITY;
+        let mut y_max = f64::NEG_INFINITY;
+        for &(x, y) in series.flatten() {
+            x_min = x_min.min(x);
+            x_max = x_max.max(x);
+            y_min = y_min.min(y);
+            y_max = y_max.max(y);
+        }
+        if !x_min.is_finite() {
+            return None;
+        }
+
+        Some((x_transform.fit(x_min, x_max), y_transform.fit(y_min, y_max)))
+    }
+
+    /// Make the highlighted picker column the X axis
+    fn set_picker_x_column(&mut self) {
+        let column = self.column_picker_selected;
+        if column >= self.data_table.column_count() {
+            return;
+        }
+        self.x_column = column;
+        self.y_columns.retain(|&c| c != column);
+        self.clear_error_column(column);
+        self.rebuild_chart_series();
+    }
+
+    /// Mark the highlighted picker column as the Y (or X) error, or unmark it
+    fn toggle_picker_error_column(&mut self, x_error: bool) {
+        let column = self.column_picker_selected;
+        if column >= self.data_table.column_count() || column == self.x_column {
+            return;
+        }
+        let target = if x_error {
+            &mut self.x_error_column
+        } else {
+            &mut self.y_error_column
+        };
+        if *target == Some(column) {
+            *target = None;
+        } else {
+            *target = Some(column);
+            self.y_columns.retain(|&c| c != column);
+            // One column cannot hold both errors
+            let other = if x_error {
+                &mut self.y_error_column
+            } else {
+                &mut self.x_error_column
+            };
+            if *other == Some(column) {
+                *other = None;
+            }
+        }
+        self.rebuild_chart_series();
+    }
+
+    /// Forget `column` as an error column once it is plotted
+    fn clear_error_column(&mut self, column: usize) {
+        if self.y_error_column == Some(column) {
+            self.y_error_column = None;
+        }
+        if self.x_error_column == Some(column) {
+            self.x_error_column = None;
+        }
+    }
+
+    /// Add or remove the highlighted picker column from the plotted Y columns
+    fn toggle_picker_y_column(&mut self) {
+        let column = self.column_picker_selected;
+        if column >= self.data_table.column_count() || column == self.x_column {
+            return;
+        }
+        if let Some(pos) = self.y_columns.iter().position(|&c| c == column) {
+            self.y_columns.remove(pos);
+        } else {
+            self.y_columns.push(column);
+            self.y_columns.sort_unstable();
+            self.clear_error_column(column);
+        }
+        self.rebuild_chart_series();
+    }
+
+    /// Downsample data while preserving peaks (local minima and maxima)
+    fn downsample_with_peaks(data: &[(f64, f64)], target_points: usize) -> Vec<(f64, f64)> {
+        if data.len() <= target_points {
+            return data.to_vec();
+        }
+
+        let mut result: Vec<(f64, f64)> = Vec::with_capacity(target_points);
+
+        // Always include first point
+        result.push(data[0]);
+
+        // Calculate bucket size
+        let bucket_size = (data.len() - 2) as f64 / (target_points - 2) as f64;
+
+        for i in 1..(target_points - 1) {
+            let start = (1.0 + (i - 1) as f64 * bucket_size) as usize;
+            let end = (1.0 + i as f64 * bucket_size) as usize;
+            let end = end.min(data.len() - 1);
+
+            if start >= end {
+                continue;
+            }
+
+            // Find min and max Y in this bucket
+            let mut min_idx = start;
+            let mut max_idx = start;
+            let mut min_y = data[start].1;
+            let mut max_y = data[start].1;
+
+            for (j, point) in data.iter().enumerate().take(end).skip(start) {
+                if point.1 < min_y {
+                    min_y = point.1;
+                    min_idx = j;
+                }
+                if point.1 > max_y {
+                    max_y = point.1;
+                    max_idx = j;
+                }
+            }
+
+            // Add min and max in X order to preserve shape
+            if min_idx < max_idx {
+                result.push(data[min_idx]);
+                if min_idx != max_idx {
+                    result.push(data[max_idx]);
+                }
+            } else {
+                result.push(data[max_idx]);
+                if min_idx != max_idx {
+                    result.push(data[min_idx]);
+                }
+            }
+        }
+
+        // Always include last point
+        result.push(data[data.len() - 1]);
+
+        result
+    }
+
+    /// Reveal a file in the tree by switching to its directory and selecting it
+    fn reveal_file_in_tree(&mut self, path: &PathBuf) {
+        if let Some(parent) = path.parent() {
+            self.current_directory = parent.to_path_buf();
+            self.refresh_directory();
+            if let Some(pos) = self
+                .entries
+                .iter()
+                .position(|entry| !entry.is_dir && entry.path == *path)
+            {
+                self.selected_index = pos;
+            }
+        }
+    }
+}
+
+fn main() -> Result<(), io::Error> {
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Create app state
+    let mut app = App::new();
+
+    // Run the app
+    let res = run_app(&mut terminal, &mut app);
+
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    if let Err(err) = res {
+        println!("Error: {:?}", err);
+    }
+
+    Ok(())
+}
+
+fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> io::Result<()> {
+    loop {
+        terminal.draw(|f| ui(f, app))?;
+
+        let event = event::read()?;
+        if let Event::Mouse(mouse) = event {
+            app.handle_mouse(mouse);
+            continue;
+        }
+
+        if let Event::Key(key) = event {
+            // Messages only last until the next key press
+            app.status_message = None;
+
+            // A prompt takes every key until Enter or Esc
+            if let Some((_, input)) = app.prompt.as_mut() {
+                match key.code {
+                    KeyCode::Enter => app.finish_prompt(),
+                    KeyCode::Esc => app.prompt = None,
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            // Key reference popup scrolls until closed
+            if app.show_key_help {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => {
+                        app.show_key_help = false;
+                    }
+                    KeyCode::Up => app.key_help_scroll = app.key_help_scroll.saturating_sub(1),
+                    KeyCode::Down => {
+                        app.key_help_scroll = (app.key_help_scroll + 1).min(KEY_HELP.len() - 1)
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            if key.code == KeyCode::Char('?') {
+                app.show_key_help = true;
+                app.key_help_scroll = 0;
+                continue;
+            }
+
+            // Handle recent files popup first
+            if app.show_recent_files {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => {
+                        app.show_recent_files = false;
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        if app.recent_files.is_empty() {
+                            continue;
+                        }
+                        app.recent_files_selected = app
+                            .recent_files_selected
+                            .checked_sub(1)
+                            .unwrap_or(app.recent_files.len() - 1);
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        if app.recent_files.is_empty() {
+                            continue;
+                        }
+                        app.recent_files_selected =
+                            (app.recent_files_selected + 1) % app.recent_files.len();
+                        continue;
+                    }
+                    KeyCode::Enter => {
+                        if let Some(path) = app.recent_files.get(app.recent_files_selected) {
+                            let path = path.clone();
+                            app.show_recent_files = false;
+                            app.reveal_file_in_tree(&path);
+                            app.open_file(&path);
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Pinned series popup
+            if app.show_pinned {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('P') => {
+                        app.show_pinned = false;
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        if !app.pinned_series.is_empty() {
+                            app.pinned_selected = app
+                                .pinned_selected
+                                .checked_sub(1)
+                                .unwrap_or(app.pinned_series.len() - 1);
+                        }
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        if !app.pinned_series.is_empty() {
+                            app.pinned_selected =
+                                (app.pinned_selected + 1) % app.pinned_series.len();
+                        }
+                        continue;
+                    }
+                    KeyCode::Char('d') | KeyCode::Delete | KeyCode::Backspace => {
+                        app.unpin_selected();
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Column picker captures navigation keys while open
+            if app.info_panel == InfoPanel::Columns {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('p') => {
+                        app.info_panel = InfoPanel::Stats;
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        let count = app.data_table.column_count();
+                        if count > 0 {
+                            app.column_picker_selected = app
+                                .column_picker_selected
+                                .checked_sub(1)
+                                .unwrap_or(count - 1);
+                        }
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        let count = app.data_table.column_count();
+                        if count > 0 {
+                            app.column_picker_selected = (app.column_picker_selected + 1) % count;
+                        }
+                        continue;
+                    }
+                    KeyCode::Char('x') => {
+                        app.set_picker_x_column();
+                        continue;
+                    }
+                    KeyCode::Char(' ') | KeyCode::Enter => {
+                        app.toggle_picker_y_column();
+                        continue;
+                    }
+                    KeyCode::Char('e') => {
+                        app.toggle_picker_error_column(false);
+                        continue;
+                    }
+                    KeyCode::Char('E') => {
+                        app.toggle_picker_error_column(true);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Peak table: selection and search settings
+            if app.info_panel == InfoPanel::Peaks {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('f') => {
+                        app.toggle_peak_table();
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        app.peak_selected = app.peak_selected.saturating_sub(1);
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        if app.peak_selected + 1 < app.peaks.len() {
+                            app.peak_selected += 1;
+                        }
+                        continue;
+                    }
+                    KeyCode::Char('[') => {
+                        app.adjust_peak_prominence(1.0 / 1.5);
+                        continue;
+                    }
+                    KeyCode::Char(']') => {
+                        app.adjust_peak_prominence(1.5);
+                        continue;
+                    }
+                    KeyCode::Char('{') => {
+                        app.adjust_peak_distance(-1);
+                        continue;
+                    }
+                    KeyCode::Char('}') => {
+                        app.adjust_peak_distance(1);
+                        continue;
+                    }
+                    KeyCode::Char('(') => {
+                        app.adjust_peak_threshold(-0.05);
+                        continue;
+                    }
+                    KeyCode::Char(')') => {
+                        app.adjust_peak_threshold(0.05);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Jump list: pick a chain, residue or ligand to scroll to
+            if app.info_panel == InfoPanel::Jump {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('l') => {
+                        app.toggle_jump_list();
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        app.select_jump(app.jump_selected.saturating_sub(1));
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        app.select_jump(app.jump_selected + 1);
+                        continue;
+                    }
+                    KeyCode::Char('[') => {
+                        app.step_jump_chain(-1);
+                        continue;
+                    }
+                    KeyCode::Char(']') => {
+                        app.step_jump_chain(1);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            // Chart focus turns navigation keys into zoom and pan
+            if app.chart_focus && !app.molecule.is_empty() {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Tab => {
+                        app.chart_focus = false;
+                        continue;
+                    }
+                    KeyCode::Left => {
+                        app.rotate_view(-15.0, 0.0);
+                        continue;
+                    }
+                    KeyCode::Right => {
+                        app.rotate_view(15.0, 0.0);
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        app.rotate_view(0.0, -15.0);
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        app.rotate_view(0.0, 15.0);
+                        continue;
+                    }
+                    KeyCode::Char('0') => {
+                        app.view_rotation = (0.0, 0.0);
+                        continue;
+                    }
+                    _ => {}
+                }
+            } else if app.chart_focus && app.show_heatmap {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Tab => {
+                        app.chart_focus = false;
+                        continue;
+                    }
+                    KeyCode::Left => {
+                        app.move_heatmap_cursor(-1, 0);
+                        continue;
+                    }
+                    KeyCode::Right => {
+                        app.move_heatmap_cursor(1, 0);
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        app.move_heatmap_cursor(0, -1);
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        app.move_heatmap_cursor(0, 1);
+                        continue;
                     }
+                    _ => {}
+                }
+            } else if app.chart_focus {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Tab => {
+                        app.chart_focus = false;
+                        continue;
+                    }
+                    KeyCode::Left => {
+                        app.pan_chart(-0.1, 0.0);
+                        continue;
+                    }
+                    KeyCode::Right => {
+                        app.pan_chart(0.1, 0.0);
+                        continue;
+                    }
+                    KeyCode::Up => {
+                        app.pan_chart(0.0, 0.1);
+                        continue;
+                    }
+                    KeyCode::Down => {
+                        app.pan_chart(0.0, -0.1);
+                        continue;
+                    }
+                    KeyCode::Char('+') | KeyCode::Char('=') => {
+                        app.zoom_chart_center(0.8);
+                        continue;
+                    }
+                    KeyCode::Char('-') => {
+                        app.zoom_chart_center(1.25);
+                        continue;
+                    }
+                    KeyCode::Char('0') => {
+                        // Reset to fit the data
+                        app.chart_view = None;
+                        continue;
+                    }
+                    KeyCode::Char('x') => {
+                        app.cycle_x_scale();
+                        continue;
+                    }
+                    KeyCode::Char('y') => {
+                        app.cycle_y_scale();
+                        continue;
+                    }
+                    KeyCode::Char('g') => {
+                        app.cycle_plot_style();
+                        continue;
+                    }
+                    KeyCode::Char('m') => {
+                        app.cycle_marker();
+                        continue;
+                    }
+                    KeyCode::Char('v') => {
+                        app.toggle_chart_cursor();
+                        continue;
+                    }
+                    KeyCode::Char('n') => {
+                        app.toggle_normalize();
+                        continue;
+                    }
+                    KeyCode::Char('F') => {
+                        app.fit_visible_range();
+                        continue;
+                    }
+                    KeyCode::Char('o') => {
+                        app.cycle_fit_profile();
+                        continue;
+                    }
+                    KeyCode::Char('R') => {
+                        app.fit_result = None;
+                        continue;
+                    }
+                    KeyCode::Char('E') => {
+                        app.export_fit();
+                        continue;
+                    }
+                    KeyCode::Char('b') => {
+                        app.cycle_baseline();
+                        continue;
+                    }
+                    KeyCode::Char('B') => {
+                        app.toggle_baseline_subtraction();
+                        continue;
+                    }
+                    KeyCode::Char('z') => {
+                        app.adjust_baseline_setting(-1);
+                        continue;
+                    }
+                    KeyCode::Char('Z') => {
+                        app.adjust_baseline_setting(1);
+                        continue;
+                    }
+                    KeyCode::Char('s') => {
+                        app.cycle_smoothing();
+                        continue;
+                    }
+                    KeyCode::Char('w') => {
+                        app.adjust_smooth_window(-1);
+                        continue;
+                    }
+                    KeyCode::Char('W') => {
+                        app.adjust_smooth_window(1);
+                        continue;
+                    }
+                    KeyCode::Char('t') => {
+                        app.cycle_transform();
+                        continue;
+                    }
+                    KeyCode::Char('i') => {
+                        app.add_integral_limit();
+                        continue;
+                    }
+                    KeyCode::Char('I') => {
+                        app.integral_limits.clear();
+                        continue;
+                    }
+                    KeyCode::Char('S') => {
+                        app.toggle_spectrum_view();
+                        continue;
+                    }
+                    KeyCode::Char('H') => {
+                        app.cycle_spectrum_window();
+                        continue;
+                    }
+                    KeyCode::Char('M') => {
+                        app.toggle_spectrum_power();
+                        continue;
+                    }
+                    KeyCode::Char('u') => {
+                        app.cycle_x_unit();
+                        continue;
+                    }
+                    KeyCode::Char('U') => {
+                        app.cycle_unit_target();
+                        continue;
+                    }
+                    KeyCode::Char('L') => {
+                        app.prompt = Some((PromptKind::Wavelength, String::new()));
+                        continue;
+                    }
+                    KeyCode::Char('X') => {
+                        app.start_chart_export();
+                        continue;
+                    }
+                    KeyCode::Char('D') => {
+                        app.start_data_export();
+                        continue;
+                    }
+                    KeyCode::Char('T') => {
+                        app.start_scripts_export();
+                        continue;
+                    }
+                    KeyCode::Char('Y') => {
+                        app.toggle_unit_jacobian();
+                        continue;
+                    }
+                    KeyCode::Char(',') => {
+                        app.step_chart_cursor(false);
+                        continue;
+                    }
+                    KeyCode::Char('.') => {
+                        app.step_chart_cursor(true);
+                        continue;
+                    }
+                    KeyCode::Char('<') => {
+                        app.jump_chart_cursor(-0.05);
+                        continue;
+                    }
+                    KeyCode::Char('>') => {
+                        app.jump_chart_cursor(0.05);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            match key.code {
+                KeyCode::Char('q') => {
+                    app.save_last_directory();
+                    return Ok(());
+                }
+                KeyCode::Up if !app.entries.is_empty() => {
+                    app.selected_index = app
+                        .selected_index
+                        .checked_sub(1)
+                        .unwrap_or(app.entries.len() - 1);
+                }
+                KeyCode::Down if !app.entries.is_empty() => {
+                    app.selected_index = (app.selected_index + 1) % app.entries.len();
                 }
                 KeyCode::Enter => {
                     app.select_entry();
@@ -532,6 +3184,13 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
                 KeyCode::Char('k') => {
                     app.scroll_offset = app.scroll_offset.saturating_sub(1);
                 }
+                // Stats panel scrolling
+                KeyCode::Char('J') => {
+                    app.stats_scroll = (app.stats_scroll + 1).min(app.stats_max_scroll);
+                }
+                KeyCode::Char('K') => {
+                    app.stats_scroll = app.stats_scroll.saturating_sub(1);
+                }
                 KeyCode::Char('u') | KeyCode::PageUp => {
                     // Page up in content
                     app.scroll_offset = app.scroll_offset.saturating_sub(app.visible_height);
@@ -549,17 +3208,85 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
                     // Go to end of file
                     app.scroll_offset = app.file_content.len().saturating_sub(app.visible_height);
                 }
-                // Directory navigation
-                KeyCode::Char('.') => {
-                    // Return to startup directory
-                    app.go_startup();
+                // Directory navigation
+                KeyCode::Char('.') => {
+                    // Return to startup directory
+                    app.go_startup();
+                }
+                KeyCode::Char('~') => {
+                    // Go to home directory
+                    app.go_home();
+                }
+                KeyCode::Char('c') => {
+                    app.show_chart = !app.show_chart;
+                }
+                KeyCode::Char('p') => {
+                    // Show column picker
+                    app.info_panel = InfoPanel::Columns;
+                }
+                KeyCode::Char('f') => {
+                    // Find peaks and show the peak table
+                    app.toggle_peak_table();
+                }
+                KeyCode::Char('l') => {
+                    // Jump by chain or residue in a PDB file
+                    app.toggle_jump_list();
+                }
+                KeyCode::Char('a') => {
+                    // Pin current chart series for comparison
+                    app.pin_current_series();
+                }
+                KeyCode::Char('P') => {
+                    // Show pinned series popup
+                    app.show_pinned = true;
+                    app.pinned_selected = 0;
+                }
+                KeyCode::Char('G') => {
+                    // Heatmap of matrix or gridded data
+                    app.toggle_heatmap();
+                }
+                KeyCode::Char('C') if app.show_heatmap || app.multi_block() => {
+                    app.colormap = app.colormap.next();
+                }
+                KeyCode::Char('w') if app.multi_block() => {
+                    // Overlay, waterfall or single block
+                    app.cycle_block_view();
+                }
+                KeyCode::Char('[') if app.molecule.len() > 1 => {
+                    app.step_frame(-1);
+                }
+                KeyCode::Char(']') if app.molecule.len() > 1 => {
+                    app.step_frame(1);
+                }
+                KeyCode::Char('[') if app.multi_block() => {
+                    app.step_block(-1);
+                }
+                KeyCode::Char(']') if app.multi_block() => {
+                    app.step_block(1);
+                }
+                KeyCode::Char('H') => {
+                    // Show the plotted primary series as a histogram
+                    app.toggle_histogram();
+                }
+                KeyCode::Char('#') if app.show_histogram => {
+                    app.cycle_bin_rule();
+                }
+                KeyCode::Char('+') | KeyCode::Char('=') if app.show_histogram => {
+                    app.adjust_bins(1);
+                }
+                KeyCode::Char('-') if app.show_histogram => {
+                    app.adjust_bins(-1);
                 }
-                KeyCode::Char('~') => {
-                    // Go to home directory
-                    app.go_home();
+                KeyCode::Char('V') if app.show_histogram => {
+                    app.cycle_histogram_column();
                 }
-                KeyCode::Char('c') => {
-                    app.show_chart = !app.show_chart;
+                KeyCode::Tab
+                    if app.show_chart
+                        && (app.has_chart() || !app.molecule.is_empty())
+                        && !app.show_histogram =>
+                {
+                    // Focus chart for zoom and pan
+                    app.chart_focus = true;
                 }
                 KeyCode::Char('n') => {
                     // Toggle nerd fonts vs emoji
@@ -623,6 +3350,15 @@ fn ui(f: &mut Frame, app: &mut App) {
     if app.show_recent_files {
         render_recent_files_popup(f, app);
     }
+
+    // Render pinned series popup if enabled
+    if app.show_pinned {
+        render_pinned_popup(f, app);
+    }
+
+    if app.show_key_help {
+        render_key_help_popup(f, app);
+    }
 }
 
 fn render_file_tree(f: &mut Frame, app: &mut App, area: Rect) {
@@ -680,7 +3416,7 @@ fn render_file_tree(f: &mut Frame, app: &mut App, area: Rect) {
                             ("🔬 ", Color::Rgb(198, 120, 221))
                         }
                     }
-                    "dat" | "csv" => {
+                    "dat" | "csv" | "jdx" | "dx" | "jcm" => {
                         // nf-fa-table \uf0ce
                         if app.use_nerd_fonts {
                             ("\u{f0ce} ", Color::Rgb(152, 195, 121))
@@ -773,6 +3509,9 @@ fn render_content_viewer(f: &mut Frame, app: &mut App, area: Rect) {
         (total_lines as f64).log10().floor() as usize + 1
     };
 
+    // Source line of the point under the chart crosshair or the jump list entry
+    let highlighted = app.cursor_source_line().or_else(|| app.jump_line());
+
     // Build content lines with line numbers
     let mut lines: Vec<Line> = Vec::with_capacity(visible_height);
     let content_width = area.width.saturating_sub(2) as usize; // minus borders
@@ -797,10 +3536,15 @@ fn render_content_viewer(f: &mut Frame, app: &mut App, area: Rect) {
             let padding_needed = available_width.saturating_sub(display_width);
             let padded_content = format!("{}{}", display_content, " ".repeat(padding_needed));
 
-            lines.push(Line::from(vec![
+            let line = Line::from(vec![
                 Span::styled(prefix, Style::default().fg(Color::Rgb(92, 99, 112))), // Dark gray
                 Span::raw(padded_content),
-            ]));
+            ]);
+            if highlighted == Some(content_idx) {
+                lines.push(line.style(Style::default().bg(Color::Rgb(62, 68, 81)))); // Selection gray
+            } else {
+                lines.push(line);
+            }
         } else {
             // Empty line with just spaces to fill width
             lines.push(Line::from(" ".repeat(content_width)));
@@ -880,7 +3624,7 @@ fn get_scroll_info(app: &App, area: Rect) -> String {
     }
 }
 
-fn render_right_panel(f: &mut Frame, app: &App, area: Rect) {
+fn render_right_panel(f: &mut Frame, app: &mut App, area: Rect) {
     if app.show_chart {
         // Split right panel into chart (top) and stats (bottom)
         let right_chunks = Layout::default()
@@ -892,22 +3636,223 @@ fn render_right_panel(f: &mut Frame, app: &App, area: Rect) {
             .split(area);
 
         // Render chart placeholder
-        render_chart(f, app, right_chunks[0]);
+        if !app.molecule.is_empty() {
+            render_molecule(f, app, right_chunks[0]);
+        } else if app.show_histogram {
+            render_histogram(f, app, right_chunks[0]);
+        } else if app.show_heatmap {
+            render_heatmap(f, app, right_chunks[0]);
+        } else {
+            render_chart(f, app, right_chunks[0]);
+        }
 
-        // Render stats/info
-        render_stats(f, app, right_chunks[1]);
+        // Render stats/info or a tool panel in its place
+        render_info_panel(f, app, right_chunks[1]);
     } else {
-        // Just show stats if chart is hidden
-        render_stats(f, app, area);
+        // Just show the info panel if chart is hidden
+        render_info_panel(f, app, area);
+    }
+}
+
+fn render_info_panel(f: &mut Frame, app: &mut App, area: Rect) {
+    match app.info_panel {
+        InfoPanel::Stats => render_stats(f, app, area),
+        InfoPanel::Columns => render_column_picker(f, app, area),
+        InfoPanel::Peaks => render_peak_table(f, app, area),
+        InfoPanel::Jump => render_jump_list(f, app, area),
+    }
+}
+
+/// Chains with their residues, then ligands, of a PDB file
+fn render_jump_list(f: &mut Frame, app: &App, area: Rect) {
+    // Keep the highlighted entry visible
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let skip = (app.jump_selected + 1).saturating_sub(visible_height);
+
+    let items: Vec<ListItem> = app
+        .jump_targets
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(visible_height)
+        .map(|(i, target)| {
+            let (text, style) = match target.kind {
+                JumpKind::Chain => (
+                    target.label.clone(),
+                    Style::default()
+                        .fg(Color::Rgb(229, 192, 123)) // Yellow
+                        .add_modifier(Modifier::BOLD),
+                ),
+                JumpKind::Residue => (
+                    format!("  {}", target.label),
+                    Style::default().fg(Color::Rgb(171, 178, 191)), // Light gray
+                ),
+                JumpKind::Ligand => (
+                    format!("Ligand {}", target.label),
+                    Style::default().fg(Color::Rgb(152, 195, 121)), // Green
+                ),
+            };
+            let style = if i == app.jump_selected {
+                Style::default()
+                    .fg(Color::Rgb(40, 44, 52)) // Dark background text
+                    .bg(Color::Rgb(97, 175, 239)) // Blue highlight
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                style
+            };
+            ListItem::new(Line::from(Span::styled(
+                format!("{:<5} {}", target.line + 1, text),
+                style,
+            )))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(format!(
+                " Jump to ({}/{}) ",
+                app.jump_selected + 1,
+                app.jump_targets.len()
+            ))
+            .title_bottom(" ↑↓ entry  [ ] chain ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Rgb(152, 195, 121))), // Green
+    );
+
+    f.render_widget(list, area);
+}
+
+fn render_peak_table(f: &mut Frame, app: &App, area: Rect) {
+    let params = &app.peak_params;
+    let title = format!(
+        " Peaks ({}) prom {:.1}% dist {} thr {:.0}% ",
+        app.peaks.len(),
+        params.prominence * 100.0,
+        params.min_distance,
+        params.threshold * 100.0
+    );
+    let block = Block::default()
+        .title(title)
+        .title_bottom(" [ ] prom  { } dist  ( ) thr ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Rgb(224, 108, 117))); // Red
+
+    let header = Row::new(["#", "Position", "Height", "FWHM", "Area"])
+        .style(Style::default().fg(Color::Rgb(229, 192, 123))); // Yellow
+    let rows: Vec<Row> = app
+        .peaks
+        .iter()
+        .enumerate()
+        .map(|(i, peak)| {
+            Row::new(vec![
+                Cell::from(format!("{}", i + 1)),
+                Cell::from(format_axis_value(peak.x)),
+                Cell::from(format_axis_value(peak.height)),
+                Cell::from(format_axis_value(peak.fwhm)),
+                Cell::from(format_axis_value(peak.area)),
+            ])
+            .style(Style::default().fg(Color::Rgb(171, 178, 191))) // Light gray
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header)
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .fg(Color::Rgb(40, 44, 52)) // Dark background text
+            .bg(Color::Rgb(97, 175, 239)), // Blue highlight
+    );
+
+    let mut state = TableState::default().with_selected(Some(app.peak_selected));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_column_picker(f: &mut Frame, app: &App, area: Rect) {
+    let count = app.data_table.column_count();
+    if count == 0 {
+        let message = Paragraph::new(vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                "  No numeric columns",
+                Style::default().fg(Color::Rgb(92, 99, 112)), // Dark gray
+            )),
+        ])
+        .block(
+            Block::default()
+                .title(" Columns ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Rgb(229, 192, 123))), // Yellow
+        );
+        f.render_widget(message, area);
+        return;
     }
+
+    // Keep the highlighted column visible
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let skip = (app.column_picker_selected + 1).saturating_sub(visible_height);
+
+    let items: Vec<ListItem> = (0..count)
+        .skip(skip)
+        .take(visible_height)
+        .map(|col| {
+            let role = if col == app.x_column {
+                Span::styled(" X ", Style::default().fg(Color::Rgb(97, 175, 239)))
+            } else if let Some(pos) = app.y_columns.iter().position(|&c| c == col) {
+                Span::styled(
+                    " Y ",
+                    Style::default().fg(SERIES_COLORS[pos % SERIES_COLORS.len()]),
+                )
+            } else if app.y_error_column == Some(col) {
+                Span::styled("σY ", Style::default().fg(Color::Rgb(92, 99, 112))) // Dark gray
+            } else if app.x_error_column == Some(col) {
+                Span::styled("σX ", Style::default().fg(Color::Rgb(92, 99, 112))) // Dark gray
+            } else {
+                Span::raw("   ")
+            };
+
+            let style = if col == app.column_picker_selected {
+                Style::default()
+                    .fg(Color::Rgb(40, 44, 52)) // Dark background text
+                    .bg(Color::Rgb(97, 175, 239)) // Blue highlight
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Rgb(171, 178, 191)) // Light gray
+            };
+
+            ListItem::new(Line::from(vec![
+                role,
+                Span::styled(app.data_table.column_name(col).to_string(), style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(" Columns (x: X, Space: Y, e/E: σY/σX) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Rgb(229, 192, 123))), // Yellow
+    );
+
+    f.render_widget(list, area);
 }
 
-fn render_chart(f: &mut Frame, app: &App, area: Rect) {
+fn render_chart(f: &mut Frame, app: &mut App, area: Rect) {
     // Clear the chart area first to prevent Braille character artifacts
     f.render_widget(Clear, area);
+    app.chart_plot_area = Rect::default();
 
     // Check if we have chart data
-    if app.chart_data.is_empty() {
+    if !app.has_chart() {
         // Show placeholder when no data
         let placeholder = Paragraph::new(vec![
             Line::from(""),
@@ -928,58 +3873,719 @@ fn render_chart(f: &mut Frame, app: &App, area: Rect) {
     // Inner area is area minus borders (2 chars) minus y-axis labels (~8 chars)
     let chart_width = area.width.saturating_sub(12) as usize;
 
-    // Downsample if we have too many points
+    // Create nice axis labels
+    let (x_bounds, y_bounds) = app.view_bounds();
+    let x_labels = axis_labels(&app.x_transform, x_bounds);
+    let y_labels = axis_labels(&app.y_transform, y_bounds);
+
+    // Remember where the plot lands for mouse mapping
+    let y_label_width = y_labels.iter().map(Span::width).max().unwrap_or(0) as u16;
+    let first_x_label_width = x_labels.first().map(Span::width).unwrap_or(0) as u16;
+    app.chart_plot_area = chart_plot_area(area, y_label_width, first_x_label_width);
+
     // Use 2 * width to allow for min/max preservation per bucket
-    let target_points = (chart_width * 2).max(50);
-    let display_data = App::downsample_with_peaks(&app.chart_data, target_points);
+    let figure = chart_figure(
+        app,
+        (chart_width * 2).max(50),
+        app.chart_plot_area.width as usize,
+    );
+    let datasets: Vec<Dataset> = figure
+        .layers
+        .iter()
+        .map(|layer| {
+            let dataset = Dataset::default()
+                .marker(layer.marker)
+                .graph_type(layer.graph_type)
+                .style(Style::default().fg(layer.color))
+                .data(&layer.points);
+            match &layer.name {
+                Some(name) => dataset.name(name.clone()),
+                None => dataset,
+            }
+        })
+        .collect();
 
-    // Format axis labels
-    let (x_bounds, y_bounds) = app.chart_bounds;
+    let border_color = if app.chart_focus {
+        Color::Rgb(97, 175, 239) // Blue when focused
+    } else {
+        Color::Rgb(198, 120, 221) // Purple
+    };
 
-    // Create nice axis labels
-    let x_labels = vec![
-        format_axis_value(x_bounds[0]).bold(),
-        format_axis_value((x_bounds[0] + x_bounds[1]) / 2.0),
-        format_axis_value(x_bounds[1]).bold(),
-    ];
+    let chart = Chart::new(datasets)
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+        .block(
+            Block::default()
+                .title(figure.title.clone())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color)),
+        )
+        .x_axis(
+            Axis::default()
+                .title(Span::styled(
+                    figure.x_title.clone(),
+                    Style::default().fg(Color::Gray),
+                ))
+                .style(Style::default().fg(Color::Gray))
+                .bounds(x_bounds)
+                .labels(x_labels),
+        )
+        .y_axis(
+            Axis::default()
+                .title(Span::styled(
+                    figure.y_title.clone(),
+                    Style::default().fg(Color::Gray),
+                ))
+                .style(Style::default().fg(Color::Gray))
+                .bounds(y_bounds)
+                .labels(y_labels),
+        );
 
-    let y_labels = vec![
-        format_axis_value(y_bounds[0]).bold(),
-        format_axis_value((y_bounds[0] + y_bounds[1]) / 2.0),
-        format_axis_value(y_bounds[1]).bold(),
-    ];
+    f.render_widget(chart, area);
+
+    // Rubber-band zoom rectangle
+    if let Some((start, end)) = app.chart_drag
+        && start.0 != end.0
+        && start.1 != end.1
+    {
+        let band = Rect::new(
+            start.0.min(end.0),
+            start.1.min(end.1),
+            start.0.abs_diff(end.0) + 1,
+            start.1.abs_diff(end.1) + 1,
+        );
+        f.render_widget(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Rgb(229, 192, 123))), // Yellow
+            band,
+        );
+    }
+}
+
+/// Layers, labels and titles of the line chart in its current view, shared by
+/// the terminal chart and file export; `columns` is the plot width that error
+/// bars are spaced against
+fn chart_figure(app: &App, target_points: usize, columns: usize) -> Figure {
+    // Only the visible X range is downsampled, so detail appears when zoomed in
+    let (x_bounds, y_bounds) = app.view_bounds();
+    let plotted = app.plotted_series();
+    let hidden_points: usize = plotted.iter().map(|series| series.hidden).sum();
+    let line = |name: Option<String>, points: Vec<(f64, f64)>, color: Color| Layer {
+        name,
+        points,
+        marker: Marker::Braille,
+        graph_type: GraphType::Line,
+        color,
+    };
+
+    // Error bars go under the data, as a shaded band when they would crowd
+    let mut layers: Vec<Layer> = Vec::new();
+    let (error_segments, error_band) = app.error_bar_segments((x_bounds, y_bounds), columns);
+    let error_name = app
+        .y_error_column
+        .or(app.x_error_column)
+        .map(|column| format!("± {}", app.data_table.column_name(column)));
+    for (i, segment) in error_segments.into_iter().enumerate() {
+        let name = if i == 0 { error_name.clone() } else { None };
+        layers.push(if error_band {
+            Layer {
+                marker: Marker::HalfBlock,
+                ..line(name, segment.to_vec(), Color::Rgb(62, 68, 81)) // Gutter gray
+            }
+        } else {
+            line(name, segment.to_vec(), Color::Rgb(92, 99, 112)) // Dark gray
+        });
+    }
+
+    // One layer per selected Y column and pinned series
+    let style = app.chart_style;
+    for series in &plotted {
+        let visible = visible_points(&series.points, x_bounds);
+        let data = App::downsample_with_peaks(&visible, target_points);
+        let graph_type = match style.plot {
+            PlotStyle::Scatter => GraphType::Scatter,
+            PlotStyle::Line | PlotStyle::LinePoints => GraphType::Line,
+        };
+        layers.push(Layer {
+            name: (!series.name.is_empty()).then(|| series.name.clone()),
+            points: data.clone(),
+            marker: style.marker,
+            graph_type,
+            color: series.color,
+        });
+        if style.plot == PlotStyle::LinePoints {
+            // Unnamed point overlay, kept out of the legend
+            layers.push(Layer {
+                name: None,
+                points: data,
+                marker: style.point_marker(),
+                graph_type: GraphType::Scatter,
+                color: series.color,
+            });
+        }
+    }
+
+    // Overlays on the primary series share its normalization
+    let primary_factor = app.series_factor(&app.chart_data);
+
+    // Estimated baseline under the raw primary series
+    if let Some(method) = app.baseline_method
+        && !app.subtract_baseline
+        && app.primary_in_data_units()
+    {
+        let mut curve: Vec<(f64, f64)> = app
+            .chart_data
+            .iter()
+            .zip(&app.baseline)
+            .map(|(&(x, _), &b)| (x, b))
+            .collect();
+        curve.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let curve = app.plot_points(&curve, primary_factor);
+        let curve = App::downsample_with_peaks(&visible_points(&curve, x_bounds), target_points);
+        if !curve.is_empty() {
+            layers.push(line(
+                Some(format!("{} baseline", method.name())),
+                curve,
+                Color::Rgb(86, 182, 194), // Cyan
+            ));
+        }
+    }
+
+    // Smoothed primary series over the unsmoothed points
+    if let Some(method) = app.smooth_method
+        && !app.unsmoothed_data.is_empty()
+    {
+        let mut curve = app.plot_points(&app.chart_data, primary_factor);
+        curve.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let curve = App::downsample_with_peaks(&visible_points(&curve, x_bounds), target_points);
+        layers.push(line(
+            Some(format!("{} smoothed", method.name())),
+            curve,
+            Color::White,
+        ));
+    }
+
+    // Fitted model across its X range
+    if let Some(result) = &app.fit_result {
+        layers.push(line(
+            Some(format!("{} fit", result.profile.name())),
+            app.plot_points(&result.curve(200), primary_factor),
+            Color::Rgb(209, 154, 102), // Orange
+        ));
+    }
+
+    // Peak markers, with the selected row of the peak table highlighted
+    if !app.peaks.is_empty() {
+        let peak_points: Vec<(f64, f64)> = app.peaks.iter().map(|p| (p.x, p.height)).collect();
+        layers.push(Layer {
+            name: Some("peaks".to_string()),
+            points: app.plot_points(&peak_points, primary_factor),
+            marker: Marker::Dot,
+            graph_type: GraphType::Scatter,
+            color: Color::Rgb(224, 108, 117), // Red
+        });
+        layers.push(Layer {
+            name: None,
+            points: app
+                .peaks
+                .get(app.peak_selected)
+                .map(|p| app.plot_points(&[(p.x, p.height)], primary_factor))
+                .unwrap_or_default(),
+            marker: Marker::Block,
+            graph_type: GraphType::Scatter,
+            color: Color::White,
+        });
+    }
+
+    // Integration limits as vertical lines
+    for x in app
+        .integral_limits
+        .iter()
+        .filter_map(|&x| app.x_transform.forward(x))
+    {
+        layers.push(line(
+            None,
+            vec![(x, y_bounds[0]), (x, y_bounds[1])],
+            Color::Rgb(152, 195, 121), // Green
+        ));
+    }
 
-    // Create dataset
-    let datasets = vec![
-        Dataset::default()
-            .name(format!("{} pts", app.chart_data.len()))
-            .marker(Marker::Braille)
-            .graph_type(GraphType::Scatter)
-            .style(Style::default().fg(Color::Rgb(86, 182, 194))) // Cyan
-            .data(&display_data),
+    // Vertical crosshair through the snapped point
+    let cursor_point = app
+        .chart_cursor
+        .and_then(|i| app.chart_data.get(i))
+        .copied();
+    let cursor_plot = cursor_point.and_then(|(x, y)| {
+        Some((
+            app.x_transform.forward(x)?,
+            app.y_transform.forward(y * primary_factor),
+        ))
+    });
+    if let Some((x, y)) = cursor_plot {
+        let cursor_color = Color::Rgb(229, 192, 123); // Yellow
+        layers.push(line(
+            None,
+            vec![(x, y_bounds[0]), (x, y_bounds[1])],
+            cursor_color,
+        ));
+        layers.push(Layer {
+            name: None,
+            points: y.map(|y| (x, y)).into_iter().collect(),
+            marker: Marker::Block,
+            graph_type: GraphType::Scatter,
+            color: cursor_color,
+        });
+    }
+
+    // Axis titles from the column names, tagged with non-linear scales
+    let scale_tag = |transform: &AxisTransform| match transform.scale {
+        AxisScale::Linear => String::new(),
+        scale => format!(" ({})", scale.label()),
+    };
+    let x_title = format!("{}{}", app.x_axis_name(), scale_tag(&app.x_transform));
+    let y_title = format!(
+        "{}{}",
+        (0..app.shown_series_count())
+            .map(|i| app.series_name(i))
+            .collect::<Vec<_>>()
+            .join(", "),
+        scale_tag(&app.y_transform)
+    );
+
+    let mut title = format!(" {} ", style.plot.title());
+    if app.chart_view.is_some() {
+        title.push_str("(zoomed) ");
+    }
+    if app.normalize_series {
+        title.push_str("(normalized) ");
+    }
+    if app.subtract_baseline {
+        title.push_str("(baseline subtracted) ");
+    }
+    if app.spectrum_view {
+        title.push_str(&format!("({} spectrum) ", app.spectrum_window.name()));
+    }
+    if let Some(conversion) = app.unit_conversion() {
+        title.push_str(&format!(
+            "({} → {}) ",
+            conversion.from.symbol(),
+            conversion.to.symbol()
+        ));
+    }
+    if app.multi_block() {
+        title.push_str(&format!(
+            "(block {}/{}, {}) ",
+            app.current_block + 1,
+            app.data_table.blocks.len(),
+            app.block_view.name()
+        ));
+    }
+    if hidden_points > 0 {
+        // Non-positive values have no place on a log axis
+        title.push_str(&format!("({} pts ≤0 hidden) ", hidden_points));
+    }
+    if let Some((x, y)) = cursor_point {
+        title.push_str(&format!(
+            "x={} y={} ",
+            format_axis_value(x).content,
+            format_axis_value(y).content
+        ));
+    }
+
+    let labels = |transform: &AxisTransform, bounds: [f64; 2]| {
+        axis_labels(transform, bounds)
+            .into_iter()
+            .map(|label| label.content.into_owned())
+            .collect()
+    };
+    Figure {
+        title,
+        x_bounds,
+        y_bounds,
+        x_labels: labels(&app.x_transform, x_bounds),
+        y_labels: labels(&app.y_transform, y_bounds),
+        x_title,
+        y_title,
+        layers,
+    }
+}
+
+/// Gridded values as half-block pixels, two per cell, with a colour scale legend
+fn render_heatmap(f: &mut Frame, app: &mut App, area: Rect) {
+    f.render_widget(Clear, area);
+    app.chart_plot_area = Rect::default();
+    app.heatmap_area = Rect::default();
+    let Some(grid) = &app.grid else {
+        return;
+    };
+    let colormap = app.colormap;
+    let (width, height) = (grid.width(), grid.height());
+    let cursor = app
+        .heatmap_cursor
+        .map(|(col, row)| (col, row, grid.values[row][col]));
+
+    let kind = match grid.kind {
+        GridKind::Matrix => "matrix",
+        GridKind::Xyz => "x/y/z grid",
+    };
+    let mut title = format!(
+        " Heatmap: {}×{} {} ({}) ",
+        width,
+        height,
+        kind,
+        colormap.name()
+    );
+    if let Some((col, row, value)) = cursor {
+        title.push_str(&format!(
+            "x={} y={} z={} ",
+            format_axis_value(grid.x[col]).content,
+            format_axis_value(grid.y[row]).content,
+            value
+        ));
+    }
+    let border_color = if app.chart_focus {
+        Color::Rgb(97, 175, 239) // Blue when focused
+    } else {
+        Color::Rgb(198, 120, 221) // Purple
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border_color));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // Y labels on the left, X labels underneath and the legend on the right
+    let label_style = Style::default().fg(Color::Gray);
+    let y_labels = [
+        format_axis_value(grid.y[0]),
+        format_axis_value(grid.y[height - 1]),
     ];
+    let legend_labels = [
+        format_axis_value(grid.max),
+        format_axis_value((grid.min + grid.max) / 2.0),
+        format_axis_value(grid.min),
+    ];
+    let label_width = y_labels.iter().map(Span::width).max().unwrap_or(0) as u16;
+    let legend_width = 3 + legend_labels.iter().map(Span::width).max().unwrap_or(0) as u16;
+    if inner.width < label_width + legend_width + 6 || inner.height < 3 {
+        return;
+    }
+    let plot = Rect::new(
+        inner.x + label_width + 1,
+        inner.y,
+        inner.width - label_width - legend_width - 2,
+        inner.height - 1,
+    );
+    app.heatmap_area = plot;
 
-    let chart = Chart::new(datasets)
+    let (plot_width, plot_height) = (plot.width as usize, plot.height as usize);
+    let pixel = |px: usize, py: usize| {
+        let value = grid.values[py * height / (2 * plot_height)][px * width / plot_width];
+        if value.is_finite() {
+            colormap.color(grid.normalized(value))
+        } else {
+            Color::Reset
+        }
+    };
+    // The cursor sits on the screen cell over its grid cell's centre
+    let cursor_cell = cursor.map(|(col, row, value)| {
+        let sx = ((col as f64 + 0.5) * plot_width as f64 / width as f64) as usize;
+        let sy = ((row as f64 + 0.5) * plot_height as f64 / height as f64) as usize;
+        (sx, sy, grid.normalized(value))
+    });
+    let lines: Vec<Line> = (0..plot_height)
+        .map(|sy| {
+            let spans: Vec<Span> = (0..plot_width)
+                .map(|sx| {
+                    let (top, bottom) = (pixel(sx, 2 * sy), pixel(sx, 2 * sy + 1));
+                    match cursor_cell {
+                        // Marked in the colour from the far end of the scale
+                        Some((cx, cy, t)) if (cx, cy) == (sx, sy) => {
+                            Span::styled("┼", Style::default().fg(colormap.color(1.0 - t)).bg(top))
+                        }
+                        _ => Span::styled("▀", Style::default().fg(top).bg(bottom)),
+                    }
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), plot);
+
+    let [y_top, y_bottom] = y_labels;
+    f.render_widget(
+        Paragraph::new(y_top.style(label_style)).alignment(Alignment::Right),
+        Rect::new(inner.x, plot.y, label_width, 1),
+    );
+    f.render_widget(
+        Paragraph::new(y_bottom.style(label_style)).alignment(Alignment::Right),
+        Rect::new(inner.x, plot.bottom() - 1, label_width, 1),
+    );
+    let x_row = Rect::new(plot.x, plot.bottom(), plot.width, 1);
+    f.render_widget(
+        Paragraph::new(format_axis_value(grid.x[0]).style(label_style)),
+        x_row,
+    );
+    f.render_widget(
+        Paragraph::new(format_axis_value(grid.x[width - 1]).style(label_style))
+            .alignment(Alignment::Right),
+        x_row,
+    );
+
+    // Colour bar from the maximum at the top to the minimum at the bottom
+    let bar_x = plot.right() + 1;
+    let steps = (2 * plot_height - 1).max(1) as f64;
+    let bar: Vec<Line> = (0..plot_height)
+        .map(|sy| {
+            let top = colormap.color(1.0 - (2 * sy) as f64 / steps);
+            let bottom = colormap.color(1.0 - (2 * sy + 1) as f64 / steps);
+            Line::from(Span::styled("▀▀", Style::default().fg(top).bg(bottom)))
+        })
+        .collect();
+    f.render_widget(
+        Paragraph::new(bar),
+        Rect::new(bar_x, plot.y, 2, plot.height),
+    );
+    let [max_label, mid_label, min_label] = legend_labels;
+    let label_rect = |y: u16| Rect::new(bar_x + 3, y, legend_width - 3, 1);
+    f.render_widget(
+        Paragraph::new(max_label.style(label_style)),
+        label_rect(plot.y),
+    );
+    if plot.height >= 5 {
+        f.render_widget(
+            Paragraph::new(mid_label.style(label_style)),
+            label_rect(plot.y + plot.height / 2),
+        );
+    }
+    f.render_widget(
+        Paragraph::new(min_label.style(label_style)),
+        label_rect(plot.bottom() - 1),
+    );
+}
+
+/// Atoms of the current XYZ frame in element colours, rotated and projected
+/// flat, with bonds between close neighbours
+fn render_molecule(f: &mut Frame, app: &mut App, area: Rect) {
+    f.render_widget(Clear, area);
+    app.chart_plot_area = Rect::default();
+    app.heatmap_area = Rect::default();
+    let Some(structure) = app.molecule.get(app.molecule_frame) else {
+        return;
+    };
+    let (yaw, pitch) = app.view_rotation;
+    let mut parts = vec![format!(
+        "Structure: {}, {} atoms",
+        structure.summary().formula,
+        structure.atoms.len()
+    )];
+    if app.molecule.len() > 1 {
+        parts.push(format!(
+            "frame {}/{}",
+            app.molecule_frame + 1,
+            app.molecule.len()
+        ));
+    }
+    parts.push(format!(
+        "yaw {:.0}° pitch {:.0}°",
+        yaw.to_degrees(),
+        pitch.to_degrees()
+    ));
+    let title = format!(" {} ", parts.join(" · "));
+    let border_color = if app.chart_focus {
+        Color::Rgb(97, 175, 239) // Blue when focused
+    } else {
+        Color::Rgb(198, 120, 221) // Purple
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border_color));
+    let inner = block.inner(area);
+
+    // Bounds from the rotation-independent extent, widened along the longer
+    // side since cells are about twice as tall as they are wide
+    let (points, radius) = structure.project(yaw, pitch);
+    let radius = radius.max(0.5) * 1.1;
+    let aspect = inner.width as f64 / (2.0 * inner.height.max(1) as f64);
+    let (x_half, y_half) = if aspect >= 1.0 {
+        (radius * aspect, radius)
+    } else {
+        (radius, radius / aspect)
+    };
+    let bonds = structure.bonds(2000);
+    // Far atoms first so nearer labels are drawn over them
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| points[a][2].total_cmp(&points[b][2]));
+
+    let canvas = Canvas::default()
+        .block(block)
+        .marker(Marker::Braille)
+        .x_bounds([-x_half, x_half])
+        .y_bounds([-y_half, y_half])
+        .paint(|ctx| {
+            for &(i, j) in &bonds {
+                ctx.draw(&CanvasLine {
+                    x1: points[i][0],
+                    y1: points[i][1],
+                    x2: points[j][0],
+                    y2: points[j][1],
+                    color: Color::Rgb(92, 99, 112), // Dark gray
+                });
+            }
+            for &i in &order {
+                let atom = &structure.atoms[i];
+                let (r, g, b) =
+                    molecule::element(&atom.symbol).map_or(molecule::UNKNOWN_COLOR, |e| e.color);
+                ctx.print(
+                    points[i][0],
+                    points[i][1],
+                    Span::styled(
+                        atom.symbol.clone(),
+                        Style::default()
+                            .fg(Color::Rgb(r, g, b))
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                );
+            }
+        });
+    f.render_widget(canvas, area);
+}
+
+/// Histogram of the plotted primary series or a raw column as solid bars
+fn render_histogram(f: &mut Frame, app: &mut App, area: Rect) {
+    f.render_widget(Clear, area);
+    app.chart_plot_area = Rect::default();
+    let border_style = Style::default().fg(Color::Rgb(198, 120, 221)); // Purple
+
+    let Some(hist) = &app.histogram else {
+        let placeholder = Paragraph::new(vec![Line::from(""), Line::from("  No values to bin.")])
+            .block(
+                Block::default()
+                    .title(" Histogram ")
+                    .borders(Borders::ALL)
+                    .border_style(border_style),
+            );
+        f.render_widget(placeholder, area);
+        return;
+    };
+
+    let x_bounds = [hist.start, hist.end()];
+    let max_count = hist.counts.iter().copied().max().unwrap_or(0);
+    let y_bounds = [0.0, (max_count as f64 * 1.05).max(1.0)];
+    let linear = AxisTransform::default();
+    let x_labels = axis_labels(&linear, x_bounds);
+    let y_labels = axis_labels(&linear, y_bounds);
+
+    // One bar column per terminal cell, leaving a gap where bins are wide enough
+    let y_label_width = y_labels.iter().map(Span::width).max().unwrap_or(0) as u16;
+    let first_x_label_width = x_labels.first().map(Span::width).unwrap_or(0) as u16;
+    let width = chart_plot_area(area, y_label_width, first_x_label_width).width as usize;
+    // The canvas maps the X range onto width - 1 cells
+    let bins = hist.counts.len();
+    let cell = (x_bounds[1] - x_bounds[0]) / width.saturating_sub(1).max(1) as f64;
+    let gaps = bins * 3 <= width;
+    let bars: Vec<(f64, f64)> = (0..width.saturating_sub(1))
+        .filter_map(|c| {
+            let left = (c as f64 * cell) / hist.bin_width;
+            let right = ((c + 1) as f64 * cell) / hist.bin_width;
+            let on_edge = left.ceil().max(1.0) < right;
+            let bin = (((left + right) / 2.0) as usize).min(bins - 1);
+            let x = x_bounds[0] + (c as f64 + 0.5) * cell;
+            (!(gaps && on_edge)).then(|| (x, hist.counts[bin] as f64))
+        })
+        .collect();
+
+    let dataset = Dataset::default()
+        .marker(Marker::HalfBlock)
+        .graph_type(GraphType::Bar)
+        .style(Style::default().fg(SERIES_COLORS[0]))
+        .data(&bars);
+    let (column, scope) = app.histogram_source();
+    let title = format!(
+        " Histogram of {} ({}; {} bins, {}) ",
+        column,
+        scope,
+        bins,
+        hist.rule.name()
+    );
+
+    let chart = Chart::new(vec![dataset])
         .block(
             Block::default()
-                .title(" Scatter Plot ")
+                .title(title)
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Rgb(198, 120, 221))), // Purple
+                .border_style(border_style),
         )
         .x_axis(
             Axis::default()
+                .title(Span::styled(column, Style::default().fg(Color::Gray)))
                 .style(Style::default().fg(Color::Gray))
                 .bounds(x_bounds)
                 .labels(x_labels),
         )
         .y_axis(
             Axis::default()
+                .title(Span::styled("count", Style::default().fg(Color::Gray)))
                 .style(Style::default().fg(Color::Gray))
                 .bounds(y_bounds)
                 .labels(y_labels),
         );
+    f.render_widget(chart, area);
+}
+
+/// Axis labels at the transform's tick positions, with bold end labels
+fn axis_labels(transform: &AxisTransform, bounds: [f64; 2]) -> Vec<Span<'static>> {
+    let ticks = transform.ticks(bounds);
+    let last = ticks.len() - 1;
+    ticks
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let Some(value) = value else {
+                return Span::raw("");
+            };
+            let label = format_axis_value(value);
+            if i == 0 || i == last {
+                label.bold()
+            } else {
+                label
+            }
+        })
+        .collect()
+}
+
+/// Plot area inside a bordered `Chart`, mirroring ratatui's axis layout
+fn chart_plot_area(area: Rect, y_label_width: u16, first_x_label_width: u16) -> Rect {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    // Y labels (or the overhang of the first X label) sit left of the axis line
+    let left = y_label_width
+        .max(first_x_label_width.saturating_sub(1))
+        .min(inner.width / 3)
+        + 1;
+    // X labels and the axis line take the bottom two rows
+    Rect::new(
+        inner.x + left,
+        inner.y,
+        inner.width.saturating_sub(left),
+        inner.height.saturating_sub(2),
+    )
+}
 
-    f.render_widget(chart, area);
+/// Points inside an X range, plus their immediate neighbours so lines reach the edges
+fn visible_points(points: &[(f64, f64)], x_range: [f64; 2]) -> Vec<(f64, f64)> {
+    let inside = |i: usize| {
+        points
+            .get(i)
+            .is_some_and(|&(x, _)| x >= x_range[0] && x <= x_range[1])
+    };
+    points
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| inside(i) || inside(i + 1) || (i > 0 && inside(i - 1)))
+        .map(|(_, &p)| p)
+        .collect()
 }
 
 /// Format a numeric value for axis labels (compact representation)
@@ -999,7 +4605,40 @@ fn format_axis_value(val: f64) -> Span<'static> {
     Span::raw(formatted)
 }
 
-fn render_stats(f: &mut Frame, app: &App, area: Rect) {
+/// Value rounded to `digits` significant digits, without trailing zeros
+fn format_significant(val: f64, digits: i32) -> String {
+    if val == 0.0 || !val.is_finite() {
+        return val.to_string();
+    }
+    let magnitude = val.abs().log10().floor() as i32;
+    if !(-4..=9).contains(&magnitude) {
+        return format!("{:.*e}", (digits - 1).max(0) as usize, val);
+    }
+    let decimals = (digits - 1 - magnitude).max(0) as usize;
+    let formatted = format!("{:.*}", decimals, val);
+    if formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    }
+}
+
+/// Value ± uncertainty, rounded to the uncertainty's second significant digit
+fn format_estimate(estimate: Estimate) -> String {
+    if !estimate.error.is_finite() || estimate.error <= 0.0 {
+        return format!("{}", format_axis_value(estimate.value).content);
+    }
+    let decimals = (1 - estimate.error.log10().floor() as i32).max(0) as usize;
+    format!(
+        "{:.*} ± {:.*}",
+        decimals, estimate.value, decimals, estimate.error
+    )
+}
+
+fn render_stats(f: &mut Frame, app: &mut App, area: Rect) {
     // Build stats lines from file_stats (opened file)
     let mut stats_lines: Vec<Line> = vec![
         // Line::from(""),
@@ -1010,6 +4649,418 @@ fn render_stats(f: &mut Frame, app: &App, area: Rect) {
         //Line::from(""),
     ];
 
+    // Crosshair readout with full precision, ahead of the file info
+    if let Some(&(x, y)) = app.chart_cursor.and_then(|i| app.chart_data.get(i)) {
+        let cursor_style = Style::default().fg(Color::Rgb(229, 192, 123)); // Yellow
+        let line = app.cursor_source_line().map(|l| l + 1).unwrap_or(0);
+        stats_lines.push(Line::from(Span::styled(
+            format!("Cursor (line {}):", line),
+            cursor_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!("  x = {}", x),
+            cursor_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!("  y = {}", y),
+            cursor_style,
+        )));
+    }
+
+    // Declared X unit and any conversion
+    if let Some(from) = app.x_unit {
+        let unit_style = Style::default().fg(Color::Rgb(97, 175, 239)); // Blue
+        let text = match app.unit_conversion() {
+            Some(conversion) if app.unit_jacobian => format!(
+                "Units: {} → {}, Y × {}",
+                from.symbol(),
+                conversion.to.symbol(),
+                conversion.jacobian_label()
+            ),
+            Some(conversion) => format!(
+                "Units: {} → {}, Y not rescaled",
+                from.symbol(),
+                conversion.to.symbol()
+            ),
+            None => format!("Units: X in {}", from.symbol()),
+        };
+        stats_lines.push(Line::from(Span::styled(text, unit_style)));
+        if from.needs_wavelength() {
+            stats_lines.push(Line::from(Span::styled(
+                format!("  X-ray λ = {} Å", app.xray_wavelength),
+                unit_style,
+            )));
+        }
+    }
+
+    // Metadata records of a JCAMP-DX spectrum
+    if !app.jcamp_labels.is_empty() {
+        let jcamp_style = Style::default().fg(Color::Rgb(198, 120, 221)); // Purple
+        stats_lines.push(Line::from(Span::styled("JCAMP-DX:", jcamp_style)));
+        for (label, value) in &app.jcamp_labels {
+            stats_lines.push(Line::from(Span::styled(
+                format!("  {}: {}", label, value),
+                jcamp_style,
+            )));
+        }
+    }
+
+    // Composition and geometry of the XYZ frame on show
+    if let Some(structure) = app.molecule.get(app.molecule_frame) {
+        let summary = structure.summary();
+        let structure_style = Style::default().fg(Color::Rgb(198, 120, 221)); // Purple
+        let mut lines = vec![
+            if app.molecule.len() > 1 {
+                format!(
+                    "Structure (frame {} of {}):",
+                    app.molecule_frame + 1,
+                    app.molecule.len()
+                )
+            } else {
+                "Structure:".to_string()
+            },
+            format!("  Formula: {}", summary.formula),
+            format!(
+                "  Atoms: {} ({})",
+                structure.atoms.len(),
+                summary
+                    .counts
+                    .iter()
+                    .map(|(symbol, n)| format!("{} {}", symbol, n))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ];
+        if !structure.comment.is_empty() {
+            lines.push(format!("  Comment: {}", structure.comment));
+        }
+        lines.push("  Bounding box (Å):".to_string());
+        for (axis, name) in ["x", "y", "z"].iter().enumerate() {
+            lines.push(format!(
+                "    {}: {:.3} to {:.3} ({:.3})",
+                name,
+                summary.min[axis],
+                summary.max[axis],
+                summary.max[axis] - summary.min[axis]
+            ));
+        }
+        if let Some([x, y, z]) = summary.centre_of_mass {
+            lines.push(format!("  Centre of mass: ({:.3}, {:.3}, {:.3})", x, y, z));
+        }
+        if summary.unknown_mass > 0 {
+            lines.push(format!(
+                "  {} atoms of unknown mass left out of the centre",
+                summary.unknown_mass
+            ));
+        }
+        stats_lines.extend(
+            lines
+                .into_iter()
+                .map(|line| Line::from(Span::styled(line, structure_style))),
+        );
+    }
+
+    // Chains, ligands and crystal data of a PDB file
+    if let Some(pdb) = &app.pdb {
+        let pdb_style = Style::default().fg(Color::Rgb(198, 120, 221)); // Purple
+        let mut lines = vec![match (&pdb.id, &pdb.classification) {
+            (Some(id), Some(class)) => format!("PDB {} ({}):", id, class),
+            (Some(id), None) => format!("PDB {}:", id),
+            (None, Some(class)) => format!("PDB structure ({}):", class),
+            (None, None) => "PDB structure:".to_string(),
+        }];
+        if let Some(title) = &pdb.title {
+            lines.push(format!("  Title: {}", title));
+        }
+        if let Some(resolution) = &pdb.resolution {
+            lines.push(format!("  Resolution: {}", resolution));
+        }
+        if let Some(cell) = &pdb.cell {
+            let [a, b, c] = cell.lengths;
+            let [alpha, beta, gamma] = cell.angles;
+            lines.push(format!("  Cell: a {:.3} b {:.3} c {:.3} Å", a, b, c));
+            lines.push(format!(
+                "        α {:.2} β {:.2} γ {:.2}°",
+                alpha, beta, gamma
+            ));
+            if !cell.space_group.is_empty() {
+                lines.push(format!("  Space group: {}", cell.space_group));
+            }
+        }
+        if pdb.models > 1 {
+            lines.push(format!("  Models: {} (first summarised)", pdb.models));
+        }
+        lines.push(format!("  Chains: {}", pdb.chains.len()));
+        for chain in &pdb.chains {
+            lines.push(format!(
+                "    {}: {} residues, {} atoms",
+                chain.id,
+                chain.residues.len(),
+                chain.atoms
+            ));
+        }
+        lines.push(format!("  Ligands: {}", pdb.ligands.len()));
+        for ligand in &pdb.ligands {
+            lines.push(format!(
+                "    {} {} {} ({} atoms)",
+                ligand.name, ligand.chain, ligand.number, ligand.atoms
+            ));
+        }
+        if pdb.waters > 0 {
+            lines.push(format!("  Waters: {}", pdb.waters));
+        }
+        let helices = pdb.segments.iter().filter(|s| s.kind == "Helix").count();
+        lines.push(format!(
+            "  Secondary structure: {} helices, {} strands",
+            helices,
+            pdb.segments.len() - helices
+        ));
+        for segment in &pdb.segments {
+            lines.push(format!(
+                "    {} {}: {} – {}",
+                segment.kind, segment.id, segment.start, segment.end
+            ));
+        }
+        stats_lines.extend(
+            lines
+                .into_iter()
+                .map(|line| Line::from(Span::styled(line, pdb_style))),
+        );
+    }
+
+    // Current block of a multi-block file
+    if let Some(block) = app
+        .data_table
+        .blocks
+        .get(app.current_block)
+        .filter(|_| app.multi_block())
+    {
+        let block_style = Style::default().fg(Color::Rgb(86, 182, 194)); // Cyan
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "Block {} of {} ({} view, {} colours)",
+                app.current_block + 1,
+                app.data_table.blocks.len(),
+                app.block_view.name(),
+                app.colormap.name()
+            ),
+            block_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "  lines {}–{}{}",
+                app.data_table.source_lines[block.rows.start] + 1,
+                app.data_table.source_lines[block.rows.end - 1] + 1,
+                block
+                    .title
+                    .as_ref()
+                    .map(|t| format!(": {}", t))
+                    .unwrap_or_default()
+            ),
+            block_style,
+        )));
+    }
+
+    // Grid summary and the value under the heatmap cursor
+    if let Some(grid) = app.grid.as_ref().filter(|_| app.show_heatmap) {
+        let map_style = Style::default().fg(Color::Rgb(198, 120, 221)); // Purple
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "Heatmap: {}×{}, z {} … {}",
+                grid.width(),
+                grid.height(),
+                format_significant(grid.min, 5),
+                format_significant(grid.max, 5)
+            ),
+            map_style,
+        )));
+        if let Some((col, row)) = app.heatmap_cursor {
+            stats_lines.push(Line::from(Span::styled(
+                format!(
+                    "  cursor x = {}  y = {}  z = {}",
+                    grid.x[col], grid.y[row], grid.values[row][col]
+                ),
+                map_style,
+            )));
+        }
+    }
+
+    // Fitted parameters with standard uncertainties
+    if let Some(result) = &app.fit_result {
+        let fit_style = Style::default().fg(Color::Rgb(209, 154, 102)); // Orange
+        let value_style = Style::default().fg(Color::Rgb(171, 178, 191)); // Light gray
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "{} fit, {} peak(s), R² = {:.5}",
+                result.profile.name(),
+                result.peaks.len(),
+                result.r_squared
+            ),
+            fit_style,
+        )));
+        if result.weighted {
+            stats_lines.push(Line::from(Span::styled(
+                format!(
+                    "  weighted by 1/σ², χ²ᵣ = {}",
+                    format_significant(result.reduced_chi2, 4)
+                ),
+                value_style,
+            )));
+        }
+        for (i, peak) in result.peaks.iter().enumerate() {
+            stats_lines.push(Line::from(Span::styled(
+                format!("  #{} centre {}", i + 1, format_estimate(peak.center)),
+                fit_style,
+            )));
+            stats_lines.push(Line::from(Span::styled(
+                format!(
+                    "     height {}  FWHM {}",
+                    format_estimate(peak.height),
+                    format_estimate(peak.fwhm)
+                ),
+                value_style,
+            )));
+            let mut details = format!("     area {}", format_estimate(peak.area));
+            if let Some(eta) = peak.eta {
+                details.push_str(&format!("  η {}", format_estimate(eta)));
+            }
+            stats_lines.push(Line::from(Span::styled(details, value_style)));
+        }
+    }
+
+    // Distribution summary of the histogram column
+    if let Some(hist) = &app.histogram {
+        let hist_style = Style::default().fg(SERIES_COLORS[0]);
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "Histogram: {} bins of {} ({})",
+                hist.counts.len(),
+                format_significant(hist.bin_width, 4),
+                hist.rule.name()
+            ),
+            hist_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "  n {}  mean {}  std {}",
+                hist.count,
+                format_significant(hist.mean, 5),
+                format_significant(hist.std_dev, 5)
+            ),
+            hist_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "  skewness {}  excess kurtosis {}",
+                format_significant(hist.skewness, 4),
+                format_significant(hist.kurtosis, 4)
+            ),
+            hist_style,
+        )));
+        if let Some((mode, count)) = hist
+            .counts
+            .iter()
+            .enumerate()
+            .max_by_key(|&(i, c)| (c, std::cmp::Reverse(i)))
+        {
+            stats_lines.push(Line::from(Span::styled(
+                format!(
+                    "  fullest bin at {} ({} values)",
+                    format_significant(hist.bin_center(mode), 5),
+                    count
+                ),
+                hist_style,
+            )));
+        }
+    }
+
+    if let Some(method) = app.baseline_method {
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "Baseline: {}{}",
+                app.baseline_description(method),
+                if app.subtract_baseline {
+                    " (subtracted)"
+                } else {
+                    ""
+                }
+            ),
+            Style::default().fg(Color::Rgb(86, 182, 194)), // Cyan
+        )));
+    }
+
+    if let Some(method) = app.smooth_method {
+        stats_lines.push(Line::from(Span::styled(
+            format!("Smoothing: {}, {} pts", method.name(), app.smooth_window),
+            Style::default().fg(Color::Rgb(152, 195, 121)), // Green
+        )));
+    }
+    if let Some(transform) = app.transform {
+        stats_lines.push(Line::from(Span::styled(
+            format!("Transform: {}", transform.name()),
+            Style::default().fg(Color::Rgb(229, 192, 123)), // Yellow
+        )));
+    }
+
+    if let Some(spectrum) = &app.spectrum {
+        let spectrum_style = Style::default().fg(Color::Rgb(97, 175, 239)); // Blue
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "Spectrum: {} window, {} samples (FFT {})",
+                app.spectrum_window.name(),
+                spectrum.samples,
+                spectrum.fft_len
+            ),
+            spectrum_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "  step {}, Nyquist {}",
+                format_significant(spectrum.sample_step, 6),
+                format_significant(0.5 / spectrum.sample_step, 6)
+            ),
+            spectrum_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            "Dominant frequencies:",
+            spectrum_style,
+        )));
+        for &(frequency, value) in &spectrum.dominant {
+            stats_lines.push(Line::from(Span::styled(
+                format!(
+                    "  f = {:<10} {}",
+                    format_significant(frequency, 4),
+                    format_significant(value, 4)
+                ),
+                Style::default().fg(Color::Rgb(171, 178, 191)), // Light gray
+            )));
+        }
+    }
+
+    // Integral between the picked limits
+    let limit_style = Style::default().fg(Color::Rgb(152, 195, 121)); // Green
+    match app.integral_limits[..] {
+        [a] => stats_lines.push(Line::from(Span::styled(
+            format!("Integral from x = {}: pick the second limit with i", a),
+            limit_style,
+        ))),
+        [a, b] => {
+            stats_lines.push(Line::from(Span::styled(
+                format!("Integral x = {} … {}:", a.min(b), a.max(b)),
+                limit_style,
+            )));
+            let value = app
+                .limits_integral()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "outside the data".to_string());
+            stats_lines.push(Line::from(Span::styled(
+                format!("  {}", value),
+                limit_style,
+            )));
+        }
+        _ => {}
+    }
+
     for line in app.file_stats.lines() {
         stats_lines.push(Line::from(Span::styled(
             line.to_string(),
@@ -1017,17 +5068,154 @@ fn render_stats(f: &mut Frame, app: &App, area: Rect) {
         )));
     }
 
-    let stats = Paragraph::new(stats_lines).block(
-        Block::default()
-            .title(" Info & Stats ")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Rgb(229, 192, 123))), // Yellow
-    );
+    // Plotted column names
+    if !app.chart_series.is_empty() {
+        let label_style = Style::default().fg(Color::Rgb(92, 99, 112)); // Dark gray
+        stats_lines.push(Line::from(vec![
+            Span::styled("X: ", label_style),
+            Span::styled(
+                app.data_table.column_name(app.x_column).to_string(),
+                Style::default().fg(Color::Rgb(97, 175, 239)), // Blue
+            ),
+        ]));
+        let mut y_spans = vec![Span::styled("Y: ", label_style)];
+        for (i, series) in app.chart_series.iter().enumerate() {
+            if i > 0 {
+                y_spans.push(Span::styled(", ", label_style));
+            }
+            y_spans.push(Span::styled(
+                app.data_table.column_name(series.column).to_string(),
+                Style::default().fg(SERIES_COLORS[i % SERIES_COLORS.len()]),
+            ));
+        }
+        stats_lines.push(Line::from(y_spans));
+        let error_style = Style::default().fg(Color::Rgb(92, 99, 112)); // Dark gray
+        for (label, column) in [("σY: ", app.y_error_column), ("σX: ", app.x_error_column)] {
+            if let Some(column) = column {
+                stats_lines.push(Line::from(vec![
+                    Span::styled(label, label_style),
+                    Span::styled(app.data_table.column_name(column).to_string(), error_style),
+                ]));
+            }
+        }
+        let values: Vec<f64> = app.chart_data.iter().map(|p| p.1).collect();
+        if let Some(weighted) = app
+            .chart_errors()
+            .and_then(|errors| stats::weighted_mean(&values, errors))
+        {
+            let value_style = Style::default().fg(Color::Rgb(171, 178, 191)); // Light gray
+            stats_lines.push(Line::from(Span::styled(
+                format!(
+                    "Weighted Y mean {} ± {}",
+                    format_significant(weighted.mean, 6),
+                    format_significant(weighted.error, 3)
+                ),
+                value_style,
+            )));
+            stats_lines.push(Line::from(Span::styled(
+                format!(
+                    "  {} pts, χ²ᵣ about mean {}",
+                    weighted.count,
+                    format_significant(weighted.reduced_chi2, 4)
+                ),
+                value_style,
+            )));
+        }
+    }
+    if !app.pinned_series.is_empty() {
+        stats_lines.push(Line::from(Span::styled(
+            format!("Pinned: {} series", app.pinned_series.len()),
+            Style::default().fg(Color::Rgb(198, 120, 221)), // Purple
+        )));
+    }
+
+    // Per-column statistics
+    if let Some(spacing) = app.column_stats.get(app.x_column).and_then(|s| s.spacing) {
+        let spacing_text = match spacing {
+            Spacing::Uniform(step) => format!("uniform, step {}", format_significant(step, 6)),
+            Spacing::Varying { min, max } => format!(
+                "non-uniform, step {} … {}",
+                format_significant(min, 4),
+                format_significant(max, 4)
+            ),
+        };
+        stats_lines.push(Line::from(Span::styled(
+            format!("X spacing: {}", spacing_text),
+            Style::default().fg(Color::Rgb(97, 175, 239)), // Blue
+        )));
+    }
+    let value_style = Style::default().fg(Color::Rgb(171, 178, 191)); // Light gray
+    for (col, stats) in app.column_stats.iter().enumerate() {
+        let value = |v: f64| format_significant(v, 6);
+        stats_lines.push(Line::from(Span::styled(
+            app.data_table.column_name(col).to_string(),
+            Style::default()
+                .fg(Color::Rgb(229, 192, 123)) // Yellow
+                .add_modifier(Modifier::BOLD),
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "  count {}  NaN {}  unparsed {}",
+                stats.count, stats.nan, stats.unparsed
+            ),
+            value_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!("  min {}  max {}", value(stats.min), value(stats.max)),
+            value_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!(
+                "  mean {}  median {}",
+                value(stats.mean),
+                value(stats.median)
+            ),
+            value_style,
+        )));
+        stats_lines.push(Line::from(Span::styled(
+            format!("  std {}  sum {}", value(stats.std_dev), value(stats.sum)),
+            value_style,
+        )));
+    }
+
+    // Scroll within the panel, keeping the last line at the bottom
+    let inner_height = area.height.saturating_sub(2) as usize;
+    app.stats_max_scroll = stats_lines.len().saturating_sub(inner_height) as u16;
+    app.stats_scroll = app.stats_scroll.min(app.stats_max_scroll);
+    let mut title = " Info & Stats ".to_string();
+    if app.stats_max_scroll > 0 {
+        title = format!(
+            " Info & Stats [{}/{}] ",
+            app.stats_scroll, app.stats_max_scroll
+        );
+    }
+
+    let stats = Paragraph::new(stats_lines)
+        .scroll((app.stats_scroll, 0))
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Rgb(229, 192, 123))), // Yellow
+        );
 
     f.render_widget(stats, area);
 }
 
 fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    if app.chart_focus && !app.molecule.is_empty() {
+        render_molecule_status_bar(f, area);
+        return;
+    }
+    if app.chart_focus && app.show_heatmap {
+        render_heatmap_status_bar(f, area);
+        return;
+    }
+    if app.chart_focus {
+        render_chart_status_bar(f, area);
+        return;
+    }
+
     let nerd = app.use_nerd_fonts;
 
     // Create shortcut hints with Atom colors
@@ -1072,6 +5260,14 @@ fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
         ),
         Span::styled(" Page ", Style::default().fg(Color::Rgb(171, 178, 191))),
         Span::raw(" "),
+        Span::styled(
+            " J/K ",
+            Style::default()
+                .fg(Color::Rgb(40, 44, 52))
+                .bg(Color::Rgb(229, 192, 123)),
+        ),
+        Span::styled(" Stats ", Style::default().fg(Color::Rgb(171, 178, 191))),
+        Span::raw(" "),
         Span::styled(
             " c ",
             Style::default()
@@ -1099,6 +5295,14 @@ fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(Color::Rgb(171, 178, 191)),
         ),
         Span::raw(" "),
+        Span::styled(
+            " ? ",
+            Style::default()
+                .fg(Color::Rgb(40, 44, 52))
+                .bg(Color::Rgb(229, 192, 123)),
+        ),
+        Span::styled(" Keys ", Style::default().fg(Color::Rgb(171, 178, 191))),
+        Span::raw(" "),
         Span::styled(
             " q ",
             Style::default()
@@ -1114,6 +5318,69 @@ fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(status, area);
 }
 
+/// Status bar shortcuts while the chart has focus
+fn render_chart_status_bar(f: &mut Frame, area: Rect) {
+    // The rest of the chart keys are in the `?` list
+    let hints = [
+        (" ←→↑↓ ", " Pan ", Color::Rgb(97, 175, 239)),
+        (" +/- Wheel ", " Zoom ", Color::Rgb(152, 195, 121)),
+        (" Drag ", " Box ", Color::Rgb(229, 192, 123)),
+        (" 0 ", " Reset ", Color::Rgb(86, 182, 194)),
+        (" x/y ", " Scale ", Color::Rgb(209, 154, 102)),
+        (" F ", " Fit ", Color::Rgb(224, 108, 117)),
+        (" X/D/T ", " Export ", Color::Rgb(198, 120, 221)),
+        (" ? ", " Keys ", Color::Rgb(229, 192, 123)),
+        (" Tab ", " Done ", Color::Rgb(198, 120, 221)),
+    ];
+    let shortcuts: Vec<Span> = hints.iter().flat_map(key_hint).collect();
+
+    let status =
+        Paragraph::new(Line::from(shortcuts)).style(Style::default().bg(Color::Rgb(33, 37, 43)));
+
+    f.render_widget(status, area);
+}
+
+/// Status bar shortcuts while the heatmap has focus
+fn render_heatmap_status_bar(f: &mut Frame, area: Rect) {
+    let hints = [
+        (" ←→↑↓ Click ", " Cursor ", Color::Rgb(97, 175, 239)),
+        (" C ", " Colormap ", Color::Rgb(152, 195, 121)),
+        (" G ", " Line chart ", Color::Rgb(229, 192, 123)),
+        (" Tab ", " Done ", Color::Rgb(198, 120, 221)),
+    ];
+    let shortcuts: Vec<Span> = hints.iter().flat_map(key_hint).collect();
+
+    let status =
+        Paragraph::new(Line::from(shortcuts)).style(Style::default().bg(Color::Rgb(33, 37, 43)));
+
+    f.render_widget(status, area);
+}
+
+/// Status bar shortcuts while the structure preview has focus
+fn render_molecule_status_bar(f: &mut Frame, area: Rect) {
+    let hints = [
+        (" ←→↑↓ ", " Rotate ", Color::Rgb(97, 175, 239)),
+        (" 0 ", " Reset ", Color::Rgb(229, 192, 123)),
+        (" [ ] ", " Frame ", Color::Rgb(152, 195, 121)),
+        (" Tab ", " Done ", Color::Rgb(198, 120, 221)),
+    ];
+    let shortcuts: Vec<Span> = hints.iter().flat_map(key_hint).collect();
+
+    let status =
+        Paragraph::new(Line::from(shortcuts)).style(Style::default().bg(Color::Rgb(33, 37, 43)));
+
+    f.render_widget(status, area);
+}
+
+/// Key badge, label and spacer for a status bar hint
+fn key_hint(&(key, label, color): &(&'static str, &'static str, Color)) -> [Span<'static>; 3] {
+    [
+        Span::styled(key, Style::default().fg(Color::Rgb(40, 44, 52)).bg(color)),
+        Span::styled(label, Style::default().fg(Color::Rgb(171, 178, 191))),
+        Span::raw(" "),
+    ]
+}
+
 fn render_path_bar(f: &mut Frame, app: &App, area: Rect) {
     let path_text = if let Some(ref file_path) = app.current_file {
         format!(" {}", file_path.display())
@@ -1121,7 +5388,21 @@ fn render_path_bar(f: &mut Frame, app: &App, area: Rect) {
         format!(" {}", app.current_directory.display())
     };
 
-    let path_bar = Paragraph::new(path_text).style(
+    let mut spans = vec![Span::raw(path_text)];
+    if let Some((kind, ref input)) = app.prompt {
+        spans.push(Span::styled(
+            format!("  {}: {}█", kind.label(), input),
+            Style::default().fg(Color::Rgb(97, 175, 239)), // Blue
+        ));
+    }
+    if let Some(ref message) = app.status_message {
+        spans.push(Span::styled(
+            format!("  {}", message),
+            Style::default().fg(Color::Rgb(229, 192, 123)), // Yellow
+        ));
+    }
+
+    let path_bar = Paragraph::new(Line::from(spans)).style(
         Style::default()
             .fg(Color::Rgb(171, 178, 191)) // Light gray text
             .bg(Color::Rgb(40, 44, 52)),
@@ -1130,25 +5411,136 @@ fn render_path_bar(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(path_bar, area);
 }
 
+/// Every key, grouped by where it applies; an empty key starts a group.
+/// Keys that mean something else in chart focus say so.
+const KEY_HELP: &[(&str, &str)] = &[
+    ("", "Chart focus (Tab, then Tab or Esc to leave)"),
+    ("←→↑↓", "Pan"),
+    ("+ - Wheel", "Zoom around the centre or the mouse"),
+    ("Drag", "Zoom to a box"),
+    ("0", "Reset the view"),
+    ("x y", "Cycle the X or Y scale: linear, log10, symlog"),
+    ("g m", "Cycle the plot style or the marker"),
+    ("n", "Scale every series to a peak of 1"),
+    ("v", "Show the cursor"),
+    (", .", "Step the cursor by one point"),
+    ("< >", "Move the cursor by 5% of the view"),
+    ("b B", "Cycle the baseline method, subtract it"),
+    ("z Z", "Baseline polynomial degree or radius"),
+    ("s", "Cycle the smoothing method"),
+    ("w W", "Smoothing window"),
+    ("t", "Cycle derivative and running integral"),
+    ("i I", "Add an integral limit, clear them"),
+    ("F", "Fit the peaks in view"),
+    ("o", "Cycle the fit profile"),
+    ("R", "Remove the fit"),
+    ("E", "Save the fit report"),
+    ("S", "Show the amplitude spectrum"),
+    ("H", "Cycle the spectrum window"),
+    ("M", "Switch amplitude and power"),
+    ("u U", "X unit of the file, unit to show"),
+    ("L", "Set the X-ray wavelength"),
+    ("Y", "Rescale Y by the unit Jacobian"),
+    ("X", "Export the chart as SVG and PNG"),
+    ("D", "Export the processed data"),
+    ("T", "Write gnuplot, matplotlib and Origin files"),
+    ("", "Heatmap and structure focus"),
+    ("←→↑↓", "Move the heatmap cursor, rotate the structure"),
+    ("0", "Reset the rotation"),
+    ("", "Browser"),
+    ("↑↓ Enter Bksp", "Select, open, parent directory"),
+    ("Tab", "Focus the chart, heatmap or structure"),
+    ("j/k u/d", "Scroll the file, by line or page"),
+    ("J/K", "Scroll the stats panel"),
+    (". ~ r", "Startup directory, home, refresh"),
+    ("c", "Show or hide the chart"),
+    ("p", "Columns: x sets X, Space toggles Y, e/E error columns"),
+    (
+        "f",
+        "Peak table: [ ] prominence, { } distance, ( ) threshold",
+    ),
+    ("l", "Jump to a chain, residue or ligand of a PDB file"),
+    ("H", "Histogram: # bin rule, +/- bins, V column"),
+    ("G C", "Heatmap, colormap"),
+    ("w [ ]", "Overlay or waterfall blocks, step through them"),
+    ("[ ]", "Step through the frames of a trajectory"),
+    ("a P", "Pin the chart series, list pinned series"),
+    ("h", "Recent files"),
+    ("n", "Nerd font or emoji icons"),
+    ("?", "This list"),
+    ("q", "Quit"),
+];
+
+fn render_key_help_popup(f: &mut Frame, app: &App) {
+    let area = f.area();
+
+    // Centered, 60% width, up to the height of the whole list
+    let popup_width = (area.width as f32 * 0.6).clamp(40.0, 72.0) as u16;
+    let popup_height = (KEY_HELP.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let popup_area = Rect::new(
+        area.width.saturating_sub(popup_width) / 2,
+        area.height.saturating_sub(popup_height) / 2,
+        popup_width.min(area.width),
+        popup_height,
+    );
+    f.render_widget(Clear, popup_area);
+
+    let key_width = KEY_HELP
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = KEY_HELP
+        .iter()
+        .skip(app.key_help_scroll)
+        .map(|&(key, text)| {
+            if key.is_empty() {
+                Line::from(Span::styled(
+                    text,
+                    Style::default()
+                        .fg(Color::Rgb(229, 192, 123)) // Yellow
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {:<width$} ", key, width = key_width),
+                        Style::default().fg(Color::Rgb(97, 175, 239)), // Blue
+                    ),
+                    Span::styled(text, Style::default().fg(Color::Rgb(171, 178, 191))),
+                ])
+            }
+        })
+        .collect();
+
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Keys (↑↓ scroll, Esc close) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Rgb(198, 120, 221))), // Purple
+    );
+    f.render_widget(help, popup_area);
+}
+
 fn render_recent_files_popup(f: &mut Frame, app: &App) {
     let area = f.area();
-    
+
     // Calculate popup size (centered, 50% width, up to 14 lines height)
-    let popup_width = (area.width as f32 * 0.5).min(60.0).max(30.0) as u16;
+    let popup_width = (area.width as f32 * 0.5).clamp(30.0, 60.0) as u16;
     let popup_height = if app.recent_files.is_empty() {
         5 // Minimum height for "no history" message
     } else {
         (app.recent_files.len() as u16 + 4).min(14)
     };
-    
+
     let popup_x = (area.width - popup_width) / 2;
     let popup_y = (area.height - popup_height) / 2;
-    
+
     let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
-    
+
     // Clear the popup area
     f.render_widget(Clear, popup_area);
-    
+
     if app.recent_files.is_empty() {
         // Show "no history" message
         let message = Paragraph::new(vec![
@@ -1167,7 +5559,7 @@ fn render_recent_files_popup(f: &mut Frame, app: &App) {
         f.render_widget(message, popup_area);
         return;
     }
-    
+
     // Create list items showing only the file name (end part of path)
     let items: Vec<ListItem> = app
         .recent_files
@@ -1179,7 +5571,7 @@ fn render_recent_files_popup(f: &mut Frame, app: &App) {
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("Unknown");
-            
+
             let style = if i == app.recent_files_selected {
                 Style::default()
                     .fg(Color::Rgb(40, 44, 52)) // Dark background text
@@ -1188,17 +5580,86 @@ fn render_recent_files_popup(f: &mut Frame, app: &App) {
             } else {
                 Style::default().fg(Color::Rgb(171, 178, 191)) // Light gray
             };
-            
+
             ListItem::new(Line::from(Span::styled(display_name.to_string(), style)))
         })
         .collect();
-    
+
     let list = List::new(items).block(
         Block::default()
             .title(" Recent Files ")
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Rgb(198, 120, 221))), // Purple
     );
-    
+
+    f.render_widget(list, popup_area);
+}
+
+fn render_pinned_popup(f: &mut Frame, app: &App) {
+    let area = f.area();
+
+    // Calculate popup size (centered, 50% width, up to 14 lines height)
+    let popup_width = (area.width as f32 * 0.5).clamp(30.0, 60.0) as u16;
+    let popup_height = if app.pinned_series.is_empty() {
+        5 // Minimum height for "nothing pinned" message
+    } else {
+        (app.pinned_series.len() as u16 + 4).min(14)
+    };
+
+    let popup_x = (area.width - popup_width) / 2;
+    let popup_y = (area.height - popup_height) / 2;
+
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    // Clear the popup area
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Pinned Series (d: unpin) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Rgb(198, 120, 221))); // Purple
+
+    if app.pinned_series.is_empty() {
+        let message = Paragraph::new(vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                "  Nothing pinned (press a)",
+                Style::default().fg(Color::Rgb(92, 99, 112)), // Dark gray
+            )),
+        ])
+        .block(block);
+        f.render_widget(message, popup_area);
+        return;
+    }
+
+    // Pinned series follow the current file's series in the chart palette
+    let offset = app.shown_series_count();
+    let items: Vec<ListItem> = app
+        .pinned_series
+        .iter()
+        .enumerate()
+        .map(|(i, series)| {
+            let style = if i == app.pinned_selected {
+                Style::default()
+                    .fg(Color::Rgb(40, 44, 52)) // Dark background text
+                    .bg(Color::Rgb(97, 175, 239)) // Blue highlight
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Rgb(171, 178, 191)) // Light gray
+            };
+            let swatch = Span::styled(
+                "■ ",
+                Style::default().fg(SERIES_COLORS[(offset + i) % SERIES_COLORS.len()]),
+            );
+
+            ListItem::new(Line::from(vec![
+                swatch,
+                Span::styled(series.label.clone(), style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(block);
+
     f.render_widget(list, popup_area);
 }
@@ -0,0 +1,393 @@
+//! XYZ molecular coordinates: parsing, composition and a rotatable projection
+
+/// Element data: symbol, atomic number, standard atomic weight, covalent
+/// radius in Å and Jmol colour
+pub struct Element {
+    pub symbol: &'static str,
+    pub number: u32,
+    pub mass: f64,
+    pub radius: f64,
+    pub color: (u8, u8, u8),
+}
+
+const fn el(symbol: &'static str, number: u32, mass: f64, radius: f64, color: u32) -> Element {
+    Element {
+        symbol,
+        number,
+        mass,
+        radius,
+        color: ((color >> 16) as u8, (color >> 8) as u8, color as u8),
+    }
+}
+
+/// Elements 1–118; radii past Cm are the 1.5 Å used for unknown atoms,
+/// colours past Mt repeat Mt's, and unstable elements take the mass number of
+/// their longest-lived isotope
+const ELEMENTS: [Element; 118] = [
+    el("H", 1, 1.008, 0.31, 0xFFFFFF),
+    el("He", 2, 4.003, 0.28, 0xD9FFFF),
+    el("Li", 3, 6.94, 1.28, 0xCC80FF),
+    el("Be", 4, 9.012, 0.96, 0xC2FF00),
+    el("B", 5, 10.81, 0.84, 0xFFB5B5),
+    el("C", 6, 12.011, 0.76, 0x909090),
+    el("N", 7, 14.007, 0.71, 0x3050F8),
+    el("O", 8, 15.999, 0.66, 0xFF0D0D),
+    el("F", 9, 18.998, 0.57, 0x90E050),
+    el("Ne", 10, 20.180, 0.58, 0xB3E3F5),
+    el("Na", 11, 22.990, 1.66, 0xAB5CF2),
+    el("Mg", 12, 24.305, 1.41, 0x8AFF00),
+    el("Al", 13, 26.982, 1.21, 0xBFA6A6),
+    el("Si", 14, 28.085, 1.11, 0xF0C8A0),
+    el("P", 15, 30.974, 1.07, 0xFF8000),
+    el("S", 16, 32.06, 1.05, 0xFFFF30),
+    el("Cl", 17, 35.45, 1.02, 0x1FF01F),
+    el("Ar", 18, 39.948, 1.06, 0x80D1E3),
+    el("K", 19, 39.098, 2.03, 0x8F40D4),
+    el("Ca", 20, 40.078, 1.76, 0x3DFF00),
+    el("Sc", 21, 44.956, 1.70, 0xE6E6E6),
+    el("Ti", 22, 47.867, 1.60, 0xBFC2C7),
+    el("V", 23, 50.942, 1.53, 0xA6A6AB),
+    el("Cr", 24, 51.996, 1.39, 0x8A99C7),
+    el("Mn", 25, 54.938, 1.39, 0x9C7AC7),
+    el("Fe", 26, 55.845, 1.32, 0xE06633),
+    el("Co", 27, 58.933, 1.26, 0xF090A0),
+    el("Ni", 28, 58.693, 1.24, 0x50D050),
+    el("Cu", 29, 63.546, 1.32, 0xC88033),
+    el("Zn", 30, 65.38, 1.22, 0x7D80B0),
+    el("Ga", 31, 69.723, 1.22, 0xC28F8F),
+    el("Ge", 32, 72.630, 1.20, 0x668F8F),
+    el("As", 33, 74.922, 1.19, 0xBD80E3),
+    el("Se", 34, 78.971, 1.20, 0xFFA100),
+    el("Br", 35, 79.904, 1.20, 0xA62929),
+    el("Kr", 36, 83.798, 1.16, 0x5CB8D1),
+    el("Rb", 37, 85.468, 2.20, 0x702EB0),
+    el("Sr", 38, 87.62, 1.95, 0x00FF00),
+    el("Y", 39, 88.906, 1.90, 0x94FFFF),
+    el("Zr", 40, 91.224, 1.75, 0x94E0E0),
+    el("Nb", 41, 92.906, 1.64, 0x73C2C9),
+    el("Mo", 42, 95.95, 1.54, 0x54B5B5),
+    el("Tc", 43, 98.0, 1.47, 0x3B9E9E),
+    el("Ru", 44, 101.07, 1.46, 0x248F8F),
+    el("Rh", 45, 102.906, 1.42, 0x0A7D8C),
+    el("Pd", 46, 106.42, 1.39, 0x006985),
+    el("Ag", 47, 107.868, 1.45, 0xC0C0C0),
+    el("Cd", 48, 112.414, 1.44, 0xFFD98F),
+    el("In", 49, 114.818, 1.42, 0xA67573),
+    el("Sn", 50, 118.71, 1.39, 0x668080),
+    el("Sb", 51, 121.760, 1.39, 0x9E63B5),
+    el("Te", 52, 127.60, 1.38, 0xD47A00),
+    el("I", 53, 126.904, 1.39, 0x940094),
+    el("Xe", 54, 131.293, 1.40, 0x429EB0),
+    el("Cs", 55, 132.905, 2.44, 0x57178F),
+    el("Ba", 56, 137.327, 2.15, 0x00C900),
+    el("La", 57, 138.905, 2.07, 0x70D4FF),
+    el("Ce", 58, 140.116, 2.04, 0xFFFFC7),
+    el("Pr", 59, 140.908, 2.03, 0xD9FFC7),
+    el("Nd", 60, 144.242, 2.01, 0xC7FFC7),
+    el("Pm", 61, 145.0, 1.99, 0xA3FFC7),
+    el("Sm", 62, 150.36, 1.98, 0x8FFFC7),
+    el("Eu", 63, 151.964, 1.98, 0x61FFC7),
+    el("Gd", 64, 157.25, 1.96, 0x45FFC7),
+    el("Tb", 65, 158.925, 1.94, 0x30FFC7),
+    el("Dy", 66, 162.500, 1.92, 0x1FFFC7),
+    el("Ho", 67, 164.930, 1.92, 0x00FF9C),
+    el("Er", 68, 167.259, 1.89, 0x00E675),
+    el("Tm", 69, 168.934, 1.90, 0x00D452),
+    el("Yb", 70, 173.045, 1.87, 0x00BF38),
+    el("Lu", 71, 174.967, 1.87, 0x00AB24),
+    el("Hf", 72, 178.49, 1.75, 0x4DC2FF),
+    el("Ta", 73, 180.948, 1.70, 0x4DA6FF),
+    el("W", 74, 183.84, 1.62, 0x2194D6),
+    el("Re", 75, 186.207, 1.51, 0x267DAB),
+    el("Os", 76, 190.23, 1.44, 0x266696),
+    el("Ir", 77, 192.217, 1.41, 0x175487),
+    el("Pt", 78, 195.084, 1.36, 0xD0D0E0),
+    el("Au", 79, 196.967, 1.36, 0xFFD123),
+    el("Hg", 80, 200.592, 1.32, 0xB8B8D0),
+    el("Tl", 81, 204.38, 1.45, 0xA6544D),
+    el("Pb", 82, 207.2, 1.46, 0x575961),
+    el("Bi", 83, 208.980, 1.48, 0x9E4FB5),
+    el("Po", 84, 209.0, 1.40, 0xAB5C00),
+    el("At", 85, 210.0, 1.50, 0x754F45),
+    el("Rn", 86, 222.0, 1.50, 0x428296),
+    el("Fr", 87, 223.0, 2.60, 0x420066),
+    el("Ra", 88, 226.0, 2.21, 0x007D00),
+    el("Ac", 89, 227.0, 2.15, 0x70ABFA),
+    el("Th", 90, 232.038, 2.06, 0x00BAFF),
+    el("Pa", 91, 231.036, 2.00, 0x00A1FF),
+    el("U", 92, 238.029, 1.96, 0x008FFF),
+    el("Np", 93, 237.0, 1.90, 0x0080FF),
+    el("Pu", 94, 244.0, 1.87, 0x006BFF),
+    el("Am", 95, 243.0, 1.80, 0x545CF2),
+    el("Cm", 96, 247.0, 1.69, 0x785CE3),
+    el("Bk", 97, 247.0, 1.50, 0x8A4FE3),
+    el("Cf", 98, 251.0, 1.50, 0xA136D4),
+    el("Es", 99, 252.0, 1.50, 0xB31FD4),
+    el("Fm", 100, 257.0, 1.50, 0xB31FBA),
+    el("Md", 101, 258.0, 1.50, 0xB30DA6),
+    el("No", 102, 259.0, 1.50, 0xBD0D87),
+    el("Lr", 103, 262.0, 1.50, 0xC70066),
+    el("Rf", 104, 267.0, 1.50, 0xCC0059),
+    el("Db", 105, 268.0, 1.50, 0xD1004F),
+    el("Sg", 106, 269.0, 1.50, 0xD90045),
+    el("Bh", 107, 270.0, 1.50, 0xE00038),
+    el("Hs", 108, 269.0, 1.50, 0xE6002E),
+    el("Mt", 109, 278.0, 1.50, 0xEB0026),
+    el("Ds", 110, 281.0, 1.50, 0xEB0026),
+    el("Rg", 111, 282.0, 1.50, 0xEB0026),
+    el("Cn", 112, 285.0, 1.50, 0xEB0026),
+    el("Nh", 113, 286.0, 1.50, 0xEB0026),
+    el("Fl", 114, 289.0, 1.50, 0xEB0026),
+    el("Mc", 115, 290.0, 1.50, 0xEB0026),
+    el("Lv", 116, 293.0, 1.50, 0xEB0026),
+    el("Ts", 117, 294.0, 1.50, 0xEB0026),
+    el("Og", 118, 294.0, 1.50, 0xEB0026),
+];
+
+/// Colour of elements missing from the table
+pub const UNKNOWN_COLOR: (u8, u8, u8) = (255, 20, 147);
+
+pub fn element(symbol: &str) -> Option<&'static Element> {
+    ELEMENTS.iter().find(|e| e.symbol == symbol)
+}
+
+/// Element symbol from an atom name such as `C12`, `CL` or an atomic number
+fn element_symbol(name: &str) -> String {
+    if let Ok(number) = name.parse::<u32>() {
+        return ELEMENTS
+            .iter()
+            .find(|e| e.number == number)
+            .map_or_else(|| name.to_string(), |e| e.symbol.to_string());
+    }
+    let letters: String = name.chars().take_while(char::is_ascii_alphabetic).collect();
+    let mut chars = letters.chars();
+    let symbol: String = chars
+        .next()
+        .map(|c| c.to_ascii_uppercase())
+        .into_iter()
+        .chain(chars.take(1).map(|c| c.to_ascii_lowercase()))
+        .collect();
+    // Labels such as `HW1` fall back to one letter when two are no element;
+    // a bare symbol stays as written, to count as unknown
+    let labelled = name[letters.len()..].starts_with(|c: char| !c.is_ascii_alphabetic());
+    if labelled && symbol.len() == 2 && element(&symbol).is_none() {
+        symbol[..1].to_string()
+    } else {
+        symbol
+    }
+}
+
+pub struct Atom {
+    pub symbol: String,
+    pub position: [f64; 3],
+}
+
+/// One frame of an XYZ file
+pub struct Structure {
+    pub comment: String,
+    pub atoms: Vec<Atom>,
+    /// Zero-based line of the atom count that starts the frame
+    pub line: usize,
+}
+
+/// Parse every frame of an XYZ file: an atom count line, a comment line and
+/// one `symbol x y z` line per atom
+///
+/// Returns `None` when not even the first frame is well formed.
+pub fn parse_xyz(content: &str) -> Option<Vec<Structure>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut frames = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].trim().is_empty() {
+            i += 1;
+            continue;
+        }
+        let Some(frame) = parse_frame(&lines, i) else {
+            break;
+        };
+        i += frame.atoms.len() + 2;
+        frames.push(frame);
+    }
+    (!frames.is_empty()).then_some(frames)
+}
+
+fn parse_frame(lines: &[&str], start: usize) -> Option<Structure> {
+    let count: usize = lines[start].split_whitespace().next()?.parse().ok()?;
+    let comment = lines.get(start + 1)?.trim().to_string();
+    let atom_lines = lines.get(start + 2..start + 2 + count)?;
+    let atoms = atom_lines
+        .iter()
+        .map(|line| {
+            let mut fields = line.split_whitespace();
+            let symbol = element_symbol(fields.next()?);
+            let mut position = [0.0; 3];
+            for value in &mut position {
+                *value = fields.next()?.parse().ok()?;
+            }
+            Some(Atom { symbol, position })
+        })
+        .collect::<Option<Vec<Atom>>>()?;
+    (count > 0).then_some(Structure {
+        comment,
+        atoms,
+        line: start,
+    })
+}
+
+/// Composition and geometry of a structure
+pub struct Summary {
+    /// Hill formula, carbon and hydrogen first when there is carbon
+    pub formula: String,
+    /// Atoms per element in formula order
+    pub counts: Vec<(String, usize)>,
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+    /// Mass-weighted centre, leaving out atoms of unknown mass
+    pub centre_of_mass: Option<[f64; 3]>,
+    pub unknown_mass: usize,
+}
+
+impl Structure {
+    pub fn summary(&self) -> Summary {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for atom in &self.atoms {
+            match counts.iter_mut().find(|(s, _)| *s == atom.symbol) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((atom.symbol.clone(), 1)),
+            }
+        }
+        let has_carbon = counts.iter().any(|(s, _)| s == "C");
+        let rank = |symbol: &str| match symbol {
+            "C" if has_carbon => 0,
+            "H" if has_carbon => 1,
+            _ => 2,
+        };
+        counts.sort_by(|(a, _), (b, _)| rank(a).cmp(&rank(b)).then(a.cmp(b)));
+        let formula = counts
+            .iter()
+            .map(|(symbol, n)| match n {
+                1 => symbol.clone(),
+                n => format!("{}{}", symbol, n),
+            })
+            .collect();
+
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        let mut weighted = [0.0; 3];
+        let mut total_mass = 0.0;
+        let mut unknown_mass = 0;
+        for atom in &self.atoms {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(atom.position[axis]);
+                max[axis] = max[axis].max(atom.position[axis]);
+            }
+            match element(&atom.symbol) {
+                Some(element) => {
+                    for (sum, value) in weighted.iter_mut().zip(atom.position) {
+                        *sum += element.mass * value;
+                    }
+                    total_mass += element.mass;
+                }
+                None => unknown_mass += 1,
+            }
+        }
+        Summary {
+            formula,
+            counts,
+            min,
+            max,
+            centre_of_mass: (total_mass > 0.0).then(|| weighted.map(|w| w / total_mass)),
+            unknown_mass,
+        }
+    }
+
+    /// Pairs of atoms closer than their covalent radii allow, for up to
+    /// `limit` atoms
+    pub fn bonds(&self, limit: usize) -> Vec<(usize, usize)> {
+        if self.atoms.len() > limit {
+            return Vec::new();
+        }
+        let radii: Vec<f64> = self
+            .atoms
+            .iter()
+            .map(|a| element(&a.symbol).map_or(1.5, |e| e.radius))
+            .collect();
+        let mut bonds = Vec::new();
+        for (i, a) in self.atoms.iter().enumerate() {
+            for (j, b) in self.atoms.iter().enumerate().skip(i + 1) {
+                let reach = radii[i] + radii[j] + 0.4;
+                let d2: f64 = (0..3)
+                    .map(|k| (a.position[k] - b.position[k]).powi(2))
+                    .sum();
+                if d2 > 0.16 && d2 < reach * reach {
+                    bonds.push((i, j));
+                }
+            }
+        }
+        bonds
+    }
+
+    /// Atoms rotated about their centroid by `yaw` around the vertical axis,
+    /// then `pitch` around the horizontal one, as (screen x, screen y, depth
+    /// towards the viewer), with the largest distance from the centroid
+    pub fn project(&self, yaw: f64, pitch: f64) -> (Vec<[f64; 3]>, f64) {
+        let n = self.atoms.len().max(1) as f64;
+        let mut centroid = [0.0; 3];
+        for atom in &self.atoms {
+            for (sum, value) in centroid.iter_mut().zip(atom.position) {
+                *sum += value / n;
+            }
+        }
+        let (sin_yaw, cos_yaw) = yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = pitch.sin_cos();
+        let mut radius: f64 = 0.0;
+        let points = self
+            .atoms
+            .iter()
+            .map(|atom| {
+                let [x, y, z] = [0, 1, 2].map(|k| atom.position[k] - centroid[k]);
+                radius = radius.max((x * x + y * y + z * z).sqrt());
+                let (x, z) = (x * cos_yaw + z * sin_yaw, z * cos_yaw - x * sin_yaw);
+                let (y, z) = (y * cos_pitch - z * sin_pitch, z * cos_pitch + y * sin_pitch);
+                [x, y, z]
+            })
+            .collect();
+        (points, radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_holds_every_element_in_order() {
+        for (index, element) in ELEMENTS.iter().enumerate() {
+            assert_eq!(element.number as usize, index + 1, "{}", element.symbol);
+        }
+    }
+
+    #[test]
+    fn two_letter_symbols_are_kept() {
+        for symbol in ["Ce", "Hf", "Os", "In", "Nb", "Pr", "Sm", "Ir", "La", "Nd"] {
+            assert_eq!(element_symbol(symbol), symbol);
+            assert_eq!(element_symbol(&symbol.to_uppercase()), symbol);
+        }
+        let structure = &parse_xyz("3\nceria\nCe 0 0 0\nO 1.8 0 0\nO -1.8 0 0\n").unwrap()[0];
+        assert_eq!(structure.summary().formula, "CeO2");
+        assert_eq!(structure.summary().unknown_mass, 0);
+    }
+
+    #[test]
+    fn labels_fall_back_to_one_letter_only_with_a_suffix() {
+        assert_eq!(element_symbol("HW1"), "H");
+        assert_eq!(element_symbol("OW2"), "O");
+        assert_eq!(element_symbol("CL3"), "Cl");
+        assert_eq!(element_symbol("C12"), "C");
+        assert_eq!(element_symbol("26"), "Fe");
+        // Unknown symbols stay unknown rather than becoming another element
+        assert_eq!(element_symbol("Xx"), "Xx");
+        assert!(element("Xx").is_none());
+    }
+}
@@ -0,0 +1,323 @@
+//! PDB coordinate files: chains, ligands, crystal data and secondary structure
+
+/// Residue names of water, counted rather than listed as ligands
+const WATERS: [&str; 4] = ["HOH", "WAT", "DOD", "H2O"];
+
+/// Residue of a polymer chain, from its ATOM records
+pub struct Residue {
+    pub name: String,
+    /// Sequence number with any insertion code
+    pub number: String,
+    /// Zero-based line of its first atom
+    pub line: usize,
+}
+
+pub struct Chain {
+    pub id: char,
+    pub residues: Vec<Residue>,
+    pub atoms: usize,
+}
+
+/// Non-water HETATM group
+pub struct Ligand {
+    pub name: String,
+    pub chain: char,
+    pub number: String,
+    pub atoms: usize,
+    pub line: usize,
+}
+
+/// Unit cell from CRYST1: lengths in Å, angles in degrees
+pub struct Cell {
+    pub lengths: [f64; 3],
+    pub angles: [f64; 3],
+    pub space_group: String,
+}
+
+/// A HELIX record or one strand of a SHEET
+pub struct Segment {
+    /// `Helix` or `Strand`
+    pub kind: &'static str,
+    /// Helix ID, or sheet ID and strand number
+    pub id: String,
+    pub start: String,
+    pub end: String,
+}
+
+/// Summary of the first model of a PDB file
+#[derive(Default)]
+pub struct Pdb {
+    /// ID code and classification from HEADER
+    pub id: Option<String>,
+    pub classification: Option<String>,
+    pub title: Option<String>,
+    /// REMARK 2 resolution, such as `1.80 Å` or `NOT APPLICABLE`
+    pub resolution: Option<String>,
+    pub cell: Option<Cell>,
+    pub chains: Vec<Chain>,
+    pub ligands: Vec<Ligand>,
+    pub waters: usize,
+    pub segments: Vec<Segment>,
+    pub models: usize,
+}
+
+/// A fixed-column field, trimmed; empty when the line is too short
+fn field(line: &str, start: usize, end: usize) -> &str {
+    line.get(start..end.min(line.len())).unwrap_or("").trim()
+}
+
+fn column(line: &str, at: usize) -> char {
+    line.as_bytes().get(at).map_or(' ', |&b| b as char)
+}
+
+/// Residue label such as `GLU A 12`, from name, chain and number columns
+fn residue_label(line: &str, name: usize, chain: usize, number: (usize, usize)) -> String {
+    format!(
+        "{} {} {}",
+        field(line, name, name + 3),
+        column(line, chain),
+        field(line, number.0, number.1)
+    )
+}
+
+pub fn parse(content: &str) -> Pdb {
+    let mut pdb = Pdb::default();
+    let mut title = String::new();
+    // Atoms after the first ENDMDL belong to other models
+    let mut first_model_done = false;
+    let mut last_water: Option<(char, String)> = None;
+
+    for (line_idx, line) in content.lines().enumerate() {
+        let record = field(line, 0, 6);
+        match record {
+            "HEADER" => {
+                pdb.classification =
+                    Some(field(line, 10, 50).to_string()).filter(|s| !s.is_empty());
+                pdb.id = Some(field(line, 62, 66).to_string()).filter(|s| !s.is_empty());
+            }
+            "TITLE" => {
+                if !title.is_empty() {
+                    title.push(' ');
+                }
+                title.push_str(field(line, 10, 80));
+            }
+            "REMARK" if field(line, 6, 10) == "2" => {
+                if let Some((_, value)) = line.split_once("RESOLUTION.") {
+                    let value = value.trim().trim_end_matches('.');
+                    pdb.resolution = Some(match value.strip_suffix("ANGSTROMS") {
+                        Some(number) => format!("{} Å", number.trim()),
+                        None => value.to_string(),
+                    });
+                }
+            }
+            "CRYST1" => {
+                let number = |start, end| field(line, start, end).parse::<f64>().ok();
+                if let (Some(a), Some(b), Some(c), Some(alpha), Some(beta), Some(gamma)) = (
+                    number(6, 15),
+                    number(15, 24),
+                    number(24, 33),
+                    number(33, 40),
+                    number(40, 47),
+                    number(47, 54),
+                ) {
+                    pdb.cell = Some(Cell {
+                        lengths: [a, b, c],
+                        angles: [alpha, beta, gamma],
+                        space_group: field(line, 55, 66).to_string(),
+                    });
+                }
+            }
+            "HELIX" => pdb.segments.push(Segment {
+                kind: "Helix",
+                id: field(line, 11, 14).to_string(),
+                start: residue_label(line, 15, 19, (21, 26)),
+                end: residue_label(line, 27, 31, (33, 38)),
+            }),
+            "SHEET" => pdb.segments.push(Segment {
+                kind: "Strand",
+                id: format!("{}{}", field(line, 11, 14), field(line, 7, 10)),
+                start: residue_label(line, 17, 21, (22, 27)),
+                end: residue_label(line, 28, 32, (33, 38)),
+            }),
+            "MODEL" => pdb.models += 1,
+            "ENDMDL" => first_model_done = true,
+            "ATOM" | "HETATM" if !first_model_done => {
+                let name = field(line, 17, 20).to_string();
+                let chain = column(line, 21);
+                let number = field(line, 22, 27).to_string();
+                if record == "HETATM" && WATERS.contains(&name.as_str()) {
+                    let key = Some((chain, number));
+                    if last_water != key {
+                        pdb.waters += 1;
+                        last_water = key;
+                    }
+                } else if record == "HETATM" {
+                    match pdb.ligands.last_mut() {
+                        Some(ligand)
+                            if ligand.name == name
+                                && ligand.chain == chain
+                                && ligand.number == number =>
+                        {
+                            ligand.atoms += 1
+                        }
+                        _ => pdb.ligands.push(Ligand {
+                            name,
+                            chain,
+                            number,
+                            atoms: 1,
+                            line: line_idx,
+                        }),
+                    }
+                } else {
+                    if pdb.chains.last().is_none_or(|c| c.id != chain) {
+                        pdb.chains.push(Chain {
+                            id: chain,
+                            residues: Vec::new(),
+                            atoms: 0,
+                        });
+                    }
+                    let Some(current) = pdb.chains.last_mut() else {
+                        continue;
+                    };
+                    current.atoms += 1;
+                    if current.residues.last().is_none_or(|r| r.number != number) {
+                        current.residues.push(Residue {
+                            name,
+                            number,
+                            line: line_idx,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    pdb.title = Some(title).filter(|t| !t.is_empty());
+    pdb
+}
+
+/// An entry of the jump list and the line it scrolls to
+pub struct JumpTarget {
+    pub label: String,
+    pub line: usize,
+    pub kind: JumpKind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JumpKind {
+    Chain,
+    Residue,
+    Ligand,
+}
+
+impl Pdb {
+    /// Each chain followed by its residues, then the ligands
+    pub fn jump_targets(&self) -> Vec<JumpTarget> {
+        let mut targets = Vec::new();
+        for chain in &self.chains {
+            let Some(first) = chain.residues.first() else {
+                continue;
+            };
+            targets.push(JumpTarget {
+                label: format!("Chain {} ({} residues)", chain.id, chain.residues.len()),
+                line: first.line,
+                kind: JumpKind::Chain,
+            });
+            targets.extend(chain.residues.iter().map(|residue| JumpTarget {
+                label: format!("{} {}", residue.name, residue.number),
+                line: residue.line,
+                kind: JumpKind::Residue,
+            }));
+        }
+        targets.extend(self.ligands.iter().map(|ligand| JumpTarget {
+            label: format!("{} {} {}", ligand.name, ligand.chain, ligand.number),
+            line: ligand.line,
+            kind: JumpKind::Ligand,
+        }));
+        targets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records from the start of 1CRN, with a water and a second model
+    const CRAMBIN: &str = "\
+HEADER    PLANT PROTEIN                           30-APR-81   1CRN
+TITLE     WATER STRUCTURE OF A HYDROPHOBIC PROTEIN AT ATOMIC RESOLUTION.
+TITLE    2 PENTAGON RINGS OF WATER MOLECULES IN CRYSTALS OF CRAMBIN
+REMARK   2
+REMARK   2 RESOLUTION.    1.50 ANGSTROMS.
+HELIX    1  H1 ILE A    7  PRO A   19  3/10 CONFORMATION RES 17,19       13
+HELIX    2  H2 GLU A   23  THR A   30  1 DISTORTED 3/10 AT RES 30         8
+SHEET    1  S1 2 THR A   1  CYS A   4  0
+SHEET    2  S1 2 CYS A  32  ILE A  35 -1
+CRYST1   40.960   18.650   22.520  90.00  90.77  90.00 P 1 21 1      2
+MODEL        1
+ATOM      1  N   THR A   1      17.047  14.099   3.625  1.00 13.79           N
+ATOM      2  CA  THR A   1      16.967  12.784   4.338  1.00 10.80           C
+ATOM      3  N   THR A   2      15.685  12.755   5.133  1.00  9.19           N
+HETATM  328  O   HOH A 101      10.000  10.000  10.000  1.00 20.00           O
+ENDMDL
+MODEL        2
+ATOM      1  N   THR A   1      17.047  14.099   3.625  1.00 13.79           N
+ENDMDL
+";
+
+    #[test]
+    fn header_remark_and_cell() {
+        let pdb = parse(CRAMBIN);
+        assert_eq!(pdb.id.as_deref(), Some("1CRN"));
+        assert_eq!(pdb.classification.as_deref(), Some("PLANT PROTEIN"));
+        assert_eq!(
+            pdb.title.as_deref(),
+            Some(
+                "WATER STRUCTURE OF A HYDROPHOBIC PROTEIN AT ATOMIC RESOLUTION. \
+                 PENTAGON RINGS OF WATER MOLECULES IN CRYSTALS OF CRAMBIN"
+            )
+        );
+        assert_eq!(pdb.resolution.as_deref(), Some("1.50 Å"));
+        let cell = pdb.cell.unwrap();
+        assert_eq!(cell.lengths, [40.96, 18.65, 22.52]);
+        assert_eq!(cell.angles, [90.0, 90.77, 90.0]);
+        assert_eq!(cell.space_group, "P 1 21 1");
+
+        let nmr = parse("REMARK   2 RESOLUTION. NOT APPLICABLE.\n");
+        assert_eq!(nmr.resolution.as_deref(), Some("NOT APPLICABLE"));
+    }
+
+    #[test]
+    fn helices_and_strands() {
+        let segments = parse(CRAMBIN).segments;
+        let summary: Vec<(&str, &str, &str, &str)> = segments
+            .iter()
+            .map(|s| (s.kind, s.id.as_str(), s.start.as_str(), s.end.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Helix", "H1", "ILE A 7", "PRO A 19"),
+                ("Helix", "H2", "GLU A 23", "THR A 30"),
+                ("Strand", "S11", "THR A 1", "CYS A 4"),
+                ("Strand", "S12", "CYS A 32", "ILE A 35"),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_the_first_model_is_counted() {
+        let pdb = parse(CRAMBIN);
+        assert_eq!(pdb.models, 2);
+        assert_eq!(pdb.chains.len(), 1);
+        assert_eq!(pdb.chains[0].atoms, 3);
+        let numbers: Vec<&str> = pdb.chains[0]
+            .residues
+            .iter()
+            .map(|r| r.number.as_str())
+            .collect();
+        assert_eq!(numbers, ["1", "2"]);
+        assert_eq!(pdb.waters, 1);
+        assert!(pdb.ligands.is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Peak finding over a single X/Y series
+
+/// Peak search settings; fractions are relative to the data's Y range
+#[derive(Clone, Copy)]
+pub struct PeakParams {
+    /// Minimum prominence as a fraction of the Y range
+    pub prominence: f64,
+    /// Minimum separation between peaks, in points
+    pub min_distance: usize,
+    /// Minimum height above the data minimum, as a fraction of the Y range
+    pub threshold: f64,
+}
+
+impl Default for PeakParams {
+    fn default() -> Self {
+        PeakParams {
+            prominence: 0.05,
+            min_distance: 3,
+            threshold: 0.0,
+        }
+    }
+}
+
+/// A detected peak
+#[derive(Clone, Copy)]
+pub struct Peak {
+    pub x: f64,
+    pub height: f64,
+    /// Full width at half prominence, interpolated between points
+    pub fwhm: f64,
+    /// Trapezoid area above the straight line joining the peak's bases
+    pub area: f64,
+}
+
+/// Find peaks in `data`, sorted by X position
+pub fn find_peaks(data: &[(f64, f64)], params: &PeakParams) -> Vec<Peak> {
+    let mut points = data.to_vec();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let n = points.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    let y: Vec<f64> = points.iter().map(|p| p.1).collect();
+
+    let y_min = y.iter().copied().fold(f64::INFINITY, f64::min);
+    let y_max = y.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = y_max - y_min;
+    if range <= 0.0 {
+        return Vec::new();
+    }
+    let min_height = y_min + params.threshold * range;
+    let min_prominence = params.prominence * range;
+
+    // Local maxima; a plateau counts once, at its first point
+    let mut candidates: Vec<(usize, f64, usize, usize)> = Vec::new();
+    let mut i = 1;
+    while i < n - 1 {
+        if y[i] > y[i - 1] {
+            let mut end = i;
+            while end + 1 < n && y[end + 1] == y[i] {
+                end += 1;
+            }
+            if end + 1 < n && y[end + 1] < y[i] && y[i] >= min_height {
+                let (prominence, left, right) = prominence_at(&y, i);
+                if prominence >= min_prominence {
+                    candidates.push((i, prominence, left, right));
+                }
+            }
+            i = end + 1;
+        } else {
+            i += 1;
+        }
+    }
+
+    // Enforce the minimum distance, keeping the tallest peaks first
+    candidates.sort_by(|a, b| y[b.0].total_cmp(&y[a.0]));
+    let mut kept: Vec<(usize, f64, usize, usize)> = Vec::new();
+    for candidate in candidates {
+        if kept
+            .iter()
+            .all(|k| k.0.abs_diff(candidate.0) >= params.min_distance.max(1))
+        {
+            kept.push(candidate);
+        }
+    }
+    kept.sort_by_key(|k| k.0);
+
+    kept.into_iter()
+        .map(|(idx, prominence, left, right)| Peak {
+            x: points[idx].0,
+            height: y[idx],
+            fwhm: width_at(&points, idx, y[idx] - prominence / 2.0, left, right),
+            area: area_above_bases(&points, left, right),
+        })
+        .collect()
+}
+
+/// Prominence of the peak at `idx` and the indices of its left and right bases
+fn prominence_at(y: &[f64], idx: usize) -> (f64, usize, usize) {
+    let peak = y[idx];
+
+    // Walk outwards until a higher point, tracking the lowest point passed
+    let mut left = idx;
+    let mut i = idx;
+    while i > 0 {
+        i -= 1;
+        if y[i] > peak {
+            break;
+        }
+        if y[i] < y[left] {
+            left = i;
+        }
+    }
+    let mut right = idx;
+    let mut i = idx;
+    while i + 1 < y.len() {
+        i += 1;
+        if y[i] > peak {
+            break;
+        }
+        if y[i] < y[right] {
+            right = i;
+        }
+    }
+
+    (peak - y[left].max(y[right]), left, right)
+}
+
+/// Width where the signal crosses `level` on both sides of the peak
+fn width_at(points: &[(f64, f64)], idx: usize, level: f64, left: usize, right: usize) -> f64 {
+    let crossing = |a: (f64, f64), b: (f64, f64)| {
+        if b.1 == a.1 {
+            a.0
+        } else {
+            a.0 + (level - a.1) * (b.0 - a.0) / (b.1 - a.1)
+        }
+    };
+
+    let mut i = idx;
+    while i > left && points[i].1 > level {
+        i -= 1;
+    }
+    let x_left = crossing(points[i], points[i + 1]);
+
+    let mut j = idx;
+    while j < right && points[j].1 > level {
+        j += 1;
+    }
+    let x_right = crossing(points[j - 1], points[j]);
+
+    (x_right - x_left).abs()
+}
+
+/// Area between the curve and the line joining the two base points
+fn area_above_bases(points: &[(f64, f64)], left: usize, right: usize) -> f64 {
+    let (x0, y0) = points[left];
+    let (x1, y1) = points[right];
+    let base = |x: f64| {
+        if x1 == x0 {
+            y0
+        } else {
+            y0 + (x - x0) * (y1 - y0) / (x1 - x0)
+        }
+    };
+    points[left..=right]
+        .windows(2)
+        .map(|w| {
+            let h0 = w[0].1 - base(w[0].0);
+            let h1 = w[1].1 - base(w[1].0);
+            (w[1].0 - w[0].0) * (h0 + h1) / 2.0
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tall peak at x = 30, short one at x = 70 and a small bump at x = 50
+    fn two_peaks() -> Vec<(f64, f64)> {
+        let gaussian = |x: f64, centre: f64, height: f64, sigma: f64| {
+            height * (-(x - centre).powi(2) / (2.0 * sigma * sigma)).exp()
+        };
+        (0..=100)
+            .map(|i| {
+                let x = i as f64;
+                let y = gaussian(x, 30.0, 10.0, 3.0)
+                    + gaussian(x, 70.0, 4.0, 3.0)
+                    + gaussian(x, 50.0, 0.2, 1.0);
+                (x, y)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prominence_drops_small_bumps() {
+        let peaks = find_peaks(&two_peaks(), &PeakParams::default());
+        let xs: Vec<f64> = peaks.iter().map(|p| p.x).collect();
+        assert_eq!(xs, [30.0, 70.0]);
+        assert!((peaks[0].height - 10.0).abs() < 1e-9);
+        // FWHM of a Gaussian is 2√(2 ln 2) σ
+        let fwhm = 2.0 * (2.0 * 2f64.ln()).sqrt() * 3.0;
+        assert!((peaks[0].fwhm - fwhm).abs() < 0.1);
+        // The short peak's area lies between its bases in the valleys around it
+        let root_two_pi = (2.0 * std::f64::consts::PI).sqrt();
+        assert!((peaks[1].area - 4.0 * 3.0 * root_two_pi).abs() < 0.01);
+
+        // A prominence below 2% of the range lets the bump through
+        let loose = PeakParams {
+            prominence: 0.01,
+            ..PeakParams::default()
+        };
+        assert_eq!(find_peaks(&two_peaks(), &loose).len(), 3);
+    }
+
+    #[test]
+    fn min_distance_keeps_the_taller_peak() {
+        let spaced = PeakParams {
+            min_distance: 50,
+            ..PeakParams::default()
+        };
+        let peaks = find_peaks(&two_peaks(), &spaced);
+        assert_eq!(peaks.len(), 1);
+        assert_eq!(peaks[0].x, 30.0);
+    }
+
+    #[test]
+    fn threshold_drops_low_peaks() {
+        let high = PeakParams {
+            threshold: 0.5,
+            ..PeakParams::default()
+        };
+        let peaks = find_peaks(&two_peaks(), &high);
+        assert_eq!(peaks.len(), 1);
+        assert_eq!(peaks[0].x, 30.0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Chart drawing style (graph type and marker), remembered per file extension
+
+use ratatui::symbols::Marker;
+use std::collections::HashMap;
+
+/// How each series is drawn
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlotStyle {
+    #[default]
+    Scatter,
+    Line,
+    LinePoints,
+}
+
+impl PlotStyle {
+    pub fn next(self) -> PlotStyle {
+        match self {
+            PlotStyle::Scatter => PlotStyle::Line,
+            PlotStyle::Line => PlotStyle::LinePoints,
+            PlotStyle::LinePoints => PlotStyle::Scatter,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            PlotStyle::Scatter => "Scatter Plot",
+            PlotStyle::Line => "Line Plot",
+            PlotStyle::LinePoints => "Line + Points",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            PlotStyle::Scatter => "scatter",
+            PlotStyle::Line => "line",
+            PlotStyle::LinePoints => "linepoints",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<PlotStyle> {
+        match key {
+            "scatter" => Some(PlotStyle::Scatter),
+            "line" => Some(PlotStyle::Line),
+            "linepoints" => Some(PlotStyle::LinePoints),
+            _ => None,
+        }
+    }
+}
+
+/// Graph type and marker used for a chart
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ChartStyle {
+    pub plot: PlotStyle,
+    pub marker: Marker,
+}
+
+impl Default for ChartStyle {
+    fn default() -> Self {
+        ChartStyle {
+            plot: PlotStyle::Scatter,
+            marker: Marker::Braille,
+        }
+    }
+}
+
+impl ChartStyle {
+    /// Marker used for the point overlay in line + points mode
+    pub fn point_marker(&self) -> Marker {
+        if self.marker == Marker::Dot {
+            Marker::Block
+        } else {
+            Marker::Dot
+        }
+    }
+}
+
+/// Cycle through the markers offered in the chart
+pub fn next_marker(marker: Marker) -> Marker {
+    match marker {
+        Marker::Braille => Marker::Dot,
+        Marker::Dot => Marker::Block,
+        Marker::Block => Marker::HalfBlock,
+        _ => Marker::Braille,
+    }
+}
+
+pub fn marker_name(marker: Marker) -> &'static str {
+    match marker {
+        Marker::Braille => "braille",
+        Marker::Dot => "dot",
+        Marker::Block => "block",
+        Marker::HalfBlock => "halfblock",
+        _ => "other",
+    }
+}
+
+fn marker_from_name(name: &str) -> Option<Marker> {
+    match name {
+        "braille" => Some(Marker::Braille),
+        "dot" => Some(Marker::Dot),
+        "block" => Some(Marker::Block),
+        "halfblock" => Some(Marker::HalfBlock),
+        _ => None,
+    }
+}
+
+/// Parse `ext<TAB>style<TAB>marker` lines, skipping anything malformed
+pub fn parse_styles(content: &str) -> HashMap<String, ChartStyle> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let ext = fields.next()?;
+            let plot = PlotStyle::from_key(fields.next()?)?;
+            let marker = marker_from_name(fields.next()?)?;
+            Some((ext.to_string(), ChartStyle { plot, marker }))
+        })
+        .collect()
+}
+
+/// Serialize styles in the format read by `parse_styles`
+pub fn format_styles(styles: &HashMap<String, ChartStyle>) -> String {
+    let mut exts: Vec<&String> = styles.keys().collect();
+    exts.sort();
+    exts.into_iter()
+        .map(|ext| {
+            let style = styles[ext];
+            format!(
+                "{}\t{}\t{}\n",
+                ext,
+                style.plot.key(),
+                marker_name(style.marker)
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,230 @@
+//! Axis scaling (linear, log10, symlog) between data and plot coordinates
+
+/// How values along one chart axis are mapped onto the plot
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum AxisScale {
+    #[default]
+    Linear,
+    Log10,
+    /// Linear within ±threshold, logarithmic outside
+    SymLog,
+}
+
+impl AxisScale {
+    pub fn next(self) -> AxisScale {
+        match self {
+            AxisScale::Linear => AxisScale::Log10,
+            AxisScale::Log10 => AxisScale::SymLog,
+            AxisScale::SymLog => AxisScale::Linear,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AxisScale::Linear => "lin",
+            AxisScale::Log10 => "log",
+            AxisScale::SymLog => "symlog",
+        }
+    }
+}
+
+/// Scale of one axis with the symlog linear threshold
+#[derive(Clone, Copy)]
+pub struct AxisTransform {
+    pub scale: AxisScale,
+    pub linthresh: f64,
+}
+
+impl Default for AxisTransform {
+    fn default() -> Self {
+        AxisTransform {
+            scale: AxisScale::Linear,
+            linthresh: 1.0,
+        }
+    }
+}
+
+impl AxisTransform {
+    /// Map a data value to plot space; `None` when the scale cannot show it
+    pub fn forward(&self, v: f64) -> Option<f64> {
+        match self.scale {
+            AxisScale::Linear => Some(v),
+            AxisScale::Log10 => (v > 0.0).then(|| v.log10()),
+            AxisScale::SymLog => {
+                let c = self.linthresh;
+                if v.abs() <= c {
+                    Some(v / c)
+                } else {
+                    Some(v.signum() * (1.0 + (v.abs() / c).log10()))
+                }
+            }
+        }
+    }
+
+    /// Map a plot-space coordinate back to a data value
+    pub fn inverse(&self, t: f64) -> f64 {
+        match self.scale {
+            AxisScale::Linear => t,
+            AxisScale::Log10 => 10f64.powf(t),
+            AxisScale::SymLog => {
+                let c = self.linthresh;
+                if t.abs() <= 1.0 {
+                    t * c
+                } else {
+                    t.signum() * c * 10f64.powf(t.abs() - 1.0)
+                }
+            }
+        }
+    }
+
+    /// Plot-space bounds covering `[min, max]` (both already in plot space)
+    ///
+    /// Linear axes get 5% padding, log axes are widened to whole decades so
+    /// the evenly spaced axis labels land exactly on decade ticks.
+    pub fn fit(&self, min: f64, max: f64) -> [f64; 2] {
+        if self.scale == AxisScale::Linear {
+            // Handle case where all values are the same
+            if max == min {
+                return [min - 1.0, max + 1.0];
+            }
+            let padding = (max - min).abs() * 0.05;
+            return [min - padding, max + padding];
+        }
+
+        let lo = min.floor();
+        let mut hi = max.ceil();
+        if hi == lo {
+            hi += 1.0;
+        }
+        let step = decade_step(hi - lo);
+        hi = lo + step * ((hi - lo) / step).ceil();
+        [lo, hi]
+    }
+
+    /// Data values for evenly spaced axis labels across plot-space `bounds`,
+    /// `None` for a slot left blank
+    ///
+    /// Log and symlog axes label the whole decades inside the bounds, each in
+    /// the nearest of the evenly spaced slots. With fewer than two decades
+    /// inside, or on linear axes, labels go at the ends and the middle.
+    pub fn ticks(&self, bounds: [f64; 2]) -> Vec<Option<f64>> {
+        let span = bounds[1] - bounds[0];
+        if self.scale != AxisScale::Linear && span > 0.0 {
+            // Decades within rounding of the bounds count as inside
+            let first = (bounds[0] - 1e-9).ceil();
+            let last = (bounds[1] + 1e-9).floor();
+            let step = decade_step(last - first);
+            let decades: Vec<f64> = (0..)
+                .map(|i| first + i as f64 * step)
+                .take_while(|&d| d <= last)
+                .collect();
+            if decades.len() >= 2 {
+                let fractions: Vec<f64> = decades
+                    .iter()
+                    .map(|d| ((d - bounds[0]) / span).clamp(0.0, 1.0))
+                    .collect();
+                let slots = best_slot_count(&fractions);
+                let mut ticks = vec![None; slots + 1];
+                for (&d, f) in decades.iter().zip(&fractions) {
+                    ticks[(f * slots as f64).round() as usize] = Some(self.inverse(d));
+                }
+                return ticks;
+            }
+        }
+        [bounds[0], (bounds[0] + bounds[1]) / 2.0, bounds[1]]
+            .into_iter()
+            .map(|t| Some(self.inverse(t)))
+            .collect()
+    }
+}
+
+/// Most label slots an axis is divided into, so labels keep their width
+const MAX_SLOTS: usize = 8;
+
+/// Number of evenly spaced intervals whose slots lie closest to the axis
+/// fractions `fractions`, giving each its own slot
+fn best_slot_count(fractions: &[f64]) -> usize {
+    let error = |slots: usize| {
+        let positions: Vec<f64> = fractions
+            .iter()
+            .map(|f| (f * slots as f64).round())
+            .collect();
+        if positions.windows(2).any(|w| w[0] == w[1]) {
+            return f64::INFINITY;
+        }
+        fractions
+            .iter()
+            .zip(&positions)
+            .map(|(f, p)| (f - p / slots as f64).abs())
+            .fold(0.0, f64::max)
+    };
+    let fewest = fractions.len().saturating_sub(1).max(1);
+    (fewest..=MAX_SLOTS.max(fewest))
+        .min_by(|&a, &b| error(a).total_cmp(&error(b)))
+        .unwrap_or(fewest)
+}
+
+/// Decades between labels so an axis shows at most six of them
+fn decade_step(decades: f64) -> f64 {
+    (decades / 5.0).ceil().max(1.0)
+}
+
+/// Symlog threshold: the decade of the smallest non-zero magnitude,
+/// limited to six decades below the largest one
+pub fn symlog_threshold(values: impl Iterator<Item = f64>) -> f64 {
+    let mut min_abs = f64::INFINITY;
+    let mut max_abs = 0.0f64;
+    for v in values.map(f64::abs).filter(|v| v.is_finite() && *v > 0.0) {
+        min_abs = min_abs.min(v);
+        max_abs = max_abs.max(v);
+    }
+    if !min_abs.is_finite() {
+        return 1.0;
+    }
+    let floor_decade = |v: f64| 10f64.powf(v.log10().floor());
+    floor_decade(min_abs).max(floor_decade(max_abs * 1e-6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log() -> AxisTransform {
+        AxisTransform {
+            scale: AxisScale::Log10,
+            linthresh: 1.0,
+        }
+    }
+
+    #[test]
+    fn whole_decade_bounds_label_every_decade() {
+        let ticks = log().ticks([0.0, 3.0]);
+        assert_eq!(ticks, [Some(1.0), Some(10.0), Some(100.0), Some(1000.0)]);
+    }
+
+    #[test]
+    fn zoomed_log_axis_labels_the_decades_inside() {
+        // 10^0.37 to 10^3.6: decades 1 to 3, none at the ends
+        let ticks = log().ticks([0.37, 3.6]);
+        let labelled: Vec<f64> = ticks.iter().flatten().copied().collect();
+        assert_eq!(labelled, [10.0, 100.0, 1000.0]);
+        assert!(ticks.first().unwrap().is_none() && ticks.last().unwrap().is_none());
+        // Each decade sits in the slot nearest its place along the axis
+        let slots = (ticks.len() - 1) as f64;
+        for (i, tick) in ticks.iter().enumerate() {
+            if let Some(value) = tick {
+                let fraction = (value.log10() - 0.37) / 3.23;
+                assert!((i as f64 / slots - fraction).abs() <= 0.5 / slots);
+            }
+        }
+    }
+
+    #[test]
+    fn fewer_than_two_decades_fall_back_to_even_labels() {
+        let ticks = log().ticks([0.2, 1.5]);
+        assert_eq!(ticks.len(), 3);
+        assert!((ticks[0].unwrap() - 10f64.powf(0.2)).abs() < 1e-12);
+        assert!((ticks[2].unwrap() - 10f64.powf(1.5)).abs() < 1e-12);
+        assert_eq!(AxisTransform::default().ticks([0.0, 3.0]).len(), 3);
+    }
+}
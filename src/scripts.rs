@@ -0,0 +1,509 @@
+//! gnuplot and matplotlib scripts that redraw the chart from the source
+//! file, and a table of the plotted values for Origin
+
+use std::fmt::Write as _;
+
+use crate::export::csv_field;
+use crate::plot_style::PlotStyle;
+use crate::scale::AxisScale;
+
+/// One axis of the chart as the scripts set it up
+pub struct ScriptAxis {
+    pub label: String,
+    /// Data-space limits, `None` to let the plotting program autoscale
+    pub range: Option<[f64; 2]>,
+    pub scale: AxisScale,
+    pub linthresh: f64,
+}
+
+/// A Y column drawn from the source file
+pub struct ScriptSeries {
+    /// Zero-based column of the parsed table
+    pub column: usize,
+    pub name: String,
+    pub color: (u8, u8, u8),
+}
+
+/// A run of rows of the source file drawn on the chart
+pub struct ScriptBlock {
+    /// One-based lines holding the block's rows, and how many rows there are
+    pub first_line: usize,
+    pub last_line: usize,
+    pub rows: usize,
+    /// Colour of the first series' curve when this is not the current block
+    pub color: (u8, u8, u8),
+    /// Added to Y, stacking the blocks of a waterfall
+    pub offset: f64,
+}
+
+/// Everything both scripts need to reproduce the current chart
+pub struct PlotSpec {
+    pub source: String,
+    /// Blocks on the chart: the current one, or every block of an overlay
+    /// or waterfall
+    pub blocks: Vec<ScriptBlock>,
+    /// Block whose series are drawn in full, with error bars and legend
+    pub current: usize,
+    /// Fields are separated by commas rather than whitespace
+    pub comma: bool,
+    pub title: String,
+    pub x_column: usize,
+    pub series: Vec<ScriptSeries>,
+    /// Error columns of the first series
+    pub y_error: Option<usize>,
+    pub x_error: Option<usize>,
+    pub style: PlotStyle,
+    pub x: ScriptAxis,
+    pub y: ScriptAxis,
+    /// Viewer processing the scripts do not redo, shown as comments
+    pub notes: Vec<String>,
+    pub written: String,
+}
+
+/// Error bars are drawn in the viewer's dark gray
+const ERROR_COLOR: (u8, u8, u8) = (92, 99, 112);
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// gnuplot single-quoted string, where only the quote itself needs doubling
+fn gp_string(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn py_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Comment lines listing processing the script leaves out
+fn notes_comment(notes: &[String], prefix: &str) -> String {
+    let mut out = String::new();
+    if !notes.is_empty() {
+        let _ = writeln!(out, "{}The viewer also applied, not redone here:", prefix);
+        for note in notes {
+            let _ = writeln!(out, "{}  {}", prefix, note);
+        }
+        let _ = writeln!(
+            out,
+            "{}Export the processed data from the viewer to plot it as shown.",
+            prefix
+        );
+    }
+    out
+}
+
+pub fn gnuplot(spec: &PlotSpec) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# gnuplot script for {}", spec.source);
+    let _ = writeln!(out, "# Written by sci-file-viewer on {}", spec.written);
+    out.push_str(&notes_comment(&spec.notes, "# "));
+    out.push('\n');
+    out.push_str("set datafile commentschars '#;'\n");
+    if spec.comma {
+        out.push_str("set datafile separator ','\n");
+    }
+    let _ = writeln!(out, "set title {} noenhanced", gp_string(&spec.title));
+    for (name, axis) in [("x", &spec.x), ("y", &spec.y)] {
+        let _ = writeln!(
+            out,
+            "set {}label {} noenhanced",
+            name,
+            gp_string(&axis.label)
+        );
+        if let Some([lo, hi]) = axis.range {
+            let _ = writeln!(out, "set {}range [{}:{}]", name, lo, hi);
+        }
+        match axis.scale {
+            AxisScale::Linear => {}
+            AxisScale::Log10 => {
+                let _ = writeln!(out, "set logscale {} 10", name);
+            }
+            AxisScale::SymLog => {
+                let _ = writeln!(out, "# gnuplot has no symlog scale; {} stays linear", name);
+            }
+        }
+    }
+    out.push_str("set key top right noenhanced\nset grid\n\n");
+
+    // Skip to a block's first row and stop after its last; blocks hold no
+    // blank lines, so the rows are all in gnuplot's first block after the skip
+    let source = |block: &ScriptBlock| {
+        format!(
+            "{} skip {} every 1:1:0:0:{}:0",
+            gp_string(&spec.source),
+            block.first_line - 1,
+            block.rows.saturating_sub(1)
+        )
+    };
+    let style = match spec.style {
+        PlotStyle::Scatter => "points pt 7 ps 0.5",
+        PlotStyle::Line => "lines",
+        PlotStyle::LinePoints => "linespoints pt 7 ps 0.5",
+    };
+    let x = spec.x_column + 1;
+    let mut elements = Vec::new();
+    // Other blocks show the first series beneath the current block
+    if let Some(first) = spec.series.first() {
+        for (i, block) in spec.blocks.iter().enumerate() {
+            if i == spec.current {
+                continue;
+            }
+            let y = if block.offset == 0.0 {
+                (first.column + 1).to_string()
+            } else {
+                format!("(${}+({}))", first.column + 1, block.offset)
+            };
+            elements.push(format!(
+                "{} using {}:{} with {} lc rgb '{}' notitle",
+                source(block),
+                x,
+                y,
+                style,
+                hex(block.color)
+            ));
+        }
+    }
+    let source = spec
+        .blocks
+        .get(spec.current)
+        .map(source)
+        .unwrap_or_default();
+    if let Some(first) = spec.series.first() {
+        let y = first.column + 1;
+        let errors = match (spec.x_error, spec.y_error) {
+            (Some(dx), Some(dy)) => Some(format!(
+                "{}:{}:{}:{} with xyerrorbars",
+                x,
+                y,
+                dx + 1,
+                dy + 1
+            )),
+            (Some(dx), None) => Some(format!("{}:{}:{} with xerrorbars", x, y, dx + 1)),
+            (None, Some(dy)) => Some(format!("{}:{}:{} with yerrorbars", x, y, dy + 1)),
+            (None, None) => None,
+        };
+        if let Some(errors) = errors {
+            elements.push(format!(
+                "{} using {} pt 0 lc rgb '{}' notitle",
+                source,
+                errors,
+                hex(ERROR_COLOR)
+            ));
+        }
+    }
+    for series in &spec.series {
+        elements.push(format!(
+            "{} using {}:{} with {} lc rgb '{}' title {}",
+            source,
+            x,
+            series.column + 1,
+            style,
+            hex(series.color),
+            gp_string(&series.name)
+        ));
+    }
+    let _ = writeln!(out, "plot {}", elements.join(", \\\n     "));
+    out
+}
+
+pub fn matplotlib(spec: &PlotSpec) -> String {
+    let mut out = String::new();
+    out.push_str("#!/usr/bin/env python3\n");
+    let _ = writeln!(out, "# matplotlib script for {}", spec.source);
+    let _ = writeln!(out, "# Written by sci-file-viewer on {}", spec.written);
+    out.push_str(&notes_comment(&spec.notes, "# "));
+    out.push_str("\nimport re\n\nimport matplotlib.pyplot as plt\n\n");
+    let _ = writeln!(out, "SOURCE = {}", py_string(&spec.source));
+    let ranges: Vec<String> = spec
+        .blocks
+        .iter()
+        .map(|block| format!("({}, {})", block.first_line, block.last_line))
+        .collect();
+    let _ = writeln!(
+        out,
+        "BLOCKS = [{}]  # first and last line of each plotted block, from 1",
+        ranges.join(", ")
+    );
+    let _ = writeln!(out, "CURRENT = {}", spec.current);
+    out.push_str(LOADER);
+    out.push_str("\nblocks = [load_columns(SOURCE, first, last) for first, last in BLOCKS]\n");
+    out.push_str("columns = blocks[CURRENT]\n");
+    out.push_str("fig, ax = plt.subplots(figsize=(8, 5))\n");
+
+    let x = format!("columns[{}]", spec.x_column);
+    let style = match spec.style {
+        PlotStyle::Scatter => "\"o\", markersize=2",
+        PlotStyle::Line => "\"-\"",
+        PlotStyle::LinePoints => "\"-o\", markersize=2",
+    };
+    if let Some(first) = spec.series.first() {
+        for (i, block) in spec.blocks.iter().enumerate() {
+            if i == spec.current {
+                continue;
+            }
+            let y = if block.offset == 0.0 {
+                format!("blocks[{}][{}]", i, first.column)
+            } else {
+                format!(
+                    "[v + ({}) for v in blocks[{}][{}]]",
+                    block.offset, i, first.column
+                )
+            };
+            let _ = writeln!(
+                out,
+                "ax.plot(blocks[{}][{}], {}, {}, color=\"{}\")",
+                i,
+                spec.x_column,
+                y,
+                style,
+                hex(block.color)
+            );
+        }
+    }
+    if let Some(first) = spec.series.first()
+        && (spec.x_error.is_some() || spec.y_error.is_some())
+    {
+        let error = |name: &str, column: Option<usize>| {
+            column
+                .map(|c| format!(", {}=columns[{}]", name, c))
+                .unwrap_or_default()
+        };
+        let _ = writeln!(
+            out,
+            "ax.errorbar({}, columns[{}]{}{}, fmt=\"none\", ecolor=\"{}\")",
+            x,
+            first.column,
+            error("xerr", spec.x_error),
+            error("yerr", spec.y_error),
+            hex(ERROR_COLOR)
+        );
+    }
+    for series in &spec.series {
+        let _ = writeln!(
+            out,
+            "ax.plot({}, columns[{}], {}, color=\"{}\", label={})",
+            x,
+            series.column,
+            style,
+            hex(series.color),
+            py_string(&series.name)
+        );
+    }
+    for (name, axis) in [("x", &spec.x), ("y", &spec.y)] {
+        let _ = writeln!(out, "ax.set_{}label({})", name, py_string(&axis.label));
+        if let Some([lo, hi]) = axis.range {
+            let _ = writeln!(out, "ax.set_{}lim({}, {})", name, lo, hi);
+        }
+        match axis.scale {
+            AxisScale::Linear => {}
+            AxisScale::Log10 => {
+                let _ = writeln!(out, "ax.set_{}scale(\"log\")", name);
+            }
+            AxisScale::SymLog => {
+                let _ = writeln!(
+                    out,
+                    "ax.set_{}scale(\"symlog\", linthresh={})",
+                    name, axis.linthresh
+                );
+            }
+        }
+    }
+    let _ = writeln!(out, "ax.set_title({})", py_string(&spec.title));
+    out.push_str("ax.grid(alpha=0.3)\nax.legend()\nfig.tight_layout()\nplt.show()\n");
+    out
+}
+
+/// Table for Origin: long names on the first line, then one row per value,
+/// with empty cells where a shorter column has run out or a value is missing
+pub fn origin_csv(columns: &[(String, Vec<f64>)]) -> String {
+    let mut out = String::new();
+    let names: Vec<String> = columns.iter().map(|(name, _)| csv_field(name)).collect();
+    let _ = writeln!(out, "{}", names.join(","));
+    let rows = columns
+        .iter()
+        .map(|(_, values)| values.len())
+        .max()
+        .unwrap_or(0);
+    for row in 0..rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|(_, values)| match values.get(row) {
+                Some(v) if v.is_finite() => v.to_string(),
+                _ => String::new(),
+            })
+            .collect();
+        let _ = writeln!(out, "{}", cells.join(","));
+    }
+    out
+}
+
+/// Reads rows the way the viewer does: at least two numbers, the first one
+/// numeric, fields split on whitespace and commas
+const LOADER: &str = r##"
+
+def load_columns(path, first, last):
+    """Numeric rows between two 1-based line numbers, as a list of columns"""
+    rows = []
+    with open(path) as f:
+        for number, line in enumerate(f, start=1):
+            text = line.strip()
+            if number < first or number > last or not text or text[0] in "#;":
+                continue
+            values = []
+            for field in re.split(r"[\s,]+", text):
+                try:
+                    values.append(float(field))
+                except ValueError:
+                    values.append(float("nan"))
+            numbers = [v for v in values if v == v]
+            if len(numbers) >= 2 and values[0] == values[0]:
+                rows.append(values)
+    width = max(len(row) for row in rows)
+    return [[row[i] if i < len(row) else float("nan") for row in rows] for i in range(width)]
+
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(label: &str) -> ScriptAxis {
+        ScriptAxis {
+            label: label.to_string(),
+            range: None,
+            scale: AxisScale::Linear,
+            linthresh: 1.0,
+        }
+    }
+
+    /// Two blocks of a waterfall, the second one current
+    fn spec() -> PlotSpec {
+        let block = |first_line, offset| ScriptBlock {
+            first_line,
+            last_line: first_line + 2,
+            rows: 3,
+            color: (255, 0, 0),
+            offset,
+        };
+        PlotSpec {
+            source: "it's \"run\" C:\\data.dat".to_string(),
+            blocks: vec![block(2, -5.0), block(6, 0.0)],
+            current: 1,
+            comma: false,
+            title: "Bob's \"scan\"".to_string(),
+            x_column: 0,
+            series: vec![ScriptSeries {
+                column: 1,
+                name: "I'm \"y\"".to_string(),
+                color: (0, 0, 255),
+            }],
+            y_error: None,
+            x_error: None,
+            style: PlotStyle::Line,
+            x: axis("x"),
+            y: axis("y"),
+            notes: Vec::new(),
+            written: "now".to_string(),
+        }
+    }
+
+    #[test]
+    fn strings_are_quoted_for_each_language() {
+        assert_eq!(gp_string("it's \"a\""), "'it''s \"a\"'");
+        assert_eq!(py_string("it's \"a\" \\"), "\"it's \\\"a\\\" \\\\\"");
+
+        let gnuplot = gnuplot(&spec());
+        assert!(gnuplot.contains("set title 'Bob''s \"scan\"' noenhanced"));
+        assert!(gnuplot.contains("title 'I''m \"y\"'"));
+        let python = matplotlib(&spec());
+        assert!(python.contains("SOURCE = \"it's \\\"run\\\" C:\\\\data.dat\""));
+        assert!(python.contains("label=\"I'm \\\"y\\\"\""));
+    }
+
+    #[test]
+    fn every_block_is_drawn_with_its_offset() {
+        let gnuplot = gnuplot(&spec());
+        assert!(gnuplot.contains(
+            "skip 1 every 1:1:0:0:2:0 using 1:($2+(-5)) with lines lc rgb '#ff0000' notitle"
+        ));
+        assert!(gnuplot.contains("skip 5 every 1:1:0:0:2:0 using 1:2 with lines lc rgb '#0000ff'"));
+
+        let python = matplotlib(&spec());
+        assert!(python.contains("BLOCKS = [(2, 4), (6, 8)]"));
+        assert!(python.contains("CURRENT = 1\n"));
+        assert!(python.contains(
+            "ax.plot(blocks[0][0], [v + (-5) for v in blocks[0][1]], \"-\", color=\"#ff0000\")"
+        ));
+    }
+
+    #[test]
+    fn overlaid_blocks_cover_their_own_line_ranges() {
+        // Three blocks of different lengths overlaid, the first one current
+        let mut spec = spec();
+        spec.blocks = [(3, 12, 10), (15, 34, 20), (40, 40, 1)]
+            .into_iter()
+            .map(|(first_line, last_line, rows)| ScriptBlock {
+                first_line,
+                last_line,
+                rows,
+                color: (0, 128, 0),
+                offset: 0.0,
+            })
+            .collect();
+        spec.current = 0;
+        spec.y_error = Some(2);
+
+        let gnuplot = gnuplot(&spec);
+        let plot = gnuplot.split("\nplot ").nth(1).unwrap();
+        let elements: Vec<&str> = plot.split(", \\\n     ").collect();
+        // Two other blocks, then the error bars and the series of the current one
+        assert_eq!(elements.len(), 4);
+        assert!(elements[0].contains("skip 14 every 1:1:0:0:19:0 using 1:2 with lines"));
+        assert!(elements[1].contains("skip 39 every 1:1:0:0:0:0 using 1:2 with lines"));
+        assert!(elements[2].contains("skip 2 every 1:1:0:0:9:0 using 1:2:3 with yerrorbars"));
+        assert!(elements[3].contains("skip 2 every 1:1:0:0:9:0 using 1:2 with lines"));
+        assert!(elements[3].contains("title 'I''m \"y\"'"));
+
+        let python = matplotlib(&spec);
+        assert!(python.contains("BLOCKS = [(3, 12), (15, 34), (40, 40)]"));
+        assert!(python.contains("CURRENT = 0\n"));
+        assert!(python.contains("ax.plot(blocks[1][0], blocks[1][1], \"-\", color=\"#008000\")"));
+        assert!(python.contains("ax.plot(blocks[2][0], blocks[2][1], "));
+        assert!(!python.contains("ax.plot(blocks[0]"));
+        assert!(python.contains("ax.errorbar(columns[0], columns[1], yerr=columns[2]"));
+    }
+
+    #[test]
+    fn log_and_symlog_axes_carry_over() {
+        let mut spec = spec();
+        spec.x = ScriptAxis {
+            range: Some([0.1, 100.0]),
+            scale: AxisScale::Log10,
+            ..axis("q")
+        };
+        spec.y = ScriptAxis {
+            scale: AxisScale::SymLog,
+            linthresh: 0.5,
+            ..axis("counts")
+        };
+
+        let gnuplot = gnuplot(&spec);
+        assert!(gnuplot.contains("set xrange [0.1:100]\nset logscale x 10\n"));
+        assert!(gnuplot.contains("# gnuplot has no symlog scale; y stays linear"));
+        assert!(!gnuplot.contains("set logscale y"));
+
+        let python = matplotlib(&spec);
+        assert!(python.contains("ax.set_xlim(0.1, 100)\nax.set_xscale(\"log\")\n"));
+        assert!(python.contains("ax.set_yscale(\"symlog\", linthresh=0.5)"));
+    }
+
+    #[test]
+    fn origin_table_leaves_missing_cells_empty() {
+        let table = origin_csv(&[
+            ("x, nm".to_string(), vec![1.0, 2.0, 3.0]),
+            ("y (block 2, +5)".to_string(), vec![0.5, f64::NAN]),
+        ]);
+        assert_eq!(table, "\"x, nm\",\"y (block 2, +5)\"\n1,0.5\n2,\n3,\n");
+    }
+}
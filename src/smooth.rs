@@ -0,0 +1,200 @@
+//! Smoothing filters for noisy series
+
+use crate::data::map_in_x_order;
+use crate::fit;
+
+/// Polynomial degree of the Savitzky-Golay filter
+const SAVGOL_DEGREE: usize = 2;
+
+/// Smallest and largest smoothing window, in points
+pub const MIN_WINDOW: usize = 3;
+pub const MAX_WINDOW: usize = 1001;
+
+/// Smoothing filter
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SmoothMethod {
+    /// Local quadratic least-squares fit, which keeps peak heights
+    SavitzkyGolay,
+    MovingAverage,
+    /// Gaussian-weighted average with the window spanning ±2σ
+    Gaussian,
+}
+
+impl SmoothMethod {
+    /// Cycle off → Savitzky-Golay → moving average → Gaussian → off
+    pub fn cycle(method: Option<SmoothMethod>) -> Option<SmoothMethod> {
+        match method {
+            None => Some(SmoothMethod::SavitzkyGolay),
+            Some(SmoothMethod::SavitzkyGolay) => Some(SmoothMethod::MovingAverage),
+            Some(SmoothMethod::MovingAverage) => Some(SmoothMethod::Gaussian),
+            Some(SmoothMethod::Gaussian) => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SmoothMethod::SavitzkyGolay => "Savitzky-Golay",
+            SmoothMethod::MovingAverage => "moving average",
+            SmoothMethod::Gaussian => "Gaussian",
+        }
+    }
+}
+
+/// Smoothed Y of every point of `data`, in the same order
+///
+/// `window` is the odd number of neighbouring points (in X order) each value
+/// is computed from.
+pub fn smooth(data: &[(f64, f64)], method: SmoothMethod, window: usize) -> Vec<f64> {
+    // Never wider than the data
+    let radius = (window / 2).min(data.len().saturating_sub(1) / 2);
+    map_in_x_order(data, |_, y| match method {
+        SmoothMethod::SavitzkyGolay => savitzky_golay(y, radius, SAVGOL_DEGREE),
+        SmoothMethod::MovingAverage => moving_average(y, radius),
+        SmoothMethod::Gaussian => gaussian(y, radius),
+    })
+}
+
+/// Centred moving average, with the window shrunk at the ends
+pub fn moving_average(y: &[f64], radius: usize) -> Vec<f64> {
+    let mut prefix = Vec::with_capacity(y.len() + 1);
+    prefix.push(0.0);
+    for v in y {
+        prefix.push(prefix.last().copied().unwrap_or(0.0) + v);
+    }
+    (0..y.len())
+        .map(|i| {
+            let lo = i.saturating_sub(radius);
+            let hi = (i + radius + 1).min(y.len());
+            (prefix[hi] - prefix[lo]) / (hi - lo) as f64
+        })
+        .collect()
+}
+
+/// Gaussian-weighted average, renormalized where the window is cut off at the ends
+fn gaussian(y: &[f64], radius: usize) -> Vec<f64> {
+    let sigma = (radius as f64 / 2.0).max(0.5);
+    let kernel: Vec<f64> = (0..=radius)
+        .map(|k| (-0.5 * (k as f64 / sigma).powi(2)).exp())
+        .collect();
+    (0..y.len())
+        .map(|i| {
+            let lo = i.saturating_sub(radius);
+            let hi = (i + radius + 1).min(y.len());
+            let (sum, weight) = (lo..hi).fold((0.0, 0.0), |(sum, weight), j| {
+                let w = kernel[i.abs_diff(j)];
+                (sum + w * y[j], weight + w)
+            });
+            sum / weight
+        })
+        .collect()
+}
+
+/// Savitzky-Golay filter; near the ends the polynomial is fitted to the first
+/// or last full window and evaluated off-centre
+fn savitzky_golay(y: &[f64], radius: usize, degree: usize) -> Vec<f64> {
+    let n = y.len();
+    let window = 2 * radius + 1;
+    if n < window || window <= degree {
+        return y.to_vec();
+    }
+    let centre_weights = savgol_weights(-(radius as isize), window, degree);
+    (0..n)
+        .map(|i| {
+            let start = i.saturating_sub(radius).min(n - window);
+            let edge_weights;
+            let weights = if start + radius == i {
+                &centre_weights
+            } else {
+                edge_weights = savgol_weights(start as isize - i as isize, window, degree);
+                &edge_weights
+            };
+            weights
+                .iter()
+                .zip(&y[start..start + window])
+                .map(|(w, v)| w * v)
+                .sum()
+        })
+        .collect()
+}
+
+/// Weights giving the value at offset 0 of a least-squares polynomial fitted
+/// to `window` points starting at offset `first`
+fn savgol_weights(first: isize, window: usize, degree: usize) -> Vec<f64> {
+    let offsets: Vec<f64> = (0..window).map(|k| (first + k as isize) as f64).collect();
+    let m = degree + 1;
+
+    // Row 0 of (AᵀA)⁻¹ from solving (AᵀA) a = e₀
+    let mut normal = vec![vec![0.0; m]; m];
+    for &t in &offsets {
+        for (i, row) in normal.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value += t.powi((i + j) as i32);
+            }
+        }
+    }
+    let mut unit = vec![0.0; m];
+    unit[0] = 1.0;
+    let Some(a) = fit::solve(normal, unit) else {
+        return vec![1.0 / window as f64; window];
+    };
+    offsets
+        .iter()
+        .map(|&t| {
+            a.iter()
+                .enumerate()
+                .map(|(k, c)| c * t.powi(k as i32))
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampled(f: impl Fn(f64) -> f64) -> Vec<(f64, f64)> {
+        (0..60)
+            .map(|i| {
+                let x = i as f64 * 0.25 - 5.0;
+                (x, f(x))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn savitzky_golay_preserves_a_cubic_away_from_the_ends() {
+        let cubic = |x: f64| 0.5 * x.powi(3) - 2.0 * x * x + x + 3.0;
+        let data = sampled(cubic);
+        let window = 11;
+        let smoothed = smooth(&data, SmoothMethod::SavitzkyGolay, window);
+        // A symmetric window's quadratic fit is exact for odd terms at its centre
+        for (i, (&(x, _), y)) in data.iter().zip(&smoothed).enumerate() {
+            if i >= window / 2 && i + window / 2 < data.len() {
+                assert!((y - cubic(x)).abs() < 1e-9, "x = {}", x);
+            }
+        }
+        // A moving average does not keep the curvature
+        let averaged = smooth(&data, SmoothMethod::MovingAverage, window);
+        assert!((averaged[30] - cubic(data[30].0)).abs() > 0.1);
+    }
+
+    #[test]
+    fn savitzky_golay_keeps_a_quadratic_up_to_the_ends() {
+        let quadratic = |x: f64| 3.0 * x * x - x + 2.0;
+        // Reversed X order comes back in the input order
+        let data: Vec<(f64, f64)> = sampled(quadratic).into_iter().rev().collect();
+        let smoothed = smooth(&data, SmoothMethod::SavitzkyGolay, 9);
+        for (&(x, _), y) in data.iter().zip(&smoothed) {
+            assert!((y - quadratic(x)).abs() < 1e-9, "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn averages_leave_constants_alone() {
+        let data = sampled(|_| 4.0);
+        for method in [SmoothMethod::MovingAverage, SmoothMethod::Gaussian] {
+            let smoothed = smooth(&data, method, 7);
+            assert!(smoothed.iter().all(|y| (y - 4.0).abs() < 1e-12));
+        }
+    }
+}
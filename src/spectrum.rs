@@ -0,0 +1,226 @@
+//! Frequency spectrum of evenly spaced samples
+
+use std::f64::consts::PI;
+
+use crate::data::uniform_step;
+use crate::peaks::{self, PeakParams};
+
+/// Number of dominant frequencies reported
+const DOMINANT_COUNT: usize = 5;
+
+/// Window function applied before the transform
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Window {
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    pub fn next(self) -> Window {
+        match self {
+            Window::Rectangular => Window::Hann,
+            Window::Hann => Window::Hamming,
+            Window::Hamming => Window::Blackman,
+            Window::Blackman => Window::Rectangular,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Window::Rectangular => "rectangular",
+            Window::Hann => "Hann",
+            Window::Hamming => "Hamming",
+            Window::Blackman => "Blackman",
+        }
+    }
+
+    /// Weight of sample `i` of `n`
+    fn weight(self, i: usize, n: usize) -> f64 {
+        if n < 2 {
+            return 1.0;
+        }
+        let phase = 2.0 * PI * i as f64 / (n - 1) as f64;
+        match self {
+            Window::Rectangular => 1.0,
+            Window::Hann => 0.5 - 0.5 * phase.cos(),
+            Window::Hamming => 0.54 - 0.46 * phase.cos(),
+            Window::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+        }
+    }
+}
+
+/// Single-sided spectrum of a series
+pub struct Spectrum {
+    /// (frequency, amplitude or power) from 0 up to the Nyquist frequency
+    pub points: Vec<(f64, f64)>,
+    /// Strongest spectral peaks as (frequency, value), strongest first
+    pub dominant: Vec<(f64, f64)>,
+    pub sample_step: f64,
+    pub samples: usize,
+    /// Transform length after zero padding
+    pub fft_len: usize,
+}
+
+/// Amplitude (or power, its square) spectrum of `data` after removing the mean
+///
+/// Samples are zero-padded to a power of two. Fails when X is not evenly spaced.
+pub fn compute(data: &[(f64, f64)], window: Window, power: bool) -> Result<Spectrum, String> {
+    if data.len() < 4 {
+        return Err("Spectrum needs at least 4 points".to_string());
+    }
+    let mut samples = data.to_vec();
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let x: Vec<f64> = samples.iter().map(|p| p.0).collect();
+    let Some(step) = uniform_step(&x) else {
+        return Err("Spectrum needs evenly spaced X values".to_string());
+    };
+
+    let n = samples.len();
+    let mean = samples.iter().map(|p| p.1).sum::<f64>() / n as f64;
+    let fft_len = n.next_power_of_two();
+    let mut re = vec![0.0; fft_len];
+    let mut im = vec![0.0; fft_len];
+    let mut window_sum = 0.0;
+    for (i, p) in samples.iter().enumerate() {
+        let w = window.weight(i, n);
+        re[i] = (p.1 - mean) * w;
+        window_sum += w;
+    }
+    fft(&mut re, &mut im);
+
+    // Amplitude of a sine at a bin centre, independent of window and padding
+    let points: Vec<(f64, f64)> = (0..=fft_len / 2)
+        .map(|k| {
+            let scale = if k == 0 || k == fft_len / 2 { 1.0 } else { 2.0 };
+            let amplitude = scale * re[k].hypot(im[k]) / window_sum;
+            let frequency = k as f64 / (fft_len as f64 * step);
+            (
+                frequency,
+                if power {
+                    amplitude * amplitude
+                } else {
+                    amplitude
+                },
+            )
+        })
+        .collect();
+
+    let params = PeakParams {
+        prominence: 0.01,
+        min_distance: 1,
+        threshold: 0.0,
+    };
+    let mut dominant: Vec<(f64, f64)> = peaks::find_peaks(&points, &params)
+        .iter()
+        .map(|p| (p.x, p.height))
+        .collect();
+    dominant.sort_by(|a, b| b.1.total_cmp(&a.1));
+    dominant.truncate(DOMINANT_COUNT);
+
+    Ok(Spectrum {
+        points,
+        dominant,
+        sample_step: step,
+        samples: n,
+        fft_len,
+    })
+}
+
+/// In-place iterative radix-2 FFT; the length must be a power of two
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` samples at step `step` of an offset sine
+    fn sine(n: usize, step: f64, frequency: f64, amplitude: f64) -> Vec<(f64, f64)> {
+        (0..n)
+            .map(|i| {
+                let x = i as f64 * step;
+                (x, 3.0 + amplitude * (2.0 * PI * frequency * x).sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sine_at_a_bin_centre_gives_its_frequency_and_amplitude() {
+        // Bin 8 of 256 samples at 0.01 s is 3.125 Hz
+        let data = sine(256, 0.01, 3.125, 2.0);
+        // The symmetric Hann window leaks a little even at a bin centre
+        for (window, tolerance) in [(Window::Rectangular, 1e-9), (Window::Hann, 1e-4)] {
+            let spectrum = compute(&data, window, false).unwrap();
+            let (frequency, amplitude) = spectrum.dominant[0];
+            assert!((frequency - 3.125).abs() < 1e-9, "{}", window.name());
+            assert!((amplitude - 2.0).abs() < tolerance, "{}", window.name());
+            // The mean is removed before the transform
+            assert!(spectrum.points[0].1.abs() < tolerance);
+        }
+        let power = compute(&data, Window::Rectangular, true).unwrap();
+        assert!((power.dominant[0].1 - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fft_matches_the_direct_transform() {
+        let input: Vec<f64> = (0..8).map(|i| ((i * i) % 5) as f64 - 1.5).collect();
+        let (mut re, mut im) = (input.clone(), vec![0.0; 8]);
+        fft(&mut re, &mut im);
+        for k in 0..8 {
+            let (mut sum_re, mut sum_im) = (0.0, 0.0);
+            for (j, value) in input.iter().enumerate() {
+                let phase = -2.0 * PI * (j * k) as f64 / 8.0;
+                sum_re += value * phase.cos();
+                sum_im += value * phase.sin();
+            }
+            assert!((re[k] - sum_re).abs() < 1e-9 && (im[k] - sum_im).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn padding_and_uneven_steps() {
+        let spectrum = compute(&sine(100, 0.5, 0.1, 1.0), Window::Hann, false).unwrap();
+        assert_eq!((spectrum.samples, spectrum.fft_len), (100, 128));
+        assert_eq!(spectrum.points.len(), 65);
+        // Nyquist frequency is half the sampling rate
+        assert!((spectrum.points[64].0 - 1.0).abs() < 1e-12);
+        let uneven = [(0.0, 1.0), (1.0, 2.0), (3.0, 1.0), (4.0, 0.0)];
+        assert!(compute(&uneven, Window::Hann, false).is_err());
+    }
+}
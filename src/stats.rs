@@ -0,0 +1,120 @@
+//! Descriptive statistics of data columns
+
+use crate::data::uniform_step;
+
+/// Spacing between consecutive sorted values of a column
+#[derive(Clone, Copy)]
+pub enum Spacing {
+    Uniform(f64),
+    Varying { min: f64, max: f64 },
+}
+
+/// Summary of one numeric column
+#[derive(Clone, Copy)]
+pub struct ColumnStats {
+    /// Finite values
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// Sample standard deviation
+    pub std_dev: f64,
+    pub sum: f64,
+    /// NaN or infinite values written in the file
+    pub nan: usize,
+    /// Fields that failed to parse or were missing
+    pub unparsed: usize,
+    /// `None` with fewer than two distinct values
+    pub spacing: Option<Spacing>,
+}
+
+/// Inverse-variance weighted mean of values with standard errors
+pub struct WeightedMean {
+    pub mean: f64,
+    /// Standard error of the mean, 1/√Σw
+    pub error: f64,
+    /// χ² of the values about the mean per degree of freedom
+    pub reduced_chi2: f64,
+    /// Values with a positive error
+    pub count: usize,
+}
+
+/// Weighted mean of the finite `values` whose matching `errors` are positive
+pub fn weighted_mean(values: &[f64], errors: &[f64]) -> Option<WeightedMean> {
+    let pairs: Vec<(f64, f64)> = values
+        .iter()
+        .zip(errors)
+        .filter(|&(v, e)| v.is_finite() && e.is_finite() && *e > 0.0)
+        .map(|(&v, &e)| (v, 1.0 / (e * e)))
+        .collect();
+    if pairs.is_empty() {
+        return None;
+    }
+    let weight_sum: f64 = pairs.iter().map(|p| p.1).sum();
+    let mean = pairs.iter().map(|(v, w)| w * v).sum::<f64>() / weight_sum;
+    let chi2: f64 = pairs.iter().map(|(v, w)| w * (v - mean).powi(2)).sum();
+    let reduced_chi2 = if pairs.len() > 1 {
+        chi2 / (pairs.len() - 1) as f64
+    } else {
+        f64::NAN
+    };
+    Some(WeightedMean {
+        mean,
+        error: weight_sum.sqrt().recip(),
+        reduced_chi2,
+        count: pairs.len(),
+    })
+}
+
+/// Statistics of `values`, where `unparsed` of the NaNs stand for fields that
+/// did not parse
+pub fn describe(values: &[f64], unparsed: usize) -> ColumnStats {
+    let mut finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    finite.sort_by(f64::total_cmp);
+    let count = finite.len();
+    let nan = (values.len() - count).saturating_sub(unparsed);
+
+    let sum: f64 = finite.iter().sum();
+    let mean = if count > 0 {
+        sum / count as f64
+    } else {
+        f64::NAN
+    };
+    let median = match count {
+        0 => f64::NAN,
+        n if n % 2 == 1 => finite[n / 2],
+        n => (finite[n / 2 - 1] + finite[n / 2]) / 2.0,
+    };
+    let std_dev = if count > 1 {
+        let squares: f64 = finite.iter().map(|v| (v - mean).powi(2)).sum();
+        (squares / (count - 1) as f64).sqrt()
+    } else {
+        f64::NAN
+    };
+
+    finite.dedup();
+    let spacing = (finite.len() >= 2).then(|| match uniform_step(&finite) {
+        Some(step) => Spacing::Uniform(step),
+        None => {
+            let steps = finite.windows(2).map(|w| w[1] - w[0]);
+            Spacing::Varying {
+                min: steps.clone().fold(f64::INFINITY, f64::min),
+                max: steps.fold(f64::NEG_INFINITY, f64::max),
+            }
+        }
+    });
+
+    ColumnStats {
+        count,
+        min: finite.first().copied().unwrap_or(f64::NAN),
+        max: finite.last().copied().unwrap_or(f64::NAN),
+        mean,
+        median,
+        std_dev,
+        sum,
+        nan,
+        unparsed,
+        spacing,
+    }
+}
@@ -0,0 +1,184 @@
+//! Derivatives and integrals of a series
+
+use crate::data::map_in_x_order;
+
+/// Transform applied to the processed primary series
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Derivative,
+    SecondDerivative,
+    /// Running trapezoid integral from the first point
+    Integral,
+}
+
+impl Transform {
+    /// Cycle off → d/dx → d²/dx² → ∫ → off
+    pub fn cycle(transform: Option<Transform>) -> Option<Transform> {
+        match transform {
+            None => Some(Transform::Derivative),
+            Some(Transform::Derivative) => Some(Transform::SecondDerivative),
+            Some(Transform::SecondDerivative) => Some(Transform::Integral),
+            Some(Transform::Integral) => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Transform::Derivative => "first derivative",
+            Transform::SecondDerivative => "second derivative",
+            Transform::Integral => "cumulative integral",
+        }
+    }
+
+    /// Quantity label, e.g. `d(y)/d(x)`
+    pub fn label(self, y: &str, x: &str) -> String {
+        match self {
+            Transform::Derivative => format!("d({})/d({})", y, x),
+            Transform::SecondDerivative => format!("d²({})/d({})²", y, x),
+            Transform::Integral => format!("∫{} d({})", y, x),
+        }
+    }
+
+    /// Transformed Y of every point of `data`, in the same order
+    pub fn apply(self, data: &[(f64, f64)]) -> Vec<f64> {
+        map_in_x_order(data, |x, y| match self {
+            Transform::Derivative => derivative(x, y),
+            Transform::SecondDerivative => second_derivative(x, y),
+            Transform::Integral => cumulative_integral(x, y),
+        })
+    }
+}
+
+/// Slope, or 0 where two points share an X value
+fn slope(x0: f64, y0: f64, x1: f64, y1: f64) -> f64 {
+    if x1 != x0 { (y1 - y0) / (x1 - x0) } else { 0.0 }
+}
+
+/// Central differences inside, one-sided differences at the ends
+fn derivative(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    (0..n)
+        .map(|i| {
+            let lo = i.saturating_sub(1);
+            let hi = (i + 1).min(n - 1);
+            slope(x[lo], y[lo], x[hi], y[hi])
+        })
+        .collect()
+}
+
+/// Three-point second difference for uneven spacing, repeated at the ends
+fn second_derivative(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    if n < 3 {
+        return vec![0.0; n];
+    }
+    let inner = |i: usize| {
+        let span = x[i + 1] - x[i - 1];
+        if span == 0.0 {
+            return 0.0;
+        }
+        let right = slope(x[i], y[i], x[i + 1], y[i + 1]);
+        let left = slope(x[i - 1], y[i - 1], x[i], y[i]);
+        2.0 * (right - left) / span
+    };
+    (0..n).map(|i| inner(i.clamp(1, n - 2))).collect()
+}
+
+fn cumulative_integral(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let mut total = 0.0;
+    let mut out = Vec::with_capacity(x.len());
+    for i in 0..x.len() {
+        if i > 0 {
+            total += (x[i] - x[i - 1]) * (y[i] + y[i - 1]) / 2.0;
+        }
+        out.push(total);
+    }
+    out
+}
+
+/// Trapezoid integral of `data` between `a` and `b`, interpolating at the limits
+///
+/// Swapped limits give the same (positive direction) result.
+pub fn integrate(data: &[(f64, f64)], a: f64, b: f64) -> Option<f64> {
+    let (lo, hi) = (a.min(b), a.max(b));
+    let mut points = data.to_vec();
+    points.sort_by(|p, q| p.0.total_cmp(&q.0));
+    let first = points.first()?;
+    let last = points.last()?;
+    if hi < first.0 || lo > last.0 {
+        return None;
+    }
+
+    let mut total = 0.0;
+    for w in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+        let (s0, s1) = (x0.max(lo), x1.min(hi));
+        if s1 <= s0 {
+            continue;
+        }
+        let at = |x: f64| y0 + slope(x0, y0, x1, y1) * (x - x0);
+        total += (s1 - s0) * (at(s0) + at(s1)) / 2.0;
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// sin x on an uneven grid
+    fn sine() -> Vec<(f64, f64)> {
+        (0..=400)
+            .map(|i| {
+                let t = i as f64 / 400.0;
+                let x = 6.0 * (t + 0.1 * t * t);
+                (x, x.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn integral_of_the_derivative_recovers_the_data() {
+        let data = sine();
+        let slope = Transform::Derivative.apply(&data);
+        assert!((slope[200] - data[200].0.cos()).abs() < 1e-3);
+        let derivative: Vec<(f64, f64)> = data.iter().zip(&slope).map(|(p, &d)| (p.0, d)).collect();
+        let integral = Transform::Integral.apply(&derivative);
+        let y0 = data[0].1;
+        for (p, v) in data.iter().zip(&integral) {
+            assert!((v + y0 - p.1).abs() < 1e-3, "x = {}", p.0);
+        }
+    }
+
+    #[test]
+    fn derivative_of_the_integral_recovers_the_data() {
+        let data = sine();
+        let integral = Transform::Integral.apply(&data);
+        assert_eq!(integral[0], 0.0);
+        let cumulative: Vec<(f64, f64)> =
+            data.iter().zip(&integral).map(|(p, &v)| (p.0, v)).collect();
+        let derivative = Transform::Derivative.apply(&cumulative);
+        // One-sided differences at the ends are only first order
+        for (p, d) in data.iter().zip(&derivative).skip(1).take(data.len() - 2) {
+            assert!((d - p.1).abs() < 1e-3, "x = {}", p.0);
+        }
+    }
+
+    #[test]
+    fn second_derivative_of_a_parabola_is_constant() {
+        let data: Vec<(f64, f64)> = [0.0, 0.5, 1.5, 2.0, 3.5, 4.0]
+            .iter()
+            .map(|&x| (x, 1.5 * x * x - x))
+            .collect();
+        let curvature = Transform::SecondDerivative.apply(&data);
+        assert!(curvature.iter().all(|c| (c - 3.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn integrate_between_limits_interpolates() {
+        let line = [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)];
+        assert!((integrate(&line, 0.5, 1.5).unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(integrate(&line, 1.5, 0.5), integrate(&line, 0.5, 1.5));
+        assert!(integrate(&line, 3.0, 4.0).is_none());
+    }
+}
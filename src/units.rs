@@ -0,0 +1,304 @@
+//! X unit conversions for spectroscopy and diffraction
+
+use std::f64::consts::PI;
+
+/// hc in eV·cm, turning wavenumbers into photon energies
+const EV_PER_WAVENUMBER: f64 = 1.239_841_98e-4;
+
+/// Default X-ray wavelength: Cu Kα1, in Å
+pub const DEFAULT_WAVELENGTH: f64 = 1.5406;
+
+/// Kα1 wavelengths of common X-ray anodes, in Å
+const ANODES: [(&str, f64); 6] = [
+    ("cu", 1.5406),
+    ("mo", 0.70930),
+    ("co", 1.78897),
+    ("cr", 2.28970),
+    ("fe", 1.93604),
+    ("ag", 0.55941),
+];
+
+/// Physical unit of an X column
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum XUnit {
+    /// Wavelength in nm
+    Nanometre,
+    /// Wavenumber in cm⁻¹
+    Wavenumber,
+    /// Photon energy in eV
+    ElectronVolt,
+    /// Scattering angle 2θ in degrees
+    TwoTheta,
+    /// Lattice spacing d in Å
+    DSpacing,
+    /// Scattering vector magnitude Q in Å⁻¹
+    Q,
+}
+
+impl XUnit {
+    /// Cycle off → nm → cm⁻¹ → eV → 2θ → d → Q → off
+    pub fn cycle(unit: Option<XUnit>) -> Option<XUnit> {
+        match unit {
+            None => Some(XUnit::Nanometre),
+            Some(XUnit::Nanometre) => Some(XUnit::Wavenumber),
+            Some(XUnit::Wavenumber) => Some(XUnit::ElectronVolt),
+            Some(XUnit::ElectronVolt) => Some(XUnit::TwoTheta),
+            Some(XUnit::TwoTheta) => Some(XUnit::DSpacing),
+            Some(XUnit::DSpacing) => Some(XUnit::Q),
+            Some(XUnit::Q) => None,
+        }
+    }
+
+    /// Next unit of the same family, wrapping back to the first
+    pub fn next_in_family(self) -> XUnit {
+        match self {
+            XUnit::Nanometre => XUnit::Wavenumber,
+            XUnit::Wavenumber => XUnit::ElectronVolt,
+            XUnit::ElectronVolt => XUnit::Nanometre,
+            XUnit::TwoTheta => XUnit::DSpacing,
+            XUnit::DSpacing => XUnit::Q,
+            XUnit::Q => XUnit::TwoTheta,
+        }
+    }
+
+    pub fn quantity(self) -> &'static str {
+        match self {
+            XUnit::Nanometre => "wavelength",
+            XUnit::Wavenumber => "wavenumber",
+            XUnit::ElectronVolt => "energy",
+            XUnit::TwoTheta => "2θ",
+            XUnit::DSpacing => "d",
+            XUnit::Q => "Q",
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            XUnit::Nanometre => "nm",
+            XUnit::Wavenumber => "cm⁻¹",
+            XUnit::ElectronVolt => "eV",
+            XUnit::TwoTheta => "°",
+            XUnit::DSpacing => "Å",
+            XUnit::Q => "Å⁻¹",
+        }
+    }
+
+    /// Diffraction units convert through the X-ray wavelength
+    pub fn needs_wavelength(self) -> bool {
+        matches!(self, XUnit::TwoTheta | XUnit::DSpacing | XUnit::Q)
+    }
+
+    /// Common quantity of the family: wavenumber (cm⁻¹) or Q (Å⁻¹)
+    fn common_value(self, x: f64, wavelength: f64) -> Option<f64> {
+        let value = match self {
+            XUnit::Nanometre => 1e7 / x,
+            XUnit::Wavenumber | XUnit::Q => x,
+            XUnit::ElectronVolt => x / EV_PER_WAVENUMBER,
+            XUnit::TwoTheta => {
+                if x <= 0.0 || x >= 180.0 {
+                    return None;
+                }
+                4.0 * PI * (x.to_radians() / 2.0).sin() / wavelength
+            }
+            XUnit::DSpacing => 2.0 * PI / x,
+        };
+        (value.is_finite() && value > 0.0).then_some(value)
+    }
+
+    fn value_from_common(self, c: f64, wavelength: f64) -> Option<f64> {
+        let value = match self {
+            XUnit::Nanometre => 1e7 / c,
+            XUnit::Wavenumber | XUnit::Q => c,
+            XUnit::ElectronVolt => c * EV_PER_WAVENUMBER,
+            XUnit::TwoTheta => {
+                let sine = c * wavelength / (4.0 * PI);
+                if sine > 1.0 {
+                    return None;
+                }
+                2.0 * sine.asin().to_degrees()
+            }
+            XUnit::DSpacing => 2.0 * PI / c,
+        };
+        value.is_finite().then_some(value)
+    }
+
+    /// Magnitude of d(common)/dx at `x`
+    fn common_slope(self, x: f64, wavelength: f64) -> f64 {
+        match self {
+            XUnit::Nanometre => 1e7 / (x * x),
+            XUnit::Wavenumber | XUnit::Q => 1.0,
+            XUnit::ElectronVolt => 1.0 / EV_PER_WAVENUMBER,
+            XUnit::TwoTheta => 2.0 * PI * (x.to_radians() / 2.0).cos() * PI / 180.0 / wavelength,
+            XUnit::DSpacing => 2.0 * PI / (x * x),
+        }
+    }
+}
+
+/// Source index and |dx/dx'| of a converted point
+pub type Kept = (usize, f64);
+
+/// Conversion of X values between two units of one family
+#[derive(Clone, Copy)]
+pub struct Conversion {
+    pub from: XUnit,
+    pub to: XUnit,
+    /// X-ray wavelength in Å, for diffraction units
+    pub wavelength: f64,
+}
+
+impl Conversion {
+    /// Converted X, or `None` where `x` has no counterpart (such as 2θ ≥ 180°)
+    pub fn apply(&self, x: f64) -> Option<f64> {
+        let common = self.from.common_value(x, self.wavelength)?;
+        self.to.value_from_common(common, self.wavelength)
+    }
+
+    /// |dx/dx'| at `x`, turning a density per unit of x into one per unit of x'
+    pub fn jacobian(&self, x: f64) -> Option<f64> {
+        let converted = self.apply(x)?;
+        let factor = self.to.common_slope(converted, self.wavelength)
+            / self.from.common_slope(x, self.wavelength);
+        factor.is_finite().then_some(factor)
+    }
+
+    /// Converted points, dropping those without a counterpart, with the source
+    /// index and |dx/dx'| of each kept point; Y is multiplied by |dx/dx'|
+    /// when `rescale` is set
+    pub fn convert_points(
+        &self,
+        points: &[(f64, f64)],
+        rescale: bool,
+    ) -> (Vec<(f64, f64)>, Vec<Kept>) {
+        points
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(x, y))| {
+                let jacobian = self.jacobian(x)?;
+                let y = if rescale { y * jacobian } else { y };
+                Some(((self.apply(x)?, y), (i, jacobian)))
+            })
+            .unzip()
+    }
+
+    /// Derivative label such as `|dλ/dν̃|`
+    pub fn jacobian_label(&self) -> String {
+        format!("|d{}/d{}|", symbol_of(self.from), symbol_of(self.to))
+    }
+}
+
+/// Single-letter symbol of a unit's quantity
+fn symbol_of(unit: XUnit) -> &'static str {
+    match unit {
+        XUnit::Nanometre => "λ",
+        XUnit::Wavenumber => "ν̃",
+        XUnit::ElectronVolt => "E",
+        XUnit::TwoTheta => "2θ",
+        XUnit::DSpacing => "d",
+        XUnit::Q => "Q",
+    }
+}
+
+/// Wavelength in Å from a number or an anode symbol such as `Cu`
+pub fn parse_wavelength(input: &str) -> Option<f64> {
+    let input = input.trim();
+    let lower = input.to_lowercase();
+    if let Some(&(_, wavelength)) = ANODES.iter().find(|(name, _)| *name == lower) {
+        return Some(wavelength);
+    }
+    input
+        .parse::<f64>()
+        .ok()
+        .filter(|w| w.is_finite() && *w > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversion(from: XUnit, to: XUnit) -> Conversion {
+        Conversion {
+            from,
+            to,
+            wavelength: DEFAULT_WAVELENGTH,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    #[test]
+    fn known_values() {
+        let nm_to_wavenumber = conversion(XUnit::Nanometre, XUnit::Wavenumber);
+        assert!(close(nm_to_wavenumber.apply(500.0).unwrap(), 20000.0));
+        let nm_to_ev = conversion(XUnit::Nanometre, XUnit::ElectronVolt);
+        assert!((nm_to_ev.apply(1239.84198).unwrap() - 1.0).abs() < 1e-6);
+        // Bragg's law: λ = 2d sin θ
+        let d = conversion(XUnit::TwoTheta, XUnit::DSpacing)
+            .apply(30.0)
+            .unwrap();
+        assert!(close(
+            d,
+            DEFAULT_WAVELENGTH / (2.0 * 15f64.to_radians().sin())
+        ));
+        let q = conversion(XUnit::DSpacing, XUnit::Q).apply(2.0).unwrap();
+        assert!(close(q, PI));
+        assert!(conversion(XUnit::TwoTheta, XUnit::Q).apply(180.0).is_none());
+        assert!(conversion(XUnit::Q, XUnit::TwoTheta).apply(10.0).is_none());
+    }
+
+    #[test]
+    fn round_trips() {
+        for (from, to, x) in [
+            (XUnit::Nanometre, XUnit::ElectronVolt, 632.8),
+            (XUnit::Wavenumber, XUnit::Nanometre, 1550.0),
+            (XUnit::TwoTheta, XUnit::Q, 44.5),
+            (XUnit::DSpacing, XUnit::TwoTheta, 2.1),
+        ] {
+            let there = conversion(from, to).apply(x).unwrap();
+            assert!(close(conversion(to, from).apply(there).unwrap(), x));
+        }
+    }
+
+    #[test]
+    fn jacobian_is_the_derivative() {
+        for (from, to, x) in [
+            (XUnit::Nanometre, XUnit::Wavenumber, 500.0),
+            (XUnit::Nanometre, XUnit::ElectronVolt, 400.0),
+            (XUnit::TwoTheta, XUnit::DSpacing, 40.0),
+            (XUnit::Q, XUnit::TwoTheta, 3.0),
+        ] {
+            let c = conversion(from, to);
+            let h = x * 1e-6;
+            let slope = (c.apply(x + h).unwrap() - c.apply(x - h).unwrap()) / (2.0 * h);
+            let jacobian = c.jacobian(x).unwrap();
+            assert!((jacobian * slope.abs() - 1.0).abs() < 1e-6);
+        }
+        // |dλ/dν̃| = λ² / 10⁷ for λ in nm
+        let c = conversion(XUnit::Nanometre, XUnit::Wavenumber);
+        assert!(close(c.jacobian(500.0).unwrap(), 500.0 * 500.0 / 1e7));
+    }
+
+    #[test]
+    fn rescaled_points_keep_their_integral() {
+        // A Gaussian band in nm, integrated before and after conversion
+        let points: Vec<(f64, f64)> = (0..=4000)
+            .map(|i| {
+                let x = 400.0 + i as f64 * 0.05;
+                (x, (-((x - 500.0) / 10.0).powi(2)).exp())
+            })
+            .collect();
+        let area = |points: &[(f64, f64)]| -> f64 {
+            points
+                .windows(2)
+                .map(|w| (w[1].0 - w[0].0).abs() * (w[0].1 + w[1].1) / 2.0)
+                .sum()
+        };
+        let c = conversion(XUnit::Nanometre, XUnit::Wavenumber);
+        let (converted, kept) = c.convert_points(&points, true);
+        assert_eq!(kept.len(), points.len());
+        assert!((area(&converted) / area(&points) - 1.0).abs() < 1e-4);
+        let (unscaled, _) = c.convert_points(&points, false);
+        assert_eq!(unscaled[0].1, points[0].1);
+    }
+}